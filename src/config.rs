@@ -0,0 +1,453 @@
+//! Configuration sources beyond the command line. Every flag can also be
+//! set by an `SRT_WHEP_<ID>` environment variable (`SRT_WHEP_OFFER_TIMEOUT_SEC`
+//! for `--offer-timeout-sec`) or in the `--config` file, with precedence
+//! CLI > environment > file > built-in defaults.
+//!
+//! The file is TOML; a `.yaml`/`.yml` name is refused rather than misread.
+//! Keys are the flags' ids in snake_case, grouped into one table per
+//! section:
+//!
+//! ```toml
+//! [pipeline]
+//! input_address = "127.0.0.1:1234"
+//!
+//! [coordinator]
+//! offer_timeout_sec = 10
+//!
+//! [webhooks]
+//! webhook_urls = ["https://hooks.example.com/srt-whep"]
+//! ```
+//!
+//! File settings become the flags' defaults, which is what puts them below
+//! the environment and the command line. The file is read at startup and
+//! again on SIGHUP, when the `[coordinator]` settings are applied to the
+//! running coordinator.
+use crate::routes::{AdminArgs, PublishArgs};
+use crate::signal::CoordinatorArgs;
+use crate::startup::{PushArgs, ShutdownArgs, SupervisorArgs};
+use crate::stream::Args as PipelineArgs;
+use crate::telemetry::TelemetryArgs;
+use crate::webhooks::WebhookArgs;
+use clap::{Arg, ArgAction, ArgMatches, Args, Command, CommandFactory, FromArgMatches};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Prefix of the environment variable for each flag.
+pub const ENV_PREFIX: &str = "SRT_WHEP_";
+
+/// The id of the flag naming the config file.
+const CONFIG_ID: &str = "config";
+
+/// Flags whose values `--print-config` must not reveal.
+const SECRETS: &[&str] = &[
+    "admin_token",
+    "whip_publisher_token",
+    "whep_input_token",
+    "srt_passphrase",
+    // A streamid is a publisher's credential.
+    "srt_streamid",
+    // Its SRT URIs may carry a passphrase.
+    "output_address",
+    "push_tokens",
+    "webhook_secret",
+];
+
+const REDACTED: &str = "<redacted>";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Could not read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Could not parse config file: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Config file {0} looks like YAML; only TOML is supported")]
+    NotToml(PathBuf),
+    #[error("Unknown config section [{0}]")]
+    UnknownSection(String),
+    #[error("Unknown config key `{key}` in [{section}]")]
+    UnknownKey { section: String, key: String },
+    #[error("Config key `{key}` in [{section}] must be a scalar, or a list for a repeatable flag")]
+    InvalidValue { section: String, key: String },
+    /// The command line (or a value from the environment or the file) did
+    /// not parse; also how `--help` and `--version` surface.
+    #[error(transparent)]
+    Cli(#[from] clap::Error),
+}
+
+/// The sections a config file may have, each with the flags it may set.
+fn sections() -> Vec<(&'static str, Command)> {
+    vec![
+        (
+            "pipeline",
+            PipelineArgs::augment_args(Command::new("pipeline")),
+        ),
+        (
+            "coordinator",
+            CoordinatorArgs::augment_args(Command::new("coordinator")),
+        ),
+        (
+            "supervisor",
+            SupervisorArgs::augment_args(Command::new("supervisor")),
+        ),
+        ("admin", AdminArgs::augment_args(Command::new("admin"))),
+        (
+            "shutdown",
+            ShutdownArgs::augment_args(Command::new("shutdown")),
+        ),
+        (
+            "publish",
+            PublishArgs::augment_args(Command::new("publish")),
+        ),
+        ("push", PushArgs::augment_args(Command::new("push"))),
+        (
+            "webhooks",
+            WebhookArgs::augment_args(Command::new("webhooks")),
+        ),
+        (
+            "telemetry",
+            TelemetryArgs::augment_args(Command::new("telemetry")),
+        ),
+    ]
+}
+
+/// Whether the flag takes one value per occurrence, e.g. `--webhook-url`.
+fn is_repeatable(arg: &Arg) -> bool {
+    matches!(arg.get_action(), ArgAction::Append)
+}
+
+/// The environment variable that sets the flag `id`.
+pub fn env_var(id: &str) -> String {
+    format!("{}{}", ENV_PREFIX, id.to_uppercase())
+}
+
+/// Parse `args` the way the binary does: the command line over the
+/// environment over the `--config` file over built-in defaults. `T` is the
+/// binary's parser; its `--config` flag (if any) names the file.
+pub fn load<T: CommandFactory + FromArgMatches>(
+    args: &[OsString],
+) -> Result<(T, ArgMatches), ConfigError> {
+    let matches = matches::<T>(args)?;
+    Ok((T::from_arg_matches(&matches)?, matches))
+}
+
+/// The matches [`load`] builds `T` from, for a binary that has to look at
+/// them (a subcommand, say) before deciding to.
+pub fn matches<T: CommandFactory>(args: &[OsString]) -> Result<ArgMatches, ConfigError> {
+    let command = with_env(T::command());
+    // A first, lenient pass only to find the file: flags it provides may
+    // be required and so still missing here.
+    let first = command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(args)?;
+    let matches = match first.try_get_one::<PathBuf>(CONFIG_ID).ok().flatten() {
+        Some(path) => ConfigFile::read(path)?
+            .apply_defaults(command)
+            .try_get_matches_from(args)?,
+        None => command.try_get_matches_from(args)?,
+    };
+    Ok(matches)
+}
+
+/// `command` with every flag also read from its `SRT_WHEP_*` variable.
+/// Secret values are kept out of `--help`.
+fn with_env(command: Command) -> Command {
+    let ids: Vec<String> = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional())
+        .map(|arg| arg.get_id().to_string())
+        .collect();
+    ids.into_iter().fold(command, |command, id| {
+        let var = env_var(&id);
+        let secret = SECRETS.contains(&id.as_str());
+        command.mut_arg(id, |arg| arg.env(var).hide_env_values(secret))
+    })
+}
+
+/// The effective configuration `matches` holds, as a config file in TOML,
+/// with secrets redacted. Unset optional flags are left out.
+pub fn render(matches: &ArgMatches) -> String {
+    let mut document = toml::Table::new();
+    for (section, flags) in sections() {
+        let mut table = toml::Table::new();
+        for arg in flags.get_arguments() {
+            let id = arg.get_id().as_str();
+            let Some(raw) = matches.try_get_raw(id).ok().flatten() else {
+                continue;
+            };
+            let values: Vec<String> = raw.map(|v| v.to_string_lossy().into_owned()).collect();
+            let value = if SECRETS.contains(&id) {
+                toml::Value::String(REDACTED.to_string())
+            } else if is_repeatable(arg) {
+                toml::Value::Array(values.into_iter().map(scalar).collect())
+            } else {
+                match values.into_iter().next() {
+                    Some(value) => scalar(value),
+                    None => continue,
+                }
+            };
+            table.insert(id.to_string(), value);
+        }
+        if !table.is_empty() {
+            document.insert(section.to_string(), toml::Value::Table(table));
+        }
+    }
+    toml::to_string(&document).unwrap_or_default()
+}
+
+/// A flag's raw value as the TOML type it reads as. Reading the file back
+/// turns every type into the same string again, so this only affects looks.
+fn scalar(value: String) -> toml::Value {
+    if let Ok(b) = value.parse::<bool>() {
+        toml::Value::Boolean(b)
+    } else if let Ok(i) = value.parse::<i64>() {
+        toml::Value::Integer(i)
+    } else if let Ok(f) = value.parse::<f64>() {
+        toml::Value::Float(f)
+    } else {
+        toml::Value::String(value)
+    }
+}
+
+/// A parsed and checked config file: flag ids and the defaults to give them.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    defaults: Vec<(String, Vec<String>)>,
+}
+
+impl ConfigFile {
+    /// Read the TOML file at `path`.
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        if let Some("yaml" | "yml") = path.extension().and_then(|ext| ext.to_str()) {
+            return Err(ConfigError::NotToml(path.to_path_buf()));
+        }
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Parse TOML `text`; see [`ConfigFile::from_document`].
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        Self::from_document(toml::from_str(text)?)
+    }
+
+    /// Check a parsed document, rejecting sections and keys no flag answers
+    /// to, so a typo fails loudly instead of being silently ignored.
+    fn from_document(document: serde_json::Value) -> Result<Self, ConfigError> {
+        let serde_json::Value::Object(document) = document else {
+            return Err(ConfigError::UnknownSection(document.to_string()));
+        };
+        let sections = sections();
+        let mut defaults = Vec::new();
+        for (section, table) in document {
+            let (Some((_, flags)), serde_json::Value::Object(table)) =
+                (sections.iter().find(|(name, _)| *name == section), table)
+            else {
+                return Err(ConfigError::UnknownSection(section));
+            };
+            for (key, value) in table {
+                let Some(arg) = flags
+                    .get_arguments()
+                    .find(|arg| arg.get_id() == key.as_str())
+                else {
+                    return Err(ConfigError::UnknownKey { section, key });
+                };
+                let values = match value {
+                    serde_json::Value::Array(items) if is_repeatable(arg) => {
+                        items.into_iter().map(scalar_text).collect()
+                    }
+                    value => vec![scalar_text(value)],
+                };
+                let Some(values) = values.into_iter().collect::<Option<Vec<_>>>() else {
+                    return Err(ConfigError::InvalidValue { section, key });
+                };
+                defaults.push((key, values));
+            }
+        }
+        Ok(Self { defaults })
+    }
+
+    /// `command` with every setting in the file as its flag's default; a
+    /// required flag the file sets is required no longer. Values are
+    /// validated by the flags' own parsers when `command` parses, and
+    /// settings for flags `command` lacks are ignored. `--help` shows the
+    /// defaults, but not those of the [`SECRETS`].
+    pub fn apply_defaults(&self, mut command: Command) -> Command {
+        for (id, values) in &self.defaults {
+            if !command
+                .get_arguments()
+                .any(|arg| arg.get_id() == id.as_str())
+            {
+                continue;
+            }
+            let values = values.clone();
+            let secret = SECRETS.contains(&id.as_str());
+            command = command.mut_arg(id, |arg| {
+                arg.default_values(values)
+                    .required(false)
+                    .hide_default_value(secret)
+            });
+        }
+        command
+    }
+}
+
+/// A scalar setting as the flag would read it on the command line.
+fn scalar_text(value: serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, render, ConfigError, ConfigFile};
+    use crate::signal::CoordinatorArgs;
+    use crate::webhooks::WebhookArgs;
+    use clap::{CommandFactory, FromArgMatches, Parser};
+    use std::ffi::OsString;
+    use std::path::{Path, PathBuf};
+
+    #[derive(Parser, Debug)]
+    struct TestCli {
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[command(flatten)]
+        coordinator: CoordinatorArgs,
+        #[command(flatten)]
+        webhooks: WebhookArgs,
+    }
+
+    fn parse(file: &ConfigFile, args: &[&str]) -> TestCli {
+        let matches = file
+            .apply_defaults(TestCli::command())
+            .try_get_matches_from(args)
+            .unwrap();
+        TestCli::from_arg_matches(&matches).unwrap()
+    }
+
+    #[test]
+    fn file_settings_become_defaults_and_flags_still_win() {
+        let file = ConfigFile::parse(
+            "[coordinator]\n\
+             offer_timeout_sec = 20\n\
+             watchdog_threshold = 7\n\
+             idle_timeout_sec = 30\n\
+             [webhooks]\n\
+             webhook_urls = [\"http://a\", \"http://b\"]\n",
+        )
+        .unwrap();
+
+        let cli = parse(&file, &["srt-whep", "--offer-timeout-sec", "4"]);
+        assert_eq!(4, cli.coordinator.offer_timeout_sec);
+        assert_eq!(7, cli.coordinator.watchdog_threshold);
+        assert_eq!(Some(30), cli.coordinator.idle_timeout_sec);
+        assert_eq!(vec!["http://a", "http://b"], cli.webhooks.webhook_urls);
+        // Untouched by both: the built-in default.
+        assert_eq!(10, cli.coordinator.answer_timeout_sec);
+    }
+
+    #[test]
+    fn help_shows_file_defaults_but_not_secret_ones() {
+        let file = ConfigFile::parse(
+            "[coordinator]\n\
+             offer_timeout_sec = 20\n\
+             [webhooks]\n\
+             webhook_secret = \"hunter2\"\n",
+        )
+        .unwrap();
+
+        let help = file
+            .apply_defaults(TestCli::command())
+            .render_long_help()
+            .to_string();
+        assert!(help.contains("[default: 20]"));
+        assert!(!help.contains("hunter2"));
+        // Still the secret's value, just not shown.
+        let cli = parse(&file, &["srt-whep"]);
+        assert_eq!(Some("hunter2"), cli.webhooks.webhook_secret.as_deref());
+    }
+
+    #[test]
+    fn yaml_files_are_refused_rather_than_read_as_toml() {
+        for name in ["srt-whep.yaml", "srt-whep.yml"] {
+            assert!(matches!(
+                ConfigFile::read(Path::new(name)),
+                Err(ConfigError::NotToml(path)) if path == Path::new(name)
+            ));
+        }
+    }
+
+    #[test]
+    fn unknown_sections_keys_and_values_are_rejected() {
+        assert!(matches!(
+            ConfigFile::parse("[coordinatr]\noffer_timeout_sec = 1\n"),
+            Err(ConfigError::UnknownSection(section)) if section == "coordinatr"
+        ));
+        assert!(matches!(
+            ConfigFile::parse("[coordinator]\noffer_timeout = 1\n"),
+            Err(ConfigError::UnknownKey { key, .. }) if key == "offer_timeout"
+        ));
+        assert!(matches!(
+            ConfigFile::parse("[coordinator]\noffer_timeout_sec = [1]\n"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            ConfigFile::parse("[coordinator\n"),
+            Err(ConfigError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn cli_beats_env_beats_file_and_print_config_redacts_secrets() {
+        let path = std::env::temp_dir().join(format!("srt-whep-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[coordinator]\n\
+             offer_timeout_sec = 20\n\
+             answer_timeout_sec = 20\n\
+             watchdog_threshold = 20\n\
+             [webhooks]\n\
+             webhook_secret = \"hunter2\"\n",
+        )
+        .unwrap();
+        // Only this test sets these variables.
+        std::env::set_var("SRT_WHEP_ANSWER_TIMEOUT_SEC", "30");
+        std::env::set_var("SRT_WHEP_WATCHDOG_THRESHOLD", "30");
+
+        let args: Vec<OsString> = [
+            "srt-whep",
+            "--config",
+            path.to_str().unwrap(),
+            "--watchdog-threshold",
+            "40",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        let (cli, matches) = load::<TestCli>(&args).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(20, cli.coordinator.offer_timeout_sec); // file
+        assert_eq!(30, cli.coordinator.answer_timeout_sec); // env over file
+        assert_eq!(40, cli.coordinator.watchdog_threshold); // CLI over env
+        assert_eq!(Some("hunter2"), cli.webhooks.webhook_secret.as_deref());
+
+        let printed = render(&matches);
+        assert!(printed.contains("answer_timeout_sec = 30"));
+        assert!(printed.contains("webhook_secret = \"<redacted>\""));
+        assert!(!printed.contains("hunter2"));
+        // What it prints reads back as a config file.
+        ConfigFile::parse(&printed).unwrap();
+    }
+}
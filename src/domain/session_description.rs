@@ -60,6 +60,34 @@ impl SessionDescription {
     pub fn is_sendonly(&self) -> bool {
         self.0.contains("a=sendonly")
     }
+
+    /// The media codecs this description carries, from its `a=rtpmap` lines
+    /// (e.g. `H264/90000`, `OPUS/48000/2`), in SDP order and without
+    /// duplicates. Retransmission and FEC payloads (`rtx`, `red`, `ulpfec`)
+    /// are not media codecs and are left out.
+    pub fn codecs(&self) -> Vec<String> {
+        let mut codecs: Vec<String> = Vec::new();
+        for line in self.0.lines() {
+            let Some(rtpmap) = line.trim().strip_prefix("a=rtpmap:") else {
+                continue;
+            };
+            let Some((_, encoding)) = rtpmap.split_once(' ') else {
+                continue;
+            };
+            let encoding = encoding.trim();
+            let name = encoding.split('/').next().unwrap_or_default();
+            if ["rtx", "red", "ulpfec"]
+                .iter()
+                .any(|skip| name.eq_ignore_ascii_case(skip))
+            {
+                continue;
+            }
+            if !codecs.iter().any(|c| c == encoding) {
+                codecs.push(encoding.to_string());
+            }
+        }
+        codecs
+    }
 }
 
 impl TryFrom<String> for SessionDescription {
@@ -120,6 +148,12 @@ impl SdpAnswer {
     pub fn is_sendonly(&self) -> bool {
         false
     }
+
+    /// The codecs the answerer accepted — what the session will actually
+    /// carry. See [`SessionDescription::codecs`].
+    pub fn codecs(&self) -> Vec<String> {
+        self.0.codecs()
+    }
 }
 
 impl_sdp_string_traits!(SdpAnswer);
@@ -240,6 +274,23 @@ mod tests {
         assert_err!(SdpAnswer::parse(VALID_WHIP_OFFER.to_string()));
     }
 
+    #[test]
+    fn codecs_are_read_from_rtpmap_lines() {
+        let answer = SdpAnswer::parse(VALID_WHEP_ANSWER.to_string()).unwrap();
+        assert_eq!(vec!["H264/90000".to_string()], answer.codecs());
+
+        let sdp = SessionDescription::parse(
+            "v=0\na=recvonly\na=rtpmap:96 H264/90000\na=rtpmap:97 rtx/90000\n\
+             a=rtpmap:111 OPUS/48000/2\na=rtpmap:98 H264/90000"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["H264/90000".to_string(), "OPUS/48000/2".to_string()],
+            sdp.codecs()
+        );
+    }
+
     #[test]
     fn direction_newtypes_reject_malformed_sdp() {
         assert_err!(SdpOffer::parse("v=1".to_string()));
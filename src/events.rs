@@ -0,0 +1,142 @@
+//! Lifecycle events for `GET /events`: connection transitions from the
+//! coordinator, run/restart events from the supervisor, and ingest and SRT
+//! publisher events from the pipeline, fanned out over one broadcast
+//! channel.
+//!
+//! Publishing never blocks and never fails: with no subscriber the event is
+//! simply dropped, and a subscriber that falls more than `EVENT_CAPACITY`
+//! events behind is told how many it missed (and should resync from
+//! `GET /list`) instead of slowing the publishers down.
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// How many events a slow subscriber may fall behind before it lags.
+const EVENT_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// A WHEP POST registered the connection and attached its branch.
+    ConnectionCreated { id: String },
+    /// The whipsink's SDP offer reached the WHEP client.
+    OfferDelivered { id: String },
+    /// The viewer's SDP answer reached the whipsink.
+    ConnectionEstablished { id: String },
+    /// The connection ended; `reason` names its `TerminateReason`, and
+    /// `duration_secs` is how long it was established (`None` if it never
+    /// was).
+    ConnectionTerminated {
+        id: String,
+        reason: &'static str,
+        duration_secs: Option<u64>,
+    },
+    /// The supervisor (re)started the pipeline.
+    PipelineStarted,
+    /// The pipeline reached end of stream.
+    PipelineEos,
+    /// The pipeline stopped with an error.
+    PipelineError { message: String },
+    /// The signaling watchdog forced a pipeline restart.
+    WatchdogRestart,
+    /// The supervisor waits this long before the next run.
+    Backoff { delay_ms: u64 },
+    /// The input stream is demuxed and linked: viewers can connect.
+    StreamReady,
+    /// The demuxer exposed an elementary stream with these caps.
+    CapsDetected { caps: String },
+    /// A publisher connected to the SRT input in listener mode. Its
+    /// streamid is left out, as everywhere outside the admin API.
+    PublisherConnected { id: usize, address: String },
+    /// That publisher disconnected.
+    PublisherDisconnected { id: usize, address: String },
+    /// A publisher was refused: `reason` is `address_not_allowed`,
+    /// `streamid_not_allowed`, or `address_unknown` if the SRT plugin did
+    /// not say where it came from.
+    PublisherRefused {
+        address: String,
+        reason: &'static str,
+    },
+}
+
+impl Event {
+    /// The SSE `event:` name; matches the JSON `type` tag.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::ConnectionCreated { .. } => "connection_created",
+            Event::OfferDelivered { .. } => "offer_delivered",
+            Event::ConnectionEstablished { .. } => "connection_established",
+            Event::ConnectionTerminated { .. } => "connection_terminated",
+            Event::PipelineStarted => "pipeline_started",
+            Event::PipelineEos => "pipeline_eos",
+            Event::PipelineError { .. } => "pipeline_error",
+            Event::WatchdogRestart => "watchdog_restart",
+            Event::Backoff { .. } => "backoff",
+            Event::StreamReady => "stream_ready",
+            Event::CapsDetected { .. } => "caps_detected",
+            Event::PublisherConnected { .. } => "publisher_connected",
+            Event::PublisherDisconnected { .. } => "publisher_disconnected",
+            Event::PublisherRefused { .. } => "publisher_refused",
+        }
+    }
+
+    /// One Server-Sent Events frame carrying this event as JSON.
+    pub fn to_sse(&self) -> String {
+        let data = serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string());
+        format!("event: {}\ndata: {}\n\n", self.name(), data)
+    }
+}
+
+/// Clone-able publishing end of the event channel, plus the means to
+/// subscribe to it.
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_CAPACITY);
+        Self { tx }
+    }
+}
+
+impl EventBus {
+    pub fn publish(&self, event: Event) {
+        // No subscribers is the common case, not an error.
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, EventBus};
+
+    #[test]
+    fn sse_frame_names_the_event_and_carries_it_as_json() {
+        let event = Event::ConnectionTerminated {
+            id: "a".to_string(),
+            reason: "deleted",
+            duration_secs: Some(42),
+        };
+        assert_eq!(
+            "event: connection_terminated\n\
+             data: {\"type\":\"connection_terminated\",\"id\":\"a\",\"reason\":\"deleted\",\"duration_secs\":42}\n\n",
+            event.to_sse()
+        );
+    }
+
+    #[tokio::test]
+    async fn every_subscriber_sees_every_event() {
+        let bus = EventBus::default();
+        bus.publish(Event::PipelineStarted); // nobody listening: dropped
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        bus.publish(Event::StreamReady);
+        assert_eq!(Event::StreamReady, first.recv().await.unwrap());
+        assert_eq!(Event::StreamReady, second.recv().await.unwrap());
+    }
+}
@@ -1,8 +1,13 @@
+pub mod config;
 pub mod domain;
 pub(crate) mod errors;
+pub mod events;
+pub mod metrics;
+pub(crate) mod push;
 pub mod routes;
 pub mod signal;
 pub mod startup;
 pub mod stream;
 pub(crate) mod supervisor;
 pub mod telemetry;
+pub mod webhooks;
@@ -1,47 +1,210 @@
-use clap::Parser;
-use srt_whep::signal::CoordinatorArgs;
-use srt_whep::startup::Application;
-use srt_whep::stream::{Args, SharablePipeline};
-use srt_whep::telemetry::{get_subscriber, init_subscriber};
+use clap::{FromArgMatches, Parser, Subcommand};
+use srt_whep::config::{self, ConfigError};
+use srt_whep::signal::{AdminHandle, CoordinatorArgs};
+use srt_whep::startup::{
+    AdminArgs, AppConfig, Application, PublishArgs, PushArgs, ShutdownArgs, SupervisorArgs,
+};
+use srt_whep::stream::{
+    preflight, whip_server_address, Args, Ingest, Passthrough, PreflightMode, SharablePipeline,
+};
+use srt_whep::telemetry::{
+    get_subscriber, init_subscriber, otlp_tracer, shutdown_tracing, TelemetryArgs,
+};
+use srt_whep::webhooks::{self, WebhookArgs};
 use std::error::Error;
+use std::ffi::OsString;
 use std::net::TcpListener;
+use std::path::PathBuf;
 
 /// srt-whep: SRT to WHEP (WebRTC) gateway.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    /// TOML file setting any of the flags below; flags and
+    /// SRT_WHEP_* variables take precedence. Its [coordinator] settings are
+    /// re-applied on SIGHUP.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Print the effective configuration, secrets redacted, and exit.
+    #[arg(long)]
+    print_config: bool,
     #[command(flatten)]
     pipeline: Args,
     #[command(flatten)]
     coordinator: CoordinatorArgs,
+    #[command(flatten)]
+    supervisor: SupervisorArgs,
+    #[command(flatten)]
+    admin: AdminArgs,
+    #[command(flatten)]
+    shutdown: ShutdownArgs,
+    #[command(flatten)]
+    publish: PublishArgs,
+    #[command(flatten)]
+    push: PushArgs,
+    #[command(flatten)]
+    webhooks: WebhookArgs,
+    #[command(flatten)]
+    telemetry: TelemetryArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum CliCommand {
+    /// Check that every GStreamer element the pipeline needs is installed,
+    /// print where each comes from, and exit non-zero if any is missing.
+    Check,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let cli = Cli::parse();
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let matches = match config::matches::<Cli>(&args) {
+        Ok(matches) => matches,
+        Err(ConfigError::Cli(e)) => e.exit(),
+        Err(e) => return Err(e.into()),
+    };
+    // `check` runs without the required flags, so before they are enforced.
+    if let Some(("check", _)) = matches.subcommand() {
+        // Without --input-address, check for SRT input.
+        let input_address = matches.get_one::<String>("input_address");
+        let ingest = Ingest::parse(input_address.map_or("", String::as_str))?;
+        let outputs = matches
+            .get_many::<String>("output_address")
+            .into_iter()
+            .flatten()
+            .map(|address| Passthrough::parse(address))
+            .collect::<Result<Vec<_>, _>>()?;
+        return check(&ingest, &outputs, matches.get_flag("decode_video"));
+    }
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if cli.print_config {
+        print!("{}", config::render(&matches));
+        return Ok(());
+    }
 
-    let subscriber = get_subscriber("srt_whep".into(), "debug".into(), std::io::stdout);
+    let tracer = match &cli.telemetry.otlp_endpoint {
+        Some(endpoint) => Some(otlp_tracer(endpoint, "srt-whep")?),
+        None => None,
+    };
+    let subscriber = get_subscriber("srt_whep".into(), "debug".into(), std::io::stdout, tracer);
     init_subscriber(subscriber);
 
+    let ingest = Ingest::parse(&cli.pipeline.input_address)?;
+    ingest.check_args(&cli.pipeline)?;
+    let outputs = cli
+        .pipeline
+        .output_address
+        .iter()
+        .map(|address| Passthrough::parse(address))
+        .collect::<Result<Vec<_>, _>>()?;
+    let missing_elements = match cli.pipeline.preflight {
+        PreflightMode::Skip => Vec::new(),
+        mode => {
+            let report = preflight(&ingest, &outputs, cli.pipeline.decode_video)?;
+            tracing::info!("GStreamer preflight:\n{}", report);
+            let missing = report.missing();
+            if !missing.is_empty() {
+                let message = format!(
+                    "GStreamer elements are missing: {} (run `srt-whep check` for details)",
+                    missing.join(", ")
+                );
+                if mode == PreflightMode::Strict {
+                    return Err(message.into());
+                }
+                tracing::warn!("{}; no viewer can be served", message);
+            }
+            missing
+        }
+    };
+
+    // A whip:// input's publishers reach its WHIP server through POST /whip.
+    let publish_config = cli.publish.to_config(
+        matches!(ingest, Ingest::Whip).then(|| whip_server_address(cli.pipeline.whip_server_port)),
+    );
+    let push_config = cli.push.to_config()?;
     let listener = TcpListener::bind(format!("0.0.0.0:{}", cli.pipeline.port))
         .expect("WHEP port is already in use");
     let (app, _pipeline) = Application::assemble(
         listener,
-        |branch_failures| SharablePipeline::new(cli.pipeline.clone(), branch_failures),
-        cli.coordinator.to_config(),
+        |branch_failures, events| {
+            SharablePipeline::new(cli.pipeline.clone(), branch_failures, events)
+                .with_missing_elements(missing_elements)
+                .with_outputs(outputs)
+        },
+        AppConfig {
+            coordinator: cli.coordinator.to_config(),
+            supervisor: cli.supervisor.to_config(),
+            admin: cli.admin.to_config(),
+            shutdown: cli.shutdown.to_config(),
+            publish: publish_config,
+            push: push_config,
+        },
         Some(cli.pipeline.port),
     )?;
+    webhooks::spawn(cli.webhooks.to_config(), &app.events())?;
+    spawn_config_reload(args, cli.config.is_some(), app.admin());
 
-    // Any termination signal stops everything gracefully: the HTTP server
-    // (drain), the supervisor (EOS → NULL-state cleanup → join), and with
-    // them the coordinator. `disable_signals()` removed actix's own handlers,
-    // so SIGTERM/SIGQUIT (docker stop, k8s) must be re-established here
-    // alongside SIGINT — otherwise those would hard-kill with no drain.
+    // Any termination signal stops everything gracefully: the viewers
+    // (drain, then a clean close), the HTTP server, the supervisor (EOS →
+    // NULL-state cleanup → join), and with them the coordinator.
+    // `disable_signals()` removed actix's own handlers, so SIGTERM/SIGQUIT
+    // (docker stop, k8s) must be re-established here alongside SIGINT —
+    // otherwise those would hard-kill with no drain.
     app.run_until_stopped(shutdown_signal()).await?;
+    shutdown_tracing();
+
+    Ok(())
+}
 
+/// `srt-whep check`: print the preflight report; fail if anything is missing.
+fn check(
+    ingest: &Ingest,
+    outputs: &[Passthrough],
+    decode_video: bool,
+) -> Result<(), Box<dyn Error>> {
+    let report = preflight(ingest, outputs, decode_video)?;
+    print!("{}", report);
+    if !report.is_ok() {
+        return Err(format!("missing: {}", report.missing().join(", ")).into());
+    }
     Ok(())
 }
 
+/// On SIGHUP, load the configuration again from the same command line,
+/// environment and config file, and apply its coordinator settings to the
+/// running coordinator; it logs what changed. A bad file is logged and
+/// leaves the running config alone.
+#[cfg(unix)]
+fn spawn_config_reload(args: Vec<OsString>, has_file: bool, admin: AdminHandle) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sighup = signal(SignalKind::hangup()).expect("failed to install SIGHUP handler");
+    tokio::spawn(async move {
+        while sighup.recv().await.is_some() {
+            if !has_file {
+                tracing::warn!("Received SIGHUP without a --config file; nothing to reload");
+                continue;
+            }
+            tracing::info!("Received SIGHUP: reloading the configuration");
+            let coordinator = match config::load::<Cli>(&args) {
+                Ok((cli, _)) => cli.coordinator,
+                Err(e) => {
+                    tracing::error!("Config reload failed: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = admin.reconfigure(coordinator.to_config()).await {
+                tracing::error!("Config reload failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_config_reload(_args: Vec<OsString>, _has_file: bool, _admin: AdminHandle) {}
+
 #[cfg(unix)]
 async fn shutdown_signal() {
     use tokio::signal::unix::{signal, SignalKind};
@@ -0,0 +1,350 @@
+//! Prometheus metrics for `GET /metrics`: signaling (connections by state,
+//! handshakes, terminations, watchdog), the supervisor (restarts, backoff),
+//! the pipeline seam (branch attach/detach latency, errors) and HTTP
+//! responses per route.
+//!
+//! A small in-process registry rendered in the Prometheus text exposition
+//! format. Recording takes a short uncontended lock and never awaits, so it
+//! is safe on the coordinator's actor loop.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const HANDSHAKE_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+const BRANCH_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0];
+const BACKOFF_BUCKETS: &[f64] = &[1.0, 2.0, 4.0, 8.0, 16.0, 30.0];
+
+struct Histogram {
+    bounds: &'static [f64],
+    /// Per-bucket (not cumulative) counts; rendering accumulates them.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: Duration) {
+        let secs = value.as_secs_f64();
+        if let Some(i) = self.bounds.iter().position(|&bound| secs <= bound) {
+            self.counts[i] += 1;
+        }
+        self.sum += secs;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &[(&str, &str)]) {
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count;
+            let le = bound.to_string();
+            let mut with_le = labels.to_vec();
+            with_le.push(("le", &le));
+            let _ = writeln!(out, "{}_bucket{} {}", name, label_set(&with_le), cumulative);
+        }
+        let mut with_le = labels.to_vec();
+        with_le.push(("le", "+Inf"));
+        let _ = writeln!(out, "{}_bucket{} {}", name, label_set(&with_le), self.count);
+        let _ = writeln!(out, "{}_sum{} {}", name, label_set(labels), self.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, label_set(labels), self.count);
+    }
+}
+
+struct Registry {
+    connections: BTreeMap<&'static str, u64>,
+    handshakes: BTreeMap<&'static str, Histogram>,
+    terminations: BTreeMap<&'static str, u64>,
+    watchdog_failures: u64,
+    watchdog_trips: u64,
+    restarts: BTreeMap<&'static str, u64>,
+    backoff: Histogram,
+    branch_attach: Histogram,
+    branch_detach: Histogram,
+    pipeline_errors: BTreeMap<&'static str, u64>,
+    http_responses: BTreeMap<(String, String, u16), u64>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            connections: BTreeMap::new(),
+            handshakes: BTreeMap::new(),
+            terminations: BTreeMap::new(),
+            watchdog_failures: 0,
+            watchdog_trips: 0,
+            restarts: BTreeMap::new(),
+            backoff: Histogram::new(BACKOFF_BUCKETS),
+            branch_attach: Histogram::new(BRANCH_BUCKETS),
+            branch_detach: Histogram::new(BRANCH_BUCKETS),
+            pipeline_errors: BTreeMap::new(),
+            http_responses: BTreeMap::new(),
+        }
+    }
+}
+
+/// Clone-able handle to the application's metrics registry.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<Registry>>,
+}
+
+impl Metrics {
+    fn with<T>(&self, f: impl FnOnce(&mut Registry) -> T) -> T {
+        // A panic while recording cannot leave a counter half-written.
+        let mut registry = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut registry)
+    }
+
+    /// Replace the connections-by-state gauge.
+    pub fn set_connections(&self, by_state: BTreeMap<&'static str, u64>) {
+        self.with(|r| r.connections = by_state);
+    }
+
+    /// A handshake ended after `duration`: `established`, or the name of the
+    /// `TerminateReason` that ended it first.
+    pub fn handshake(&self, outcome: &'static str, duration: Duration) {
+        self.with(|r| {
+            r.handshakes
+                .entry(outcome)
+                .or_insert_with(|| Histogram::new(HANDSHAKE_BUCKETS))
+                .observe(duration)
+        });
+    }
+
+    pub fn terminated(&self, reason: &'static str) {
+        self.with(|r| *r.terminations.entry(reason).or_default() += 1);
+    }
+
+    /// A failure fed the watchdog; `tripped` if it forced a restart.
+    pub fn watchdog_failure(&self, tripped: bool) {
+        self.with(|r| {
+            r.watchdog_failures += 1;
+            if tripped {
+                r.watchdog_trips += 1;
+            }
+        });
+    }
+
+    /// The supervisor is rerunning the pipeline after `cause` (`eos`,
+    /// `error` or `watchdog`), waiting `delay` first.
+    pub fn restart(&self, cause: &'static str, delay: Duration) {
+        self.with(|r| {
+            *r.restarts.entry(cause).or_default() += 1;
+            r.backoff.observe(delay);
+        });
+    }
+
+    pub fn branch_attached(&self, latency: Duration) {
+        self.with(|r| r.branch_attach.observe(latency));
+    }
+
+    pub fn branch_detached(&self, latency: Duration) {
+        self.with(|r| r.branch_detach.observe(latency));
+    }
+
+    /// A `BranchControl` call failed with this `PipelineError` kind.
+    pub fn pipeline_error(&self, kind: &'static str) {
+        self.with(|r| *r.pipeline_errors.entry(kind).or_default() += 1);
+    }
+
+    /// One HTTP response; `route` is the matched pattern, not the raw path,
+    /// so connection ids do not explode the label set.
+    pub fn http_response(&self, method: &str, route: &str, status: u16) {
+        self.with(|r| {
+            *r.http_responses
+                .entry((method.to_string(), route.to_string(), status))
+                .or_default() += 1
+        });
+    }
+
+    /// The registry in the Prometheus text exposition format (0.0.4).
+    pub fn render(&self) -> String {
+        self.with(|r| {
+            let mut out = String::new();
+
+            header(
+                &mut out,
+                "srt_whep_connections",
+                "gauge",
+                "Connections by state.",
+            );
+            for (state, n) in &r.connections {
+                sample(&mut out, "srt_whep_connections", &[("state", *state)], *n);
+            }
+
+            let name = "srt_whep_handshake_duration_seconds";
+            header(
+                &mut out,
+                name,
+                "histogram",
+                "Time from WHEP POST to an established session or a failed handshake, by outcome.",
+            );
+            for (outcome, histogram) in &r.handshakes {
+                histogram.render(&mut out, name, &[("outcome", *outcome)]);
+            }
+
+            let name = "srt_whep_connections_terminated_total";
+            header(
+                &mut out,
+                name,
+                "counter",
+                "Terminated connections by reason.",
+            );
+            for (reason, n) in &r.terminations {
+                sample(&mut out, name, &[("reason", *reason)], *n);
+            }
+
+            let name = "srt_whep_watchdog_failures_total";
+            header(
+                &mut out,
+                name,
+                "counter",
+                "Handshake failures fed to the watchdog.",
+            );
+            sample(&mut out, name, &[], r.watchdog_failures);
+            let name = "srt_whep_watchdog_trips_total";
+            header(
+                &mut out,
+                name,
+                "counter",
+                "Watchdog trips that forced a pipeline restart.",
+            );
+            sample(&mut out, name, &[], r.watchdog_trips);
+
+            let name = "srt_whep_supervisor_restarts_total";
+            header(&mut out, name, "counter", "Pipeline reruns by cause.");
+            for (cause, n) in &r.restarts {
+                sample(&mut out, name, &[("cause", *cause)], *n);
+            }
+            let name = "srt_whep_supervisor_backoff_seconds";
+            header(
+                &mut out,
+                name,
+                "histogram",
+                "Delay before each pipeline rerun.",
+            );
+            r.backoff.render(&mut out, name, &[]);
+
+            let name = "srt_whep_branch_attach_seconds";
+            header(
+                &mut out,
+                name,
+                "histogram",
+                "Latency of attaching a viewer branch.",
+            );
+            r.branch_attach.render(&mut out, name, &[]);
+            let name = "srt_whep_branch_detach_seconds";
+            header(
+                &mut out,
+                name,
+                "histogram",
+                "Latency of detaching a viewer branch.",
+            );
+            r.branch_detach.render(&mut out, name, &[]);
+
+            let name = "srt_whep_pipeline_errors_total";
+            header(
+                &mut out,
+                name,
+                "counter",
+                "Failed branch operations by PipelineError kind.",
+            );
+            for (kind, n) in &r.pipeline_errors {
+                sample(&mut out, name, &[("kind", *kind)], *n);
+            }
+
+            let name = "srt_whep_http_responses_total";
+            header(
+                &mut out,
+                name,
+                "counter",
+                "HTTP responses by method, route and status.",
+            );
+            for ((method, route, status), n) in &r.http_responses {
+                let status = status.to_string();
+                let labels = [
+                    ("method", method.as_str()),
+                    ("route", route.as_str()),
+                    ("status", &status),
+                ];
+                sample(&mut out, name, &labels, *n);
+            }
+
+            out
+        })
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: u64) {
+    let _ = writeln!(out, "{}{} {}", name, label_set(labels), value);
+}
+
+fn label_set(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| {
+            let v = v
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", k, v)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metrics;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    #[test]
+    fn renders_counters_gauges_and_cumulative_histograms() {
+        let metrics = Metrics::default();
+        metrics.set_connections(BTreeMap::from([("established", 2), ("awaiting_offer", 0)]));
+        metrics.terminated("deleted");
+        metrics.terminated("deleted");
+        metrics.watchdog_failure(false);
+        metrics.watchdog_failure(true);
+        metrics.handshake("established", Duration::from_millis(300));
+        metrics.handshake("established", Duration::from_secs(60));
+        metrics.http_response("GET", "/channel/{id}", 404);
+
+        let text = metrics.render();
+        for line in [
+            "# TYPE srt_whep_connections gauge",
+            "srt_whep_connections{state=\"awaiting_offer\"} 0",
+            "srt_whep_connections{state=\"established\"} 2",
+            "srt_whep_connections_terminated_total{reason=\"deleted\"} 2",
+            "srt_whep_watchdog_failures_total 2",
+            "srt_whep_watchdog_trips_total 1",
+            "srt_whep_handshake_duration_seconds_bucket{outcome=\"established\",le=\"0.25\"} 0",
+            "srt_whep_handshake_duration_seconds_bucket{outcome=\"established\",le=\"0.5\"} 1",
+            "srt_whep_handshake_duration_seconds_bucket{outcome=\"established\",le=\"30\"} 1",
+            "srt_whep_handshake_duration_seconds_bucket{outcome=\"established\",le=\"+Inf\"} 2",
+            "srt_whep_handshake_duration_seconds_count{outcome=\"established\"} 2",
+            "srt_whep_http_responses_total{method=\"GET\",route=\"/channel/{id}\",status=\"404\"} 1",
+            "srt_whep_branch_attach_seconds_count 0",
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {:?} in\n{}", line, text);
+        }
+    }
+}
@@ -0,0 +1,448 @@
+//! Push outputs: the stream sent on to remote WHIP endpoints (an SFU or a
+//! CDN's ingest) by permanent branches, alongside the local viewers, so they
+//! can take over the fan-out.
+//!
+//! Each push output has a task of its own. It attaches the branch once the
+//! input is linked and then checks on it. When the branch fails, the task
+//! removes it and attaches it again after a backoff. Failure means the
+//! endpoint refused the offer, the session dropped, or the branch went with
+//! a pipeline restart. The backoff doubles for each consecutive failure.
+//! The state of every push output is shared over a watch channel for
+//! `GET /status`.
+//!
+//! The branch's own errors are contained to it by
+//! `bus::classify_bus_message`. The pipeline never stops for a push output,
+//! and the coordinator never sees one: push outputs are not viewers.
+use crate::stream::{PipelineError, PushControl, PushHealth, PushTarget};
+use crate::supervisor::exponential_backoff;
+use anyhow::{anyhow, Error};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+const DEFAULT_RETRY_DELAY_MS: u64 = 1000;
+const DEFAULT_MAX_RETRY_DELAY_SEC: u64 = 30;
+
+/// How often a push output checks on its branch, or on the input while it
+/// waits for one.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct PushConfig {
+    pub targets: Vec<PushTarget>,
+    /// Delay before reattaching after a first failure; doubled per further
+    /// consecutive failure.
+    pub base_retry_delay: Duration,
+    /// Upper bound on that delay. A push output that stays attached this
+    /// long has recovered: its next failure starts from the base delay.
+    pub max_retry_delay: Duration,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            base_retry_delay: Duration::from_millis(DEFAULT_RETRY_DELAY_MS),
+            max_retry_delay: Duration::from_secs(DEFAULT_MAX_RETRY_DELAY_SEC),
+        }
+    }
+}
+
+/// CLI surface for push outputs. No `--push-url` means none.
+#[derive(clap::Args, Debug, Clone)]
+pub struct PushArgs {
+    /// WHIP endpoint to push the stream to (an SFU or CDN ingest); repeat
+    /// for several.
+    #[clap(long = "push-url")]
+    pub push_urls: Vec<String>,
+    /// Bearer token for the push endpoints: once for all of them, or once
+    /// per --push-url, in the same order.
+    #[clap(long = "push-token")]
+    pub push_tokens: Vec<String>,
+    /// Milliseconds before reconnecting a failed push output; doubled for
+    /// each further consecutive failure.
+    #[clap(long, default_value_t = DEFAULT_RETRY_DELAY_MS)]
+    pub push_retry_delay_ms: u64,
+    /// Upper bound, in seconds, on the delay before reconnecting.
+    #[clap(long, default_value_t = DEFAULT_MAX_RETRY_DELAY_SEC)]
+    pub push_max_retry_delay_sec: u64,
+}
+
+impl PushArgs {
+    /// Fails unless there is no --push-token, one, or one per --push-url:
+    /// any other count cannot say which endpoint a token is for.
+    pub fn to_config(&self) -> Result<PushConfig, Error> {
+        let (tokens, urls) = (self.push_tokens.len(), self.push_urls.len());
+        if tokens > 1 && tokens != urls {
+            return Err(anyhow!(
+                "--push-token given {} times for {} --push-url; give it once, or once per URL",
+                tokens,
+                urls
+            ));
+        }
+        let shared_token = match self.push_tokens.as_slice() {
+            [token] => Some(token),
+            _ => None,
+        };
+        Ok(PushConfig {
+            targets: self
+                .push_urls
+                .iter()
+                .enumerate()
+                .map(|(index, url)| PushTarget {
+                    url: url.clone(),
+                    token: shared_token.or(self.push_tokens.get(index)).cloned(),
+                })
+                .collect(),
+            base_retry_delay: Duration::from_millis(self.push_retry_delay_ms),
+            max_retry_delay: Duration::from_secs(self.push_max_retry_delay_sec),
+        })
+    }
+}
+
+/// What a push output is doing right now.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushPhase {
+    /// Waiting for the input to be linked before attaching.
+    #[default]
+    WaitingForInput,
+    /// The branch is in the pipeline, sending to the endpoint.
+    Attached,
+    /// The branch failed; waiting out the backoff before reattaching.
+    BackingOff,
+    /// Shut down.
+    Stopped,
+}
+
+/// One push output's state.
+#[derive(Debug, Clone)]
+pub struct PushStatus {
+    pub url: String,
+    pub phase: PushPhase,
+    /// When the branch was attached; `None` unless attached.
+    pub attached_since: Option<Instant>,
+    /// When the current backoff ends; `None` unless backing off.
+    pub backoff_until: Option<Instant>,
+    /// Failures since startup.
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+/// One push output in `GET /status`, with durations resolved. The token is
+/// never reported.
+#[derive(Debug, Serialize)]
+pub struct PushReport {
+    pub url: String,
+    pub phase: PushPhase,
+    pub uptime_secs: Option<u64>,
+    pub backoff_remaining_ms: Option<u64>,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+impl PushStatus {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            phase: PushPhase::default(),
+            attached_since: None,
+            backoff_until: None,
+            failures: 0,
+            consecutive_failures: 0,
+            last_error: None,
+        }
+    }
+
+    pub fn report(&self, now: Instant) -> PushReport {
+        PushReport {
+            url: self.url.clone(),
+            phase: self.phase,
+            uptime_secs: self.attached_since.map(|since| (now - since).as_secs()),
+            backoff_remaining_ms: self.backoff_until.map(|until| {
+                until
+                    .saturating_duration_since(now)
+                    .as_millis()
+                    .try_into()
+                    .unwrap_or(u64::MAX)
+            }),
+            failures: self.failures,
+            consecutive_failures: self.consecutive_failures,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// Start a task per configured push output, each running until `shutdown`
+/// reads `true` (or its sender is dropped). The returned channel holds their
+/// states, in configuration order.
+pub(crate) fn spawn<P: PushControl + 'static>(
+    pipeline: P,
+    config: PushConfig,
+    shutdown: watch::Receiver<bool>,
+) -> watch::Receiver<Vec<PushStatus>> {
+    let statuses = config
+        .targets
+        .iter()
+        .map(|target| PushStatus::new(&target.url))
+        .collect();
+    let (status_tx, status_rx) = watch::channel(statuses);
+    for (index, target) in config.targets.iter().enumerate() {
+        tokio::spawn(
+            PushOutput {
+                index,
+                target: target.clone(),
+                pipeline: pipeline.clone(),
+                status: status_tx.clone(),
+                shutdown: shutdown.clone(),
+                config: config.clone(),
+            }
+            .run(),
+        );
+    }
+    status_rx
+}
+
+struct PushOutput<P: PushControl> {
+    index: usize,
+    target: PushTarget,
+    pipeline: P,
+    status: watch::Sender<Vec<PushStatus>>,
+    shutdown: watch::Receiver<bool>,
+    config: PushConfig,
+}
+
+impl<P: PushControl> PushOutput<P> {
+    async fn run(mut self) {
+        let mut consecutive_failures: u32 = 0;
+        loop {
+            if *self.shutdown.borrow() {
+                break;
+            }
+            let error = match self
+                .pipeline
+                .add_push(self.index, self.target.clone())
+                .await
+            {
+                Ok(()) => {
+                    tracing::info!("Push output {} attached: {}", self.index, self.target.url);
+                    let attached = Instant::now();
+                    self.update(|status| {
+                        status.phase = PushPhase::Attached;
+                        status.attached_since = Some(attached);
+                        status.backoff_until = None;
+                    });
+                    let Some(error) = self.watch_branch().await else {
+                        break;
+                    };
+                    if attached.elapsed() >= self.config.max_retry_delay {
+                        consecutive_failures = 0;
+                    }
+                    // Failed or not, the branch comes out before the next
+                    // attach; its name must be free.
+                    if let Err(e) = self.pipeline.remove_push(self.index).await {
+                        tracing::warn!("Could not remove push output {}: {}", self.index, e);
+                    }
+                    error
+                }
+                Err(PipelineError::NotReady(reason)) => {
+                    tracing::debug!("Push output {} waits for the input: {}", self.index, reason);
+                    self.update(|status| status.phase = PushPhase::WaitingForInput);
+                    if self.sleep_or_stop(POLL_INTERVAL).await {
+                        break;
+                    }
+                    continue;
+                }
+                Err(e) => e.to_string(),
+            };
+
+            consecutive_failures = consecutive_failures.saturating_add(1);
+            let delay = exponential_backoff(
+                self.config.base_retry_delay,
+                self.config.max_retry_delay,
+                consecutive_failures,
+            );
+            tracing::warn!(
+                "Push output {} ({}) failed: {}; reconnecting in {:?}",
+                self.index,
+                self.target.url,
+                error,
+                delay
+            );
+            self.update(|status| {
+                status.phase = PushPhase::BackingOff;
+                status.attached_since = None;
+                status.backoff_until = Some(Instant::now() + delay);
+                status.failures += 1;
+                status.consecutive_failures = consecutive_failures;
+                status.last_error = Some(error);
+            });
+            if self.sleep_or_stop(delay).await {
+                break;
+            }
+        }
+        self.update(|status| {
+            status.phase = PushPhase::Stopped;
+            status.attached_since = None;
+            status.backoff_until = None;
+        });
+    }
+
+    /// Check on the attached branch until it fails, returning why, or until
+    /// shutdown (`None`).
+    async fn watch_branch(&mut self) -> Option<String> {
+        loop {
+            if self.sleep_or_stop(POLL_INTERVAL).await {
+                return None;
+            }
+            match self.pipeline.push_health(self.index).await {
+                Ok(PushHealth::Attached) => (),
+                Ok(PushHealth::Failed) => {
+                    return Some("the WHIP endpoint refused or dropped the session".to_string())
+                }
+                Ok(PushHealth::Absent) => return Some("the branch left the pipeline".to_string()),
+                // A lock timeout or the like: ask again next time.
+                Err(e) => tracing::debug!("Push output {} health unknown: {}", self.index, e),
+            }
+        }
+    }
+
+    /// Sleep for `delay`; `true` if shutdown was requested meanwhile. A
+    /// dropped sender means the application is tearing down, so it counts
+    /// as a request too.
+    async fn sleep_or_stop(&mut self, delay: Duration) -> bool {
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => false,
+            _ = self.shutdown.wait_for(|&stop| stop) => true,
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut PushStatus)) {
+        self.status
+            .send_modify(|statuses| f(&mut statuses[self.index]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::{TestPipeline, TestPipelineState};
+
+    fn config(urls: &[&str]) -> PushConfig {
+        PushConfig {
+            targets: urls
+                .iter()
+                .map(|url| PushTarget {
+                    url: url.to_string(),
+                    token: Some("secret".to_string()),
+                })
+                .collect(),
+            ..PushConfig::default()
+        }
+    }
+
+    /// Poll under the paused clock until `f` holds.
+    async fn wait_until(pipeline: &TestPipeline, f: impl Fn(&TestPipelineState) -> bool) {
+        for _ in 0..1000 {
+            if f(&pipeline.snapshot()) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("condition never reached: {:?}", pipeline.snapshot());
+    }
+
+    #[test]
+    fn one_token_serves_every_url_and_several_pair_up_in_order() {
+        let args = |tokens: &[&str]| PushArgs {
+            push_urls: vec!["https://a/whip".to_string(), "https://b/whip".to_string()],
+            push_tokens: tokens.iter().map(|t| t.to_string()).collect(),
+            push_retry_delay_ms: DEFAULT_RETRY_DELAY_MS,
+            push_max_retry_delay_sec: DEFAULT_MAX_RETRY_DELAY_SEC,
+        };
+        let tokens = |config: PushConfig| -> Vec<Option<String>> {
+            config.targets.into_iter().map(|t| t.token).collect()
+        };
+        let some = |t: &str| Some(t.to_string());
+        assert_eq!(vec![None, None], tokens(args(&[]).to_config().unwrap()));
+        assert_eq!(
+            vec![some("t"), some("t")],
+            tokens(args(&["t"]).to_config().unwrap())
+        );
+        assert_eq!(
+            vec![some("t1"), some("t2")],
+            tokens(args(&["t1", "t2"]).to_config().unwrap())
+        );
+        // Which URL would the third be for?
+        assert!(args(&["t1", "t2", "t3"]).to_config().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_push_output_attaches_once_the_input_is_linked() {
+        let pipeline = TestPipeline::default();
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let status = spawn(pipeline.clone(), config(&["https://sfu/whip"]), shutdown_rx);
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(pipeline.snapshot().pushes_added.is_empty());
+        assert_eq!(PushPhase::WaitingForInput, status.borrow()[0].phase);
+
+        pipeline.set_ready(true);
+        wait_until(&pipeline, |s| s.pushes_added == vec![0]).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(PushPhase::Attached, status.borrow()[0].phase);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_failed_push_is_removed_and_reattached_with_backoff() {
+        let pipeline = TestPipeline::default();
+        pipeline.set_ready(true);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let status = spawn(pipeline.clone(), config(&["https://sfu/whip"]), shutdown_rx);
+        wait_until(&pipeline, |s| s.pushes_added.len() == 1).await;
+
+        pipeline.fail_push(0);
+        wait_until(&pipeline, |s| s.pushes.is_empty()).await;
+        let report = status.borrow()[0].report(Instant::now());
+        assert_eq!(PushPhase::BackingOff, report.phase);
+        assert_eq!(1, report.consecutive_failures);
+        assert!(report.last_error.is_some());
+        assert!(report.backoff_remaining_ms.is_some());
+
+        wait_until(&pipeline, |s| s.pushes_added.len() == 2).await;
+
+        // A second failure right away doubles the delay: still backing off
+        // after the base delay.
+        pipeline.fail_push(0);
+        wait_until(&pipeline, |s| s.pushes.is_empty()).await;
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(2, pipeline.snapshot().pushes_added.len());
+        assert_eq!(2, status.borrow()[0].consecutive_failures);
+        wait_until(&pipeline, |s| s.pushes_added.len() == 3).await;
+
+        shutdown_tx.send(true).unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(PushPhase::Stopped, status.borrow()[0].phase);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_pipeline_restart_reattaches_every_push_output() {
+        use crate::stream::PipelineLifecycle;
+        let pipeline = TestPipeline::default();
+        pipeline.set_ready(true);
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let status = spawn(
+            pipeline.clone(),
+            config(&["https://a/whip", "https://b/whip"]),
+            shutdown_rx,
+        );
+        wait_until(&pipeline, |s| s.pushes.len() == 2).await;
+
+        pipeline.clean_up().await.unwrap();
+        wait_until(&pipeline, |s| s.pushes_added.len() == 4).await;
+        assert!(status.borrow().iter().all(|push| push.failures == 1));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::signal::{AdminHandle, CoordinatorArgs, Problem, SignalError};
+use crate::stream::Passthrough;
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::{self, HeaderMap};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+/// CLI surface for the admin API.
+#[derive(clap::Args, Debug, Clone)]
+pub struct AdminArgs {
+    /// Bearer token for the `/admin` endpoints. Unset: the admin API is not
+    /// mounted at all.
+    #[clap(long)]
+    pub admin_token: Option<String>,
+}
+
+impl AdminArgs {
+    pub fn to_config(&self) -> AdminConfig {
+        AdminConfig {
+            token: self.admin_token.clone().filter(|token| !token.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AdminConfig {
+    /// `None` disables the admin API.
+    pub token: Option<String>,
+}
+
+/// Whether `req` carries `Authorization: Bearer <token>`.
+pub fn authorized(req: &ServiceRequest, token: &str) -> bool {
+    bearer_token_matches(req.headers(), token)
+}
+
+/// Whether `headers` carry `Authorization: Bearer <token>`. The comparison
+/// does not stop at the first differing byte, so response timing does not
+/// reveal how much of a guess was right.
+pub fn bearer_token_matches(headers: &HeaderMap, token: &str) -> bool {
+    let Some(presented) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The `401` for a missing or wrong admin token, as problem+json like every
+/// other API error.
+pub fn unauthorized() -> HttpResponse {
+    let mut builder = HttpResponse::Unauthorized();
+    builder.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+    Problem::new(
+        StatusCode::UNAUTHORIZED,
+        "unauthorized",
+        "Unauthorized",
+        "The admin API requires a valid bearer token".to_string(),
+    )
+    .respond(builder)
+}
+
+#[derive(Serialize)]
+struct Kicked {
+    kicked: usize,
+}
+
+#[derive(Serialize)]
+struct Drain {
+    draining: bool,
+}
+
+/// The body of `POST /admin/outputs`.
+#[derive(Deserialize)]
+struct NewOutput {
+    /// Shaped like `--output-address`.
+    uri: String,
+}
+
+#[derive(Serialize)]
+struct Reconfigured {
+    previous: CoordinatorArgs,
+    current: CoordinatorArgs,
+}
+
+/// Terminate one viewer (`DELETE /admin/channel/{id}`); `404` if unknown.
+#[tracing::instrument(name = "ADMIN KICK", skip(admin))]
+pub async fn admin_kick(
+    path: web::Path<String>,
+    admin: web::Data<AdminHandle>,
+) -> Result<HttpResponse, SignalError> {
+    admin.kick(path.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Terminate every viewer (`DELETE /admin/channel`).
+#[tracing::instrument(name = "ADMIN KICK ALL", skip(admin))]
+pub async fn admin_kick_all(admin: web::Data<AdminHandle>) -> Result<HttpResponse, SignalError> {
+    let kicked = admin.kick_all().await?;
+    tracing::info!("Kicked {} connections", kicked);
+    Ok(HttpResponse::Ok().json(Kicked { kicked }))
+}
+
+/// Force a pipeline restart (`POST /admin/restart`). `202`: the supervisor
+/// carries it out asynchronously.
+#[tracing::instrument(name = "ADMIN RESTART", skip(admin))]
+pub async fn admin_restart(admin: web::Data<AdminHandle>) -> Result<HttpResponse, SignalError> {
+    admin.restart()?;
+    tracing::warn!("Pipeline restart requested through the admin API");
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// Enter drain mode (`POST /admin/drain`).
+#[tracing::instrument(name = "ADMIN DRAIN", skip(admin))]
+pub async fn admin_drain(admin: web::Data<AdminHandle>) -> Result<HttpResponse, SignalError> {
+    admin.set_draining(true).await?;
+    Ok(HttpResponse::Ok().json(Drain { draining: true }))
+}
+
+/// Leave drain mode (`DELETE /admin/drain`).
+#[tracing::instrument(name = "ADMIN UNDRAIN", skip(admin))]
+pub async fn admin_undrain(admin: web::Data<AdminHandle>) -> Result<HttpResponse, SignalError> {
+    admin.set_draining(false).await?;
+    Ok(HttpResponse::Ok().json(Drain { draining: false }))
+}
+
+/// The coordinator's configuration (`GET /admin/config`), in the units and
+/// under the names of its CLI flags.
+#[tracing::instrument(name = "ADMIN GET CONFIG", skip(admin))]
+pub async fn admin_config(admin: web::Data<AdminHandle>) -> Result<HttpResponse, SignalError> {
+    let config = admin.config().await?;
+    Ok(HttpResponse::Ok().json(CoordinatorArgs::from(&config)))
+}
+
+/// Replace the coordinator's configuration (`PUT /admin/config`) with the
+/// document in the body, shaped like the `GET`. Unset optional limits are
+/// turned off. Replies with the previous and the current configuration.
+#[tracing::instrument(name = "ADMIN PUT CONFIG", skip(body, admin))]
+pub async fn admin_reconfigure(
+    body: web::Bytes,
+    admin: web::Data<AdminHandle>,
+) -> Result<HttpResponse, SignalError> {
+    let args: CoordinatorArgs =
+        serde_json::from_slice(&body).map_err(|e| SignalError::InvalidConfig(e.to_string()))?;
+    let config = args.to_config();
+    let previous = admin.reconfigure(config.clone()).await?;
+    Ok(HttpResponse::Ok().json(Reconfigured {
+        previous: CoordinatorArgs::from(&previous),
+        current: CoordinatorArgs::from(&config),
+    }))
+}
+
+/// The passthrough outputs (`GET /admin/outputs`), passphrases redacted.
+#[tracing::instrument(name = "ADMIN LIST OUTPUTS", skip(admin))]
+pub async fn admin_outputs(admin: web::Data<AdminHandle>) -> Result<HttpResponse, SignalError> {
+    Ok(HttpResponse::Ok().json(admin.outputs().await?))
+}
+
+/// Add a passthrough output (`POST /admin/outputs`) from `{"uri": ...}`.
+/// `201` with the output and its id; `400` for a URI `--output-address`
+/// would refuse.
+#[tracing::instrument(name = "ADMIN ADD OUTPUT", skip(body, admin))]
+pub async fn admin_add_output(
+    body: web::Bytes,
+    admin: web::Data<AdminHandle>,
+) -> Result<HttpResponse, SignalError> {
+    let new: NewOutput =
+        serde_json::from_slice(&body).map_err(|e| SignalError::InvalidConfig(e.to_string()))?;
+    let output =
+        Passthrough::parse(&new.uri).map_err(|e| SignalError::InvalidConfig(e.to_string()))?;
+    let output = admin.add_output(output).await?;
+    tracing::info!("Added output {}: {}", output.id, output.uri);
+    Ok(HttpResponse::Created().json(output))
+}
+
+/// Remove a passthrough output (`DELETE /admin/outputs/{output}`); `404` if
+/// unknown.
+#[tracing::instrument(name = "ADMIN REMOVE OUTPUT", skip(admin))]
+pub async fn admin_remove_output(
+    path: web::Path<usize>,
+    admin: web::Data<AdminHandle>,
+) -> Result<HttpResponse, SignalError> {
+    admin.remove_output(path.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Disconnect a caller of an SRT listener output
+/// (`DELETE /admin/outputs/{output}/callers/{caller}`) and refuse it from
+/// then on; `404` if unknown. The output's other callers are dropped too,
+/// and may reconnect.
+#[tracing::instrument(name = "ADMIN DISCONNECT CALLER", skip(admin))]
+pub async fn admin_disconnect_caller(
+    path: web::Path<(usize, usize)>,
+    admin: web::Data<AdminHandle>,
+) -> Result<HttpResponse, SignalError> {
+    let (output, caller) = path.into_inner();
+    admin.disconnect_caller(output, caller).await?;
+    tracing::info!("Disconnected caller {} of output {}", caller, output);
+    Ok(HttpResponse::NoContent().finish())
+}
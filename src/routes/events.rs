@@ -0,0 +1,56 @@
+use crate::events::{Event, EventBus};
+use actix_web::{http::header, web, HttpResponse};
+use futures_util::stream;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+
+/// How often an otherwise idle stream sends an SSE comment, so proxies and
+/// load balancers do not time the connection out.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+struct Subscription {
+    rx: broadcast::Receiver<Event>,
+    shutdown: watch::Receiver<bool>,
+    keepalive: tokio::time::Interval,
+}
+
+/// Lifecycle events as Server-Sent Events (`GET /events`). The stream ends
+/// when the application shuts down; a client that falls too far behind gets
+/// a `lagged` event with the number it missed and should resync from
+/// `GET /list`.
+#[tracing::instrument(name = "EVENTS", skip(events, shutdown))]
+pub async fn events_stream(
+    events: web::Data<EventBus>,
+    shutdown: web::Data<watch::Receiver<bool>>,
+) -> HttpResponse {
+    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+    keepalive.reset(); // the first tick would otherwise fire immediately
+    let subscription = Subscription {
+        rx: events.subscribe(),
+        shutdown: shutdown.get_ref().clone(),
+        keepalive,
+    };
+
+    let body = stream::unfold(subscription, |mut sub| async move {
+        let frame = tokio::select! {
+            received = sub.rx.recv() => match received {
+                Ok(event) => event.to_sse(),
+                Err(RecvError::Lagged(missed)) => format!(
+                    "event: lagged\ndata: {{\"type\":\"lagged\",\"missed\":{}}}\n\n",
+                    missed
+                ),
+                Err(RecvError::Closed) => return None,
+            },
+            _ = sub.keepalive.tick() => ": keepalive\n\n".to_string(),
+            _ = sub.shutdown.wait_for(|&stop| stop) => return None,
+        };
+        Some((Ok::<_, Infallible>(web::Bytes::from(frame)), sub))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(body)
+}
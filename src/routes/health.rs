@@ -0,0 +1,103 @@
+use crate::signal::{SignalError, SignalHandle};
+use crate::stream::NotReadyReason;
+use crate::supervisor::{SupervisorPhase, SupervisorStatus};
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How long a probe waits on the coordinator before calling it unresponsive.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+struct Health {
+    status: &'static str,
+    /// `responsive`, `unresponsive` (no reply within the probe timeout) or
+    /// `stopped`.
+    coordinator: &'static str,
+    /// The supervisor's phase, or `null` if its task is gone.
+    supervisor: Option<SupervisorPhase>,
+}
+
+#[derive(Serialize)]
+struct Readiness {
+    status: &'static str,
+    /// `ready`, `not_ready` (no input demuxed or no output tees yet),
+    /// `draining` (the server refuses new viewers) or `unknown` (the
+    /// coordinator could not be asked).
+    pipeline: &'static str,
+    /// Why the pipeline is not ready, as `reason` (plus `caps` for an
+    /// unsupported codec); absent when ready or unknown.
+    #[serde(flatten)]
+    not_ready: Option<NotReadyReason>,
+    supervisor: Option<SupervisorPhase>,
+}
+
+/// The supervisor's phase, or `None` if its task has exited (dropping the
+/// status sender).
+fn supervisor_phase(status: &watch::Receiver<SupervisorStatus>) -> Option<SupervisorPhase> {
+    status.has_changed().ok()?;
+    Some(status.borrow().phase)
+}
+
+/// Liveness (`GET /healthz`): the coordinator answers within the probe
+/// timeout and the supervisor task is alive, neither stopped nor failed.
+/// `503` otherwise.
+pub async fn healthz(
+    signal: web::Data<SignalHandle>,
+    status: web::Data<watch::Receiver<SupervisorStatus>>,
+) -> HttpResponse {
+    let coordinator = match tokio::time::timeout(PROBE_TIMEOUT, signal.ping()).await {
+        Ok(Ok(())) => "responsive",
+        Ok(Err(_)) => "stopped",
+        Err(_) => "unresponsive",
+    };
+    let supervisor = supervisor_phase(&status);
+    let healthy = coordinator == "responsive"
+        && supervisor.is_some_and(|phase| {
+            !matches!(phase, SupervisorPhase::Stopped | SupervisorPhase::Failed)
+        });
+
+    let body = Health {
+        status: if healthy { "ok" } else { "unhealthy" },
+        coordinator,
+        supervisor,
+    };
+    if healthy {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
+/// Readiness (`GET /readyz`): the pipeline is running and can attach a
+/// viewer branch — the input is demuxed and the output tees exist — and
+/// the server is not draining. `503` otherwise.
+pub async fn readyz(
+    signal: web::Data<SignalHandle>,
+    status: web::Data<watch::Receiver<SupervisorStatus>>,
+) -> HttpResponse {
+    let (pipeline, not_ready) =
+        match tokio::time::timeout(PROBE_TIMEOUT, signal.pipeline_ready()).await {
+            Ok(Ok(())) => ("ready", None),
+            Ok(Err(SignalError::NotReady { reason, .. })) => ("not_ready", Some(reason)),
+            Ok(Err(SignalError::Draining)) => ("draining", None),
+            Ok(Err(SignalError::Unavailable)) | Err(_) => ("unknown", None),
+            // The pipeline could not tell (its lock is busy).
+            Ok(Err(_)) => ("not_ready", None),
+        };
+    let supervisor = supervisor_phase(&status);
+    let ready = pipeline == "ready" && supervisor == Some(SupervisorPhase::Running);
+
+    let body = Readiness {
+        status: if ready { "ready" } else { "not_ready" },
+        pipeline,
+        not_ready,
+        supervisor,
+    };
+    if ready {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
@@ -1,8 +1,28 @@
 use crate::signal::{SignalError, SignalHandle};
 use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    /// Only list connections in this state (e.g. `established`).
+    state: Option<String>,
+}
 
 #[tracing::instrument(name = "LIST", skip(signal))]
-pub async fn list(signal: web::Data<SignalHandle>) -> Result<HttpResponse, SignalError> {
-    let connections = signal.list_connections().await?;
+pub async fn list(
+    query: web::Query<ListQuery>,
+    signal: web::Data<SignalHandle>,
+) -> Result<HttpResponse, SignalError> {
+    let connections = signal.list_connections(query.into_inner().state).await?;
     Ok(HttpResponse::Ok().json(connections))
 }
+
+/// One session's GET /list entry (`GET /channel/{id}`); 404 if unknown.
+#[tracing::instrument(name = "CHANNEL INFO", skip(signal))]
+pub async fn channel_info(
+    path: web::Path<String>,
+    signal: web::Data<SignalHandle>,
+) -> Result<HttpResponse, SignalError> {
+    let info = signal.connection_info(path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(info))
+}
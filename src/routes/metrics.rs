@@ -0,0 +1,10 @@
+use crate::metrics::Metrics;
+use actix_web::{web, HttpResponse};
+
+/// Every metric in the Prometheus text format (`GET /metrics`).
+#[tracing::instrument(name = "METRICS", skip(metrics))]
+pub async fn metrics_export(metrics: web::Data<Metrics>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics.render())
+}
@@ -1,11 +1,25 @@
+mod admin;
+mod events;
+mod health;
 mod list;
+mod metrics;
 mod options;
+mod publish;
 mod remove;
+mod stats;
+mod status;
 mod whep_handler;
 mod whip_handler;
 
+pub use admin::*;
+pub use events::*;
+pub use health::*;
 pub use list::*;
+pub use metrics::*;
 pub use options::*;
+pub use publish::*;
 pub use remove::*;
+pub use stats::*;
+pub use status::*;
 pub use whep_handler::*;
 pub use whip_handler::*;
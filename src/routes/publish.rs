@@ -0,0 +1,374 @@
+//! WHIP ingest: `POST /whip` takes a publisher's offer for the `whip://`
+//! input. The WebRTC session itself belongs to the pipeline's
+//! `whipserversrc`, which serves WHIP on loopback only; these handlers
+//! authenticate the publisher and relay the exchange to it, rewriting its
+//! resource URLs under `/whip`.
+//!
+//! Not to be confused with the loopback `/whip_sink/{id}` route, where the
+//! pipeline's own viewer branches post their offers.
+
+use crate::events::Event;
+use crate::routes::bearer_token_matches;
+use crate::signal::Problem;
+use actix_web::http::{header, StatusCode};
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+/// An offer holding the [`PublisherSlot`]. Dropped unsettled (the offer
+/// failed, or its request went away), it gives the slot back.
+struct Claim<'a>(&'a PublisherSlot);
+
+impl Claim<'_> {
+    /// The offer was answered with resource `id`, or failed (`None`).
+    fn settle(self, id: Option<String>) {
+        *self.0 .0.lock().unwrap() = id.map(Publisher::Publishing);
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Claim<'_> {
+    fn drop(&mut self) {
+        *self.0 .0.lock().unwrap() = None;
+    }
+}
+
+/// `whipserversrc`'s WHIP endpoint and resource paths.
+const UPSTREAM_ENDPOINT: &str = "/whip/endpoint";
+const UPSTREAM_RESOURCE: &str = "/whip/resource/";
+
+/// Where publishers find their resource, by the id `whipserversrc` gave it.
+const RESOURCE_ROUTE: &str = "/whip/";
+
+/// How long a relayed request may take, the WHIP server's answer included.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// CLI surface for WHIP publishers.
+#[derive(clap::Args, Debug, Clone)]
+pub struct PublishArgs {
+    /// Bearer token WHIP publishers must present to POST /whip. Unset:
+    /// anyone who reaches the port may publish.
+    #[clap(long)]
+    pub whip_publisher_token: Option<String>,
+}
+
+impl PublishArgs {
+    /// `server` is `whipserversrc`'s address when the input is `whip://`.
+    pub fn to_config(&self, server: Option<String>) -> PublishConfig {
+        PublishConfig {
+            token: self
+                .whip_publisher_token
+                .clone()
+                .filter(|token| !token.is_empty()),
+            server,
+            ..PublishConfig::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PublishConfig {
+    /// `None`: publishers need no token.
+    pub token: Option<String>,
+    /// The pipeline's WHIP server; `None` unless the input is `whip://`,
+    /// and then `/whip` answers 404.
+    pub server: Option<String>,
+    /// The one publisher the input takes at a time.
+    pub publisher: PublisherSlot,
+    /// Relays to the WHIP server, bounded by [`REQUEST_TIMEOUT`].
+    pub client: reqwest::Client,
+}
+
+impl Default for PublishConfig {
+    fn default() -> Self {
+        Self {
+            token: None,
+            server: None,
+            publisher: PublisherSlot::default(),
+            // As `reqwest::Client::new()`, this fails only if no TLS
+            // backend can be initialised.
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("failed to build the WHIP relay's HTTP client"),
+        }
+    }
+}
+
+/// The publisher the `whip://` input is serving, if any: the pipeline
+/// builds its outputs for one session, so a second offer is refused until
+/// the first publisher leaves, by `DELETE /whip/{id}` or by dropping its
+/// session (which restarts the pipeline).
+#[derive(Debug, Clone, Default)]
+pub struct PublisherSlot(Arc<Mutex<Option<Publisher>>>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Publisher {
+    /// Its offer is still with the WHIP server.
+    Offering,
+    /// Its session, by resource id.
+    Publishing(String),
+}
+
+impl PublisherSlot {
+    /// Take the slot for a new offer; it is given back unless the claim
+    /// settles on a session.
+    fn claim(&self) -> Result<Claim<'_>, PublishError> {
+        let mut publisher = self.0.lock().unwrap();
+        if publisher.is_some() {
+            return Err(PublishError::Busy);
+        }
+        *publisher = Some(Publisher::Offering);
+        Ok(Claim(self))
+    }
+
+    /// Publisher `id` left.
+    fn release(&self, id: &str) {
+        let mut publisher = self.0.lock().unwrap();
+        if publisher.as_ref() == Some(&Publisher::Publishing(id.to_string())) {
+            *publisher = None;
+        }
+    }
+
+    /// Empty the slot whenever the pipeline (re)starts: its fresh WHIP
+    /// server has no session, whatever ended the last one.
+    pub fn release_on_restart(&self, mut events: broadcast::Receiver<Event>) {
+        let slot = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(Event::PipelineStarted) => *slot.0.lock().unwrap() = None,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => (),
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum PublishError {
+    #[error("The input is not whip://; nothing can be published here")]
+    NotPublishing,
+    #[error("WHIP publishing requires a valid bearer token")]
+    Unauthorized,
+    #[error("Another publisher is already publishing to the whip:// input")]
+    Busy,
+    #[error("{0:?} is not a WHIP resource id")]
+    InvalidResource(String),
+    #[error("The WHIP server did not answer: {0}")]
+    Upstream(String),
+}
+
+impl PublishError {
+    fn code(&self) -> &'static str {
+        match self {
+            PublishError::NotPublishing => "not_publishing",
+            PublishError::Unauthorized => "unauthorized",
+            PublishError::Busy => "publisher_busy",
+            PublishError::InvalidResource(_) => "invalid_resource",
+            PublishError::Upstream(_) => "publish_failed",
+        }
+    }
+}
+
+impl ResponseError for PublishError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PublishError::NotPublishing => StatusCode::NOT_FOUND,
+            PublishError::Unauthorized => StatusCode::UNAUTHORIZED,
+            PublishError::Busy => StatusCode::CONFLICT,
+            PublishError::InvalidResource(_) => StatusCode::NOT_FOUND,
+            PublishError::Upstream(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        let mut builder = HttpResponse::build(status);
+        if let PublishError::Unauthorized = self {
+            builder.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+        let title = status.canonical_reason().unwrap_or_default();
+        Problem::new(status, self.code(), title, self.to_string()).respond(builder)
+    }
+}
+
+impl PublishConfig {
+    /// The WHIP server to relay `req` to, once the publisher is let in.
+    fn admit(&self, req: &HttpRequest) -> Result<&str, PublishError> {
+        let server = self.server.as_deref().ok_or(PublishError::NotPublishing)?;
+        match &self.token {
+            Some(token) if !bearer_token_matches(req.headers(), token) => {
+                Err(PublishError::Unauthorized)
+            }
+            _ => Ok(server),
+        }
+    }
+}
+
+/// `id` as the upstream resource URL's last segment: it is decoded from
+/// the request path, so anything but a plain token could reach another of
+/// the WHIP server's paths.
+fn resource_id(id: String) -> Result<String, PublishError> {
+    let plain = id
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    if id.is_empty() || !plain || id == "." || id == ".." {
+        return Err(PublishError::InvalidResource(id));
+    }
+    Ok(id)
+}
+
+/// A publisher's offer (`POST /whip`); answered with the WHIP server's SDP
+/// answer and a `Location` under `/whip`, or 409 while another publisher
+/// holds the input.
+#[tracing::instrument(name = "WHIP PUBLISH", skip(req, body, config))]
+pub async fn whip_publish(
+    req: HttpRequest,
+    body: web::Bytes,
+    config: web::Data<PublishConfig>,
+) -> Result<HttpResponse, PublishError> {
+    let server = config.admit(&req)?;
+    let claim = config.publisher.claim()?;
+    tracing::info!("WHIP publisher offer from {:?}", req.peer_addr());
+    let upstream = config
+        .client
+        .post(format!("{}{}", server, UPSTREAM_ENDPOINT))
+        .header(header::CONTENT_TYPE.as_str(), "application/sdp")
+        .body(body.to_vec())
+        .send()
+        .await;
+    let response = relay(upstream).await;
+    let resource = response.as_ref().ok().and_then(|response| {
+        let location = response.headers().get(header::LOCATION)?.to_str().ok()?;
+        let id = location.strip_prefix(RESOURCE_ROUTE)?;
+        response.status().is_success().then(|| id.to_string())
+    });
+    claim.settle(resource);
+    response
+}
+
+/// Trickle ICE or an ICE restart for a publisher's session (`PATCH
+/// /whip/{id}`).
+#[tracing::instrument(name = "WHIP PUBLISH PATCH", skip(req, body, config))]
+pub async fn whip_publish_patch(
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: web::Bytes,
+    config: web::Data<PublishConfig>,
+) -> Result<HttpResponse, PublishError> {
+    let server = config.admit(&req)?;
+    let id = resource_id(path.into_inner())?;
+    let mut upstream = config
+        .client
+        .patch(format!("{}{}{}", server, UPSTREAM_RESOURCE, id))
+        .body(body.to_vec());
+    for name in [header::CONTENT_TYPE, header::IF_MATCH] {
+        if let Some(value) = req.headers().get(&name) {
+            upstream = upstream.header(name.as_str(), value.as_bytes());
+        }
+    }
+    relay(upstream.send().await).await
+}
+
+/// The publisher ends its session (`DELETE /whip/{id}`).
+#[tracing::instrument(name = "WHIP UNPUBLISH", skip(req, config))]
+pub async fn whip_unpublish(
+    req: HttpRequest,
+    path: web::Path<String>,
+    config: web::Data<PublishConfig>,
+) -> Result<HttpResponse, PublishError> {
+    let server = config.admit(&req)?;
+    let id = resource_id(path.into_inner())?;
+    let upstream = config
+        .client
+        .delete(format!("{}{}{}", server, UPSTREAM_RESOURCE, id))
+        .send()
+        .await;
+    let response = relay(upstream).await;
+    if response
+        .as_ref()
+        .is_ok_and(|response| response.status().is_success())
+    {
+        config.publisher.release(&id);
+    }
+    response
+}
+
+/// The WHIP server's response, passed on with its resource URL moved under
+/// `/whip`.
+async fn relay(
+    upstream: Result<reqwest::Response, reqwest::Error>,
+) -> Result<HttpResponse, PublishError> {
+    let upstream = upstream.map_err(|e| PublishError::Upstream(e.to_string()))?;
+    let status = StatusCode::from_u16(upstream.status().as_u16())
+        .map_err(|e| PublishError::Upstream(e.to_string()))?;
+    let mut response = HttpResponse::build(status);
+    for (name, value) in upstream.headers() {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        match name.as_str() {
+            "location" => {
+                let location = match value.rsplit_once(UPSTREAM_RESOURCE) {
+                    Some((_, id)) => format!("{}{}", RESOURCE_ROUTE, id),
+                    None => value.to_string(),
+                };
+                response.insert_header((header::LOCATION, location));
+            }
+            "content-type" | "etag" | "link" | "accept-patch" => {
+                response.append_header((name.as_str(), value));
+            }
+            _ => (),
+        }
+    }
+    let body = upstream
+        .bytes()
+        .await
+        .map_err(|e| PublishError::Upstream(e.to_string()))?;
+    Ok(response.body(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resource_id, PublishError, PublisherSlot};
+
+    #[test]
+    fn the_slot_takes_one_publisher_at_a_time() {
+        let slot = PublisherSlot::default();
+        let claim = slot.claim().unwrap();
+        assert!(matches!(slot.claim(), Err(PublishError::Busy)));
+        claim.settle(Some("abc".to_string()));
+        assert!(matches!(slot.claim(), Err(PublishError::Busy)));
+        slot.release("other");
+        assert!(matches!(slot.claim(), Err(PublishError::Busy)));
+        slot.release("abc");
+        assert!(slot.claim().is_ok());
+    }
+
+    #[test]
+    fn a_failed_or_abandoned_offer_gives_the_slot_back() {
+        let slot = PublisherSlot::default();
+        slot.claim().unwrap().settle(None);
+        drop(slot.claim().unwrap());
+        assert!(slot.claim().is_ok());
+    }
+
+    #[test]
+    fn only_a_single_plain_segment_is_a_resource_id() {
+        assert_eq!("3f2a-b_c.1", resource_id("3f2a-b_c.1".to_string()).unwrap());
+        for id in ["", ".", "..", "a/b", "../endpoint", "a?b", "a#b", "a%2Fb"] {
+            assert!(
+                matches!(
+                    resource_id(id.to_string()),
+                    Err(PublishError::InvalidResource(_))
+                ),
+                "{:?}",
+                id
+            );
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::signal::{SignalError, SignalHandle};
+use actix_web::{web, HttpResponse};
+
+/// One viewer's WebRTC statistics (`GET /channel/{id}/stats`); 404 if unknown.
+#[tracing::instrument(name = "CHANNEL STATS", skip(signal))]
+pub async fn channel_stats(
+    path: web::Path<String>,
+    signal: web::Data<SignalHandle>,
+) -> Result<HttpResponse, SignalError> {
+    let stats = signal.connection_stats(path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+/// The input's statistics, as its source element keeps them
+/// (`GET /input/stats`); 503 while the pipeline is stopped.
+#[tracing::instrument(name = "INPUT STATS", skip(signal))]
+pub async fn input_stats(signal: web::Data<SignalHandle>) -> Result<HttpResponse, SignalError> {
+    let stats = signal.input_stats().await?;
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+/// Statistics totalled across every viewer, with each viewer's own
+/// (`GET /stats`).
+#[tracing::instrument(name = "STATS", skip(signal))]
+pub async fn stats(signal: web::Data<SignalHandle>) -> Result<HttpResponse, SignalError> {
+    let stats = signal.aggregate_stats().await?;
+    Ok(HttpResponse::Ok().json(stats))
+}
@@ -0,0 +1,30 @@
+use crate::push::{PushReport, PushStatus};
+use crate::supervisor::{StatusReport, SupervisorStatus};
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+#[derive(Serialize)]
+struct Status {
+    #[serde(flatten)]
+    supervisor: StatusReport,
+    pushes: Vec<PushReport>,
+}
+
+/// The supervisor's state (`GET /status`): its phase, the current run's
+/// uptime, the remaining backoff, the restart count and the last error;
+/// and each push output's state.
+pub async fn supervisor_status(
+    status: web::Data<watch::Receiver<SupervisorStatus>>,
+    pushes: web::Data<watch::Receiver<Vec<PushStatus>>>,
+) -> HttpResponse {
+    let now = Instant::now();
+    let supervisor = status.borrow().report(now);
+    let pushes = pushes
+        .borrow()
+        .iter()
+        .map(|push| push.report(now))
+        .collect();
+    HttpResponse::Ok().json(Status { supervisor, pushes })
+}
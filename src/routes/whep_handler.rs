@@ -1,12 +1,20 @@
 use crate::domain::SdpAnswer;
-use crate::signal::{SignalError, SignalHandle};
-use actix_web::{web, HttpResponse};
+use crate::signal::{ClientInfo, SignalError, SignalHandle};
+use crate::supervisor::SupervisorStatus;
+use crate::telemetry::join_connection_trace;
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+use std::net::SocketAddr;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::Instrument;
 use uuid::Uuid;
 
-#[tracing::instrument(name = "WHEP", skip(form, signal))]
+#[tracing::instrument(name = "WHEP", skip(req, form, signal, supervisor))]
 pub async fn whep_handler(
+    req: HttpRequest,
     form: String,
     signal: web::Data<SignalHandle>,
+    supervisor: web::Data<watch::Receiver<SupervisorStatus>>,
 ) -> Result<HttpResponse, SignalError> {
     if !form.is_empty() {
         return Err(SignalError::InvalidSdp(
@@ -17,7 +25,21 @@ pub async fn whep_handler(
     let id = Uuid::new_v4().to_string();
     tracing::info!("Creating connection {}", id);
 
-    let offer = signal.create_connection(id.clone()).await?;
+    // The id is born here, so this request's own span cannot join the
+    // connection's trace; the wait for the offer does, in a span of its own.
+    let span = tracing::info_span!("connection", id = %id);
+    join_connection_trace(&span, &id);
+    let offer = signal
+        .create_connection(id.clone(), client_info(&req))
+        .instrument(span)
+        .await
+        .map_err(|e| {
+            // Between runs, the viewer's retry should land after the backoff.
+            match supervisor.borrow().backoff_remaining(Instant::now()) {
+                Some(remaining) => e.with_retry_after(remaining),
+                None => e,
+            }
+        })?;
 
     Ok(HttpResponse::Created()
         .append_header(("Location", format!("/channel/{}", id)))
@@ -25,6 +47,26 @@ pub async fn whep_handler(
         .body(offer.as_ref().to_string()))
 }
 
+/// Who is opening this connection, for GET /list. The address honours
+/// `Forwarded` / `X-Forwarded-For` so a reverse proxy reports its client
+/// rather than itself; it is informational only and never trusted for access.
+fn client_info(req: &HttpRequest) -> ClientInfo {
+    let remote_ip = req.connection_info().realip_remote_addr().map(|addr| {
+        addr.parse::<SocketAddr>()
+            .map(|socket| socket.ip().to_string())
+            .unwrap_or_else(|_| addr.to_string())
+    });
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    ClientInfo {
+        remote_ip,
+        user_agent,
+    }
+}
+
 #[tracing::instrument(name = "WHEP PATCH", skip(form, signal))]
 pub async fn whep_patch_handler(
     form: String,
@@ -1,12 +1,24 @@
 use super::errors::SignalError;
-use super::messages::{AnswerReply, Command, ConnectionId, ConnectionInfo, OfferReply, UnitReply};
+use super::messages::{
+    AnswerReply, ClientInfo, Command, ConfigReply, ConnectionId, ConnectionInfo, Envelope,
+    OfferReply, UnitReply,
+};
 use super::watchdog::Watchdog;
 use crate::domain::{SdpAnswer, SdpOffer};
-use crate::stream::{BranchControl, BranchId};
-use std::collections::HashMap;
-use std::time::Duration;
+use crate::events::{Event, EventBus};
+use crate::metrics::Metrics;
+use crate::stream::{
+    AggregateStats, BranchControl, BranchId, CandidatePair, PipelineError, ViewerSample,
+    ViewerStats,
+};
+use crate::telemetry::join_connection_trace;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
 use tokio::time::Instant;
+use tracing::Instrument;
 
 /// Single source of truth for the coordinator's default timing/watchdog knobs.
 /// Both `Default for CoordinatorConfig` and the `CoordinatorArgs` clap
@@ -36,6 +48,13 @@ pub struct CoordinatorConfig {
     /// GStreamer teardown from stalling every other signaling command, the
     /// expiry sweep, and the watchdog.
     pub teardown_timeout: Duration,
+    /// Upper bound on how long an established session may last; the sweep
+    /// terminates it once exceeded. `None` (the default) means no limit.
+    pub max_session_duration: Option<Duration>,
+    /// How long an established session may go without a new RTCP receiver
+    /// report from its viewer before the sweep terminates it. `None` (the
+    /// default) disables the check.
+    pub idle_timeout: Option<Duration>,
 }
 
 impl Default for CoordinatorConfig {
@@ -47,14 +66,85 @@ impl Default for CoordinatorConfig {
             watchdog_window: Duration::from_secs(DEFAULT_WATCHDOG_WINDOW_SEC),
             sweep_interval: Duration::from_millis(DEFAULT_SWEEP_INTERVAL_MS),
             teardown_timeout: Duration::from_secs(DEFAULT_TEARDOWN_TIMEOUT_SEC),
+            max_session_duration: None,
+            idle_timeout: None,
         }
     }
 }
 
+impl CoordinatorConfig {
+    /// Refuse a configuration the actor cannot run with.
+    fn validate(&self) -> Result<(), SignalError> {
+        if self.sweep_interval.is_zero() {
+            return Err(SignalError::InvalidConfig(
+                "sweep_interval must be greater than zero".to_string(),
+            ));
+        }
+        if self.watchdog_threshold == 0 {
+            return Err(SignalError::InvalidConfig(
+                "watchdog_threshold must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// `name: old -> new` for every setting `new` changes.
+    fn changes(&self, new: &CoordinatorConfig) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut compare = |name: &str, old: String, new: String| {
+            if old != new {
+                changes.push(format!("{name}: {old} -> {new}"));
+            }
+        };
+        compare(
+            "offer_timeout",
+            format!("{:?}", self.offer_timeout),
+            format!("{:?}", new.offer_timeout),
+        );
+        compare(
+            "answer_timeout",
+            format!("{:?}", self.answer_timeout),
+            format!("{:?}", new.answer_timeout),
+        );
+        compare(
+            "watchdog_threshold",
+            self.watchdog_threshold.to_string(),
+            new.watchdog_threshold.to_string(),
+        );
+        compare(
+            "watchdog_window",
+            format!("{:?}", self.watchdog_window),
+            format!("{:?}", new.watchdog_window),
+        );
+        compare(
+            "sweep_interval",
+            format!("{:?}", self.sweep_interval),
+            format!("{:?}", new.sweep_interval),
+        );
+        compare(
+            "teardown_timeout",
+            format!("{:?}", self.teardown_timeout),
+            format!("{:?}", new.teardown_timeout),
+        );
+        compare(
+            "max_session_duration",
+            format!("{:?}", self.max_session_duration),
+            format!("{:?}", new.max_session_duration),
+        );
+        compare(
+            "idle_timeout",
+            format!("{:?}", self.idle_timeout),
+            format!("{:?}", new.idle_timeout),
+        );
+        changes
+    }
+}
+
 /// CLI surface for the coordinator's timing/watchdog knobs. Kept separate
 /// from `stream::Args` so `stream` never depends on `signal`; the crate-root
-/// binary flattens both into one parser.
-#[derive(clap::Args, Debug, Clone)]
+/// binary flattens both into one parser. The same fields, in the same
+/// units, are the JSON document of `GET`/`PUT /admin/config`.
+#[derive(clap::Args, Debug, Clone, Serialize, Deserialize)]
 pub struct CoordinatorArgs {
     /// Seconds a WHEP client waits for the whipsink's SDP offer.
     #[clap(long, default_value_t = DEFAULT_OFFER_TIMEOUT_SEC)]
@@ -74,6 +164,13 @@ pub struct CoordinatorArgs {
     /// Upper bound, in seconds, on a single branch teardown.
     #[clap(long, default_value_t = DEFAULT_TEARDOWN_TIMEOUT_SEC)]
     pub teardown_timeout_sec: u64,
+    /// Maximum lifetime, in seconds, of an established session (unset: no limit).
+    #[clap(long)]
+    pub max_session_duration_sec: Option<u64>,
+    /// Seconds an established session may go without an RTCP receiver report
+    /// from its viewer before it is terminated (unset: never).
+    #[clap(long)]
+    pub idle_timeout_sec: Option<u64>,
 }
 
 impl CoordinatorArgs {
@@ -85,6 +182,24 @@ impl CoordinatorArgs {
             watchdog_window: Duration::from_secs(self.watchdog_window_sec),
             sweep_interval: Duration::from_millis(self.sweep_interval_ms),
             teardown_timeout: Duration::from_secs(self.teardown_timeout_sec),
+            max_session_duration: self.max_session_duration_sec.map(Duration::from_secs),
+            idle_timeout: self.idle_timeout_sec.map(Duration::from_secs),
+        }
+    }
+}
+
+impl From<&CoordinatorConfig> for CoordinatorArgs {
+    /// The config in CLI units; sub-unit parts are truncated.
+    fn from(config: &CoordinatorConfig) -> Self {
+        Self {
+            offer_timeout_sec: config.offer_timeout.as_secs(),
+            answer_timeout_sec: config.answer_timeout.as_secs(),
+            watchdog_threshold: config.watchdog_threshold,
+            watchdog_window_sec: config.watchdog_window.as_secs(),
+            sweep_interval_ms: config.sweep_interval.as_millis() as u64,
+            teardown_timeout_sec: config.teardown_timeout.as_secs(),
+            max_session_duration_sec: config.max_session_duration.map(|d| d.as_secs()),
+            idle_timeout_sec: config.idle_timeout.map(|d| d.as_secs()),
         }
     }
 }
@@ -98,9 +213,14 @@ enum ConnectionState {
         whip_reply: AnswerReply,
         deadline: Instant,
     },
-    // `since` is not read yet; a later task surfaces connection age.
-    #[allow(dead_code)]
-    Established { since: Instant },
+    Established {
+        since: Instant,
+        /// When the viewer last showed signs of life: a new RTCP receiver
+        /// report on its branch. Starts at `since`.
+        last_activity: Instant,
+        /// The branch's receiver-report marker as of `last_activity`.
+        last_report: Option<u64>,
+    },
 }
 
 /// Outcome of delivering the whipsink's SDP offer to the parked WHEP waiter.
@@ -135,9 +255,15 @@ impl ConnectionState {
     }
 
     fn established(since: Instant) -> Self {
-        ConnectionState::Established { since }
+        ConnectionState::Established {
+            since,
+            last_activity: since,
+            last_report: None,
+        }
     }
 
+    const NAMES: [&'static str; 3] = ["awaiting_offer", "awaiting_answer", "established"];
+
     fn name(&self) -> &'static str {
         match self {
             ConnectionState::AwaitingOffer { .. } => "awaiting_offer",
@@ -146,11 +272,32 @@ impl ConnectionState {
         }
     }
 
-    /// Project this connection into its GET /list entry.
-    fn info(&self, id: &ConnectionId) -> ConnectionInfo {
-        ConnectionInfo {
-            id: id.clone(),
-            state: self.name().to_string(),
+    /// When the handshake completed, if it has.
+    fn established_since(&self) -> Option<Instant> {
+        match self {
+            ConnectionState::Established { since, .. } => Some(*since),
+            _ => None,
+        }
+    }
+
+    /// Record the branch's latest receiver-report marker and return how long
+    /// the viewer has now been silent. A marker that differs from the last
+    /// one seen means a new report arrived, which resets the idle clock.
+    /// `None` for a connection still in its handshake.
+    fn observe_activity(&mut self, marker: Option<u64>, now: Instant) -> Option<Duration> {
+        match self {
+            ConnectionState::Established {
+                last_activity,
+                last_report,
+                ..
+            } => {
+                if marker.is_some() && marker != *last_report {
+                    *last_report = marker;
+                    *last_activity = now;
+                }
+                Some(now.duration_since(*last_activity))
+            }
+            _ => None,
         }
     }
 
@@ -240,6 +387,102 @@ impl ConnectionState {
     }
 }
 
+/// Everything the coordinator records about a connection besides its
+/// handshake state, for operators: who opened it, when, and what the
+/// session negotiated. Never consulted for any state transition.
+struct ConnectionMeta {
+    created: Instant,
+    created_at: SystemTime,
+    /// When the connection entered its current state.
+    state_since: Instant,
+    client: ClientInfo,
+    /// From the viewer's SDP answer; empty until it arrives.
+    codecs: Vec<String>,
+    /// Taken from the first sweep tick's sample that has one once
+    /// established, then kept: ICE does not renegotiate the pair for a
+    /// whipclientsink session.
+    candidate_pair: Option<CandidatePair>,
+    /// The viewer's stats as of the last sweep tick that found any, and
+    /// when: the sink only reports running totals, so the bitrate is the
+    /// delta between ticks.
+    last_stats_sample: Option<(Instant, ViewerStats)>,
+}
+
+impl ConnectionMeta {
+    fn new(client: ClientInfo, now: Instant) -> Self {
+        Self {
+            created: now,
+            created_at: SystemTime::now(),
+            state_since: now,
+            client,
+            codecs: Vec::new(),
+            candidate_pair: None,
+            last_stats_sample: None,
+        }
+    }
+
+    /// Record the stats read on a sweep tick, with the send rate since the
+    /// previous sample. A sink that reports nothing (yet) leaves the last
+    /// sample alone.
+    fn sample_stats(&mut self, mut stats: ViewerStats, now: Instant) {
+        if stats.streams.is_empty() {
+            return;
+        }
+        stats.bitrate_bps = self
+            .last_stats_sample
+            .as_ref()
+            .and_then(|(then, previous)| {
+                let elapsed = now.saturating_duration_since(*then).as_secs_f64();
+                let sent = stats.bytes_sent().checked_sub(previous.bytes_sent())?;
+                (elapsed > 0.0).then(|| (sent as f64 * 8.0 / elapsed) as u64)
+            });
+        self.last_stats_sample = Some((now, stats));
+    }
+
+    /// The last sampled stats; empty before the first.
+    fn stats(&self) -> ViewerStats {
+        self.last_stats_sample
+            .as_ref()
+            .map(|(_, stats)| stats.clone())
+            .unwrap_or_default()
+    }
+
+    /// Restart the time-in-state clock on a state transition.
+    fn entered(mut self, now: Instant) -> Self {
+        self.state_since = now;
+        self
+    }
+}
+
+/// One entry in the connection map.
+struct Connection {
+    state: ConnectionState,
+    meta: ConnectionMeta,
+}
+
+impl Connection {
+    /// Project this connection into its GET /list entry.
+    fn info(&self, id: &ConnectionId, now: Instant) -> ConnectionInfo {
+        let since = |t: Instant| now.saturating_duration_since(t).as_secs();
+        ConnectionInfo {
+            id: id.clone(),
+            state: self.state.name().to_string(),
+            created_unix_secs: self
+                .meta
+                .created_at
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            age_secs: since(self.meta.created),
+            state_secs: since(self.meta.state_since),
+            established_secs: self.state.established_since().map(since),
+            remote_ip: self.meta.client.remote_ip.clone(),
+            user_agent: self.meta.client.user_agent.clone(),
+            codecs: self.meta.codecs.clone(),
+            candidate_pair: self.meta.candidate_pair.clone(),
+        }
+    }
+}
+
 /// Why a connection is ending. Every death path in the coordinator names
 /// one of these, and `policy` maps it to the single table saying what
 /// termination does. A new way to die means a new variant and a new row —
@@ -257,6 +500,14 @@ enum TerminateReason {
     Reaped,
     /// The supervisor restarted the pipeline, or the watchdog tripped.
     Reset,
+    /// The established session outlived `max_session_duration`.
+    SessionLimit,
+    /// The viewer sent no RTCP receiver report within `idle_timeout`.
+    Idle,
+    /// An operator terminated it through the admin API.
+    Kicked,
+    /// The server is shutting down and the drain grace period is over.
+    Shutdown,
 }
 
 /// One row of the termination policy table.
@@ -274,7 +525,9 @@ enum MissingEntry {
     Reject,
     /// The death raced another termination; everything is already done.
     Skip,
-    /// Expected absence — the branch/watchdog consequences still apply.
+    /// Expected absence: a failed delivery already took the entry out and
+    /// hands its meta to `terminate_consumed`. The branch/watchdog
+    /// consequences still apply; without that meta nothing is reported.
     Proceed,
 }
 
@@ -308,6 +561,21 @@ enum Teardown {
 }
 
 impl TerminateReason {
+    /// The reason as `GET /events` reports it.
+    fn name(self) -> &'static str {
+        match self {
+            TerminateReason::Deleted => "deleted",
+            TerminateReason::Expired => "expired",
+            TerminateReason::PeerGone => "peer_gone",
+            TerminateReason::Reaped => "reaped",
+            TerminateReason::Reset => "reset",
+            TerminateReason::SessionLimit => "session_limit",
+            TerminateReason::Idle => "idle",
+            TerminateReason::Kicked => "kicked",
+            TerminateReason::Shutdown => "shutdown",
+        }
+    }
+
     /// THE termination policy table (ADR-0001/0002 pin these semantics).
     /// Rows that used to live as prose comments in five separate death
     /// paths are values here — most notably that `Reaped` does NOT feed
@@ -346,6 +614,26 @@ impl TerminateReason {
                 teardown: Teardown::Keep,
                 feeds_watchdog: false,
             },
+            // Both session limits end a healthy, established connection on
+            // operator policy: there is no waiter to notify, and a viewer
+            // running out its time says nothing about pipeline health.
+            TerminateReason::SessionLimit | TerminateReason::Idle => TerminationPolicy {
+                on_missing: MissingEntry::Skip,
+                waiter: WaiterNotice::Gone,
+                teardown: Teardown::BestEffort,
+                feeds_watchdog: false,
+            },
+            // An operator naming an unknown id hears so, like a DELETE; but
+            // the connection goes regardless of the detach, and an operator's
+            // decision says nothing about pipeline health. A shutdown closes
+            // each peer the same way, while the pipeline still runs to detach
+            // its branch cleanly.
+            TerminateReason::Kicked | TerminateReason::Shutdown => TerminationPolicy {
+                on_missing: MissingEntry::Reject,
+                waiter: WaiterNotice::Gone,
+                teardown: Teardown::BestEffort,
+                feeds_watchdog: false,
+            },
         }
     }
 }
@@ -355,9 +643,9 @@ impl TerminateReason {
 pub struct Coordinator<P: BranchControl> {
     pipeline: P,
     config: CoordinatorConfig,
-    connections: HashMap<ConnectionId, ConnectionState>,
+    connections: HashMap<ConnectionId, Connection>,
     watchdog: Watchdog,
-    rx: mpsc::Receiver<Command>,
+    rx: mpsc::Receiver<Envelope>,
     // Per-branch runtime failures observed on the pipeline bus. The
     // coordinator owns the receiver; the pipeline holds the matching sender
     // (from its own construction). This is a separate channel from `rx`, so it
@@ -368,15 +656,24 @@ pub struct Coordinator<P: BranchControl> {
     /// force-quit + rerun. A non-blocking `try_send` (coalescing) so a wedged
     /// pipeline can never stall this mailbox.
     restart_tx: mpsc::Sender<()>,
+    /// Every connection transition is published here for `GET /events`.
+    events: EventBus,
+    metrics: Metrics,
+    /// Drain mode (admin API, graceful shutdown): new connections are
+    /// refused with `Draining` and the pipeline reports not ready, while
+    /// existing connections continue.
+    draining: bool,
 }
 
 impl<P: BranchControl> Coordinator<P> {
     pub fn new(
         pipeline: P,
         config: CoordinatorConfig,
-        rx: mpsc::Receiver<Command>,
+        rx: mpsc::Receiver<Envelope>,
         branch_failures: mpsc::Receiver<BranchId>,
         restart_tx: mpsc::Sender<()>,
+        events: EventBus,
+        metrics: Metrics,
     ) -> Self {
         let watchdog = Watchdog::new(config.watchdog_threshold, config.watchdog_window);
         Self {
@@ -387,6 +684,9 @@ impl<P: BranchControl> Coordinator<P> {
             rx,
             branch_failures,
             restart_tx,
+            events,
+            metrics,
+            draining: false,
         }
     }
 
@@ -395,8 +695,12 @@ impl<P: BranchControl> Coordinator<P> {
         sweep.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
         loop {
             tokio::select! {
-                cmd = self.rx.recv() => match cmd {
-                    Some(cmd) => self.handle(cmd).await,
+                envelope = self.rx.recv() => match envelope {
+                    Some(Envelope { cmd, span }) => {
+                        let handling =
+                            tracing::info_span!(parent: &span, "coordinator", command = cmd.name());
+                        self.handle(cmd).instrument(handling).await
+                    }
                     None => break, // all handles dropped
                 },
                 // Map the stream plane's `BranchId` into the signal plane's
@@ -405,35 +709,169 @@ impl<P: BranchControl> Coordinator<P> {
                 Some(branch) = self.branch_failures.recv() => self.reap_branch(branch.into_string()).await,
                 _ = sweep.tick() => self.sweep_expired().await,
             }
+            // A reconfiguration may have changed the sweep cadence.
+            if sweep.period() != self.config.sweep_interval {
+                let period = self.config.sweep_interval;
+                sweep = tokio::time::interval_at(Instant::now() + period, period);
+                sweep.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            }
+            self.record_connection_gauge();
+        }
+    }
+
+    /// Refresh the connections-by-state gauge; every state is reported, so
+    /// an emptied state reads 0 rather than disappearing.
+    fn record_connection_gauge(&self) {
+        let mut by_state: BTreeMap<&'static str, u64> = ConnectionState::NAMES
+            .iter()
+            .map(|&name| (name, 0))
+            .collect();
+        for conn in self.connections.values() {
+            *by_state.entry(conn.state.name()).or_default() += 1;
         }
+        self.metrics.set_connections(by_state);
     }
 
     async fn handle(&mut self, cmd: Command) {
         match cmd {
-            Command::CreateConnection { id, reply } => self.create_connection(id, reply).await,
+            Command::CreateConnection { id, client, reply } => {
+                self.create_connection(id, client, reply).await
+            }
             Command::OfferReceived { id, sdp, reply } => self.offer_received(id, sdp, reply).await,
             Command::AnswerReceived { id, sdp, reply } => {
                 self.answer_received(id, sdp, reply).await
             }
             Command::RemoveConnection { id, reply } => self.remove_connection(id, reply).await,
-            Command::ListConnections { reply } => {
-                let _ = reply.send(Ok(self.list_connections()));
+            Command::ListConnections { state, reply } => {
+                let _ = reply.send(Ok(self.list_connections(state)));
+            }
+            Command::GetConnection { id, reply } => {
+                let _ = reply.send(self.get_connection(id));
+            }
+            Command::ConnectionStats { id, reply } => {
+                let _ = reply.send(self.connection_stats(id));
+            }
+            Command::AggregateStats { reply } => {
+                let _ = reply.send(Ok(self.aggregate_stats()));
+            }
+            Command::InputStats { reply } => {
+                let stats = self.pipeline.input_stats();
+                let _ = reply.send(self.pipeline_call_bounded("input stats", stats).await);
+            }
+            Command::Ping { reply } => {
+                let _ = reply.send(Ok(()));
+            }
+            // A draining server is not ready for viewers, whatever the
+            // pipeline's state: load balancers should stop sending them.
+            Command::PipelineReady { reply } if self.draining => {
+                let _ = reply.send(Err(SignalError::Draining));
+            }
+            Command::PipelineReady { reply } => {
+                let _ = reply.send(self.pipeline.ready().await.map_err(SignalError::from));
             }
             Command::Reset { reply } => {
                 self.reset();
                 let _ = reply.send(Ok(()));
             }
+            Command::Kick { id, reply } => {
+                let _ = reply.send(self.terminate(id, TerminateReason::Kicked).await);
+            }
+            Command::KickAll { reply } => {
+                let _ = reply.send(Ok(self.terminate_all(TerminateReason::Kicked).await));
+            }
+            Command::CloseAll { reply } => {
+                let _ = reply.send(Ok(self.terminate_all(TerminateReason::Shutdown).await));
+            }
+            Command::GetConfig { reply } => {
+                let _ = reply.send(Ok(self.config.clone()));
+            }
+            Command::Reconfigure { config, reply } => self.reconfigure(config, reply),
+            Command::ListOutputs { reply } => {
+                let outputs = self.pipeline.outputs();
+                let _ = reply.send(self.pipeline_call_bounded("output list", outputs).await);
+            }
+            Command::AddOutput { output, reply } => {
+                let added = self.pipeline.add_output(output);
+                let _ = reply.send(self.pipeline_call_bounded("output add", added).await);
+            }
+            Command::RemoveOutput { id, reply } => {
+                let removed = self.pipeline.remove_output(id);
+                let _ = reply.send(
+                    match self.pipeline_call_bounded("output remove", removed).await {
+                        Ok(true) => Ok(()),
+                        Ok(false) => Err(SignalError::OutputNotFound(id)),
+                        Err(e) => Err(e),
+                    },
+                );
+            }
+            Command::DisconnectCaller {
+                output,
+                caller,
+                reply,
+            } => {
+                let disconnected = self.pipeline.disconnect_caller(output, caller);
+                let _ = reply.send(
+                    match self
+                        .pipeline_call_bounded("caller disconnect", disconnected)
+                        .await
+                    {
+                        Ok(true) => Ok(()),
+                        Ok(false) => Err(SignalError::CallerNotFound { output, caller }),
+                        Err(e) => Err(e),
+                    },
+                );
+            }
+            Command::SetDraining { draining, reply } => {
+                if draining != self.draining {
+                    tracing::info!("Drain mode {}", if draining { "on" } else { "off" });
+                }
+                self.draining = draining;
+                let _ = reply.send(Ok(()));
+            }
         }
     }
 
-    /// Snapshot every connection for GET /list.
-    fn list_connections(&self) -> Vec<ConnectionInfo> {
+    /// Snapshot every connection for GET /list, or only those whose state
+    /// name is `state`. Served from the map alone, candidate pairs
+    /// included: nothing here reaches the pipeline.
+    fn list_connections(&self, state: Option<String>) -> Vec<ConnectionInfo> {
+        let now = Instant::now();
         self.connections
             .iter()
-            .map(|(id, state)| state.info(id))
+            .filter(|(_, conn)| state.as_deref().is_none_or(|s| conn.state.name() == s))
+            .map(|(id, conn)| conn.info(id, now))
             .collect()
     }
 
+    /// Snapshot one connection for GET /channel/{id}.
+    fn get_connection(&self, id: ConnectionId) -> Result<ConnectionInfo, SignalError> {
+        match self.connections.get(&id) {
+            Some(conn) => Ok(conn.info(&id, Instant::now())),
+            None => Err(SignalError::NotFound(id)),
+        }
+    }
+
+    /// One viewer's WebRTC statistics for GET /channel/{id}/stats, as the
+    /// last sweep tick sampled them, with the bitrate since the tick before.
+    /// A connection whose sink reported nothing (yet) gets empty stats
+    /// rather than an error.
+    fn connection_stats(&self, id: ConnectionId) -> Result<ViewerStats, SignalError> {
+        match self.connections.get(&id) {
+            Some(conn) => Ok(conn.meta.stats()),
+            None => Err(SignalError::NotFound(id)),
+        }
+    }
+
+    /// Every viewer's sampled statistics and their totals for GET /stats.
+    fn aggregate_stats(&self) -> AggregateStats {
+        let per_viewer = self
+            .connections
+            .iter()
+            .map(|(id, conn)| (id.clone(), conn.meta.stats()))
+            .collect();
+        AggregateStats::from_viewers(per_viewer)
+    }
+
     /// The supervisor restarted the pipeline: fail every waiter, clear the
     /// map, and clear the watchdog — the fresh pipeline starts with a clean
     /// bill of health, so stale failures must not count toward its trip.
@@ -442,28 +880,65 @@ impl<P: BranchControl> Coordinator<P> {
         self.watchdog.record_success();
     }
 
+    /// Swap in `config` and reply with the one it replaces. Deadlines already
+    /// set keep their old timeouts; everything later uses the new values.
+    fn reconfigure(&mut self, config: CoordinatorConfig, reply: ConfigReply) {
+        if let Err(e) = config.validate() {
+            tracing::warn!("Refused coordinator reconfiguration: {}", e);
+            let _ = reply.send(Err(e));
+            return;
+        }
+        let changes = self.config.changes(&config);
+        if changes.is_empty() {
+            tracing::info!("Coordinator reconfigured: no changes");
+        } else {
+            tracing::info!("Coordinator reconfigured: {}", changes.join(", "));
+        }
+        self.watchdog
+            .reconfigure(config.watchdog_threshold, config.watchdog_window);
+        let _ = reply.send(Ok(std::mem::replace(&mut self.config, config)));
+    }
+
+    /// Terminate every connection for `reason`; returns how many there were.
+    async fn terminate_all(&mut self, reason: TerminateReason) -> usize {
+        let ids: Vec<ConnectionId> = self.connections.keys().cloned().collect();
+        let count = ids.len();
+        for id in ids {
+            let _ = self.terminate(id, reason).await;
+        }
+        count
+    }
+
     // Entry API can't be held across the pipeline awaits below.
     #[allow(clippy::map_entry)]
-    async fn create_connection(&mut self, id: ConnectionId, reply: OfferReply) {
+    async fn create_connection(&mut self, id: ConnectionId, client: ClientInfo, reply: OfferReply) {
         if self.connections.contains_key(&id) {
             let _ = reply.send(Err(SignalError::WrongState(id)));
             return;
         }
+        if self.draining {
+            let _ = reply.send(Err(SignalError::Draining));
+            return;
+        }
         // Bound add_branch on the actor's critical path. Its failure path now
         // detaches a half-built branch internally (ADR 0002); an unbounded
         // detach would let one wedged GStreamer teardown stall every command,
         // the sweep, and the watchdog -- the same bound remove_branch_bounded
         // gives the other teardown paths. The synchronous attach has no await
         // to cancel; this bounds the internal cleanup detach.
+        let started = Instant::now();
         match tokio::time::timeout(
             self.config.teardown_timeout,
-            self.pipeline.add_branch(id.clone()),
+            self.pipeline
+                .add_branch(id.clone())
+                .instrument(tracing::info_span!("add_branch", id = %id)),
         )
         .await
         {
-            Ok(Ok(())) => {}
+            Ok(Ok(())) => self.metrics.branch_attached(started.elapsed()),
             Ok(Err(add_err)) => {
                 // Error variants mean retry policy only -- no matching here.
+                self.metrics.pipeline_error(add_err.kind());
                 let _ = reply.send(Err(add_err.into()));
                 return;
             }
@@ -479,21 +954,37 @@ impl<P: BranchControl> Coordinator<P> {
                 return;
             }
         }
-        let deadline = Instant::now() + self.config.offer_timeout;
-        self.connections
-            .insert(id, ConnectionState::awaiting_offer(reply, deadline));
+        let now = Instant::now();
+        let deadline = now + self.config.offer_timeout;
+        self.events
+            .publish(Event::ConnectionCreated { id: id.clone() });
+        self.connections.insert(
+            id,
+            Connection {
+                state: ConnectionState::awaiting_offer(reply, deadline),
+                meta: ConnectionMeta::new(client, now),
+            },
+        );
     }
 
     async fn offer_received(&mut self, id: ConnectionId, sdp: SdpOffer, reply: AnswerReply) {
-        let Some(state) = self.connections.remove(&id) else {
+        let Some(Connection { state, meta }) = self.connections.remove(&id) else {
             let _ = reply.send(Err(SignalError::NotFound(id)));
             return;
         };
         match state.deliver_offer(sdp) {
             Ok(OfferDelivery::Delivered) => {
-                let deadline = Instant::now() + self.config.answer_timeout;
-                self.connections
-                    .insert(id, ConnectionState::awaiting_answer(reply, deadline));
+                let now = Instant::now();
+                let deadline = now + self.config.answer_timeout;
+                self.events
+                    .publish(Event::OfferDelivered { id: id.clone() });
+                self.connections.insert(
+                    id,
+                    Connection {
+                        state: ConnectionState::awaiting_answer(reply, deadline),
+                        meta: meta.entered(now),
+                    },
+                );
             }
             Ok(OfferDelivery::WaiterGone) => {
                 // The WHEP client vanished while waiting (actix dropped its
@@ -502,26 +993,41 @@ impl<P: BranchControl> Coordinator<P> {
                 // not that its id was unknown (404).
                 tracing::warn!("WHEP waiter for {} is gone; failing handshake", id);
                 let _ = reply.send(Err(SignalError::Gone(id.clone())));
-                let _ = self.terminate(id, TerminateReason::PeerGone).await;
+                let _ = self
+                    .terminate_consumed(id, TerminateReason::PeerGone, Some(meta))
+                    .await;
             }
             Err(other) => {
                 // Wrong state: restore untouched, reject the command.
-                self.connections.insert(id.clone(), other);
+                self.connections
+                    .insert(id.clone(), Connection { state: other, meta });
                 let _ = reply.send(Err(SignalError::WrongState(id)));
             }
         }
     }
 
     async fn answer_received(&mut self, id: ConnectionId, sdp: SdpAnswer, reply: UnitReply) {
-        let Some(state) = self.connections.remove(&id) else {
+        let Some(Connection { state, mut meta }) = self.connections.remove(&id) else {
             let _ = reply.send(Err(SignalError::NotFound(id)));
             return;
         };
+        // Read before delivery consumes the answer; only kept on success.
+        let codecs = sdp.codecs();
         match state.deliver_answer(sdp) {
             Ok(AnswerDelivery::Established) => {
                 self.watchdog.record_success();
-                self.connections
-                    .insert(id, ConnectionState::established(Instant::now()));
+                let now = Instant::now();
+                self.metrics.handshake("established", now - meta.created);
+                meta.codecs = codecs;
+                self.events
+                    .publish(Event::ConnectionEstablished { id: id.clone() });
+                self.connections.insert(
+                    id,
+                    Connection {
+                        state: ConnectionState::established(now),
+                        meta: meta.entered(now),
+                    },
+                );
                 let _ = reply.send(Ok(()));
             }
             Ok(AnswerDelivery::WaiterGone) => {
@@ -531,10 +1037,13 @@ impl<P: BranchControl> Coordinator<P> {
                 // died, unlike a PATCH naming an unknown id (404).
                 tracing::warn!("WHIP waiter for {} is gone; failing handshake", id);
                 let _ = reply.send(Err(SignalError::Gone(id.clone())));
-                let _ = self.terminate(id, TerminateReason::PeerGone).await;
+                let _ = self
+                    .terminate_consumed(id, TerminateReason::PeerGone, Some(meta))
+                    .await;
             }
             Err(other) => {
-                self.connections.insert(id.clone(), other);
+                self.connections
+                    .insert(id.clone(), Connection { state: other, meta });
                 let _ = reply.send(Err(SignalError::WrongState(id)));
             }
         }
@@ -549,13 +1058,112 @@ impl<P: BranchControl> Coordinator<P> {
         let expired: Vec<ConnectionId> = self
             .connections
             .iter()
-            .filter(|(_, state)| state.deadline().is_some_and(|d| d <= now))
+            .filter(|(_, conn)| conn.state.deadline().is_some_and(|d| d <= now))
             .map(|(id, _)| id.clone())
             .collect();
 
         for id in expired {
             tracing::warn!("Handshake for {} timed out", id);
-            let _ = self.terminate(id, TerminateReason::Expired).await;
+            self.terminate_in_trace(id, TerminateReason::Expired).await;
+        }
+
+        // One read of every viewer's sink serves the idle check, the stats
+        // and the candidate pairs.
+        let samples = self.sample_viewers().await;
+        self.enforce_session_limits(samples.as_ref()).await;
+        if let Some(samples) = samples {
+            self.record_samples(samples);
+        }
+    }
+
+    /// Read every viewer's sink once per tick, in one bounded pipeline
+    /// call. `None` if the read failed or timed out: nothing is known about
+    /// any viewer this tick.
+    async fn sample_viewers(&self) -> Option<HashMap<ConnectionId, ViewerSample>> {
+        let ids: Vec<ConnectionId> = self.connections.keys().cloned().collect();
+        match self
+            .pipeline_call_bounded("viewer sampling", self.pipeline.sample_viewers(ids))
+            .await
+        {
+            Ok(samples) => Some(samples),
+            Err(e) => {
+                tracing::debug!("Could not sample viewers: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Keep this tick's stats, so the bitrate is measured over the sweep
+    /// interval however often the stats are read, and reads need not reach
+    /// the pipeline; and an established viewer's candidate pair, once its
+    /// sink reports one. A viewer missing from the sample keeps its last.
+    fn record_samples(&mut self, samples: HashMap<ConnectionId, ViewerSample>) {
+        let now = Instant::now();
+        for (id, sample) in samples {
+            if let Some(conn) = self.connections.get_mut(&id) {
+                if conn.meta.candidate_pair.is_none() && conn.state.established_since().is_some() {
+                    conn.meta.candidate_pair = sample.candidate_pair;
+                }
+                conn.meta.sample_stats(sample.stats, now);
+            }
+        }
+    }
+
+    /// Established connections have no handshake deadline, but they are not
+    /// immortal: a forgotten viewer would otherwise hold its branch forever.
+    /// Terminate any that ran past `max_session_duration`, then any whose
+    /// viewer has been silent (no new RTCP receiver report) for
+    /// `idle_timeout`, going by this tick's `samples`. Both limits are off
+    /// unless configured.
+    async fn enforce_session_limits(
+        &mut self,
+        samples: Option<&HashMap<ConnectionId, ViewerSample>>,
+    ) {
+        let now = Instant::now();
+
+        if let Some(max) = self.config.max_session_duration {
+            let over: Vec<ConnectionId> = self
+                .connections
+                .iter()
+                .filter(|(_, conn)| {
+                    conn.state
+                        .established_since()
+                        .is_some_and(|since| now.duration_since(since) >= max)
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in over {
+                tracing::info!("Session {} reached its {:?} limit", id, max);
+                self.terminate_in_trace(id, TerminateReason::SessionLimit)
+                    .await;
+            }
+        }
+
+        // Unreadable this tick (e.g. the state lock is busy): skip rather
+        // than count it as silence.
+        if let (Some(idle_timeout), Some(samples)) = (self.config.idle_timeout, samples) {
+            let established: Vec<ConnectionId> = self
+                .connections
+                .iter()
+                .filter(|(_, conn)| conn.state.established_since().is_some())
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in established {
+                let marker = samples
+                    .get(&id)
+                    .and_then(|sample| sample.receiver_report_marker);
+                let Some(conn) = self.connections.get_mut(&id) else {
+                    continue;
+                };
+                if conn
+                    .state
+                    .observe_activity(marker, now)
+                    .is_some_and(|silent| silent >= idle_timeout)
+                {
+                    tracing::info!("Session {} idle for {:?}; terminating", id, idle_timeout);
+                    self.terminate_in_trace(id, TerminateReason::Idle).await;
+                }
+            }
         }
     }
 
@@ -569,15 +1177,38 @@ impl<P: BranchControl> Coordinator<P> {
         &mut self,
         id: ConnectionId,
         reason: TerminateReason,
+    ) -> Result<(), SignalError> {
+        self.terminate_consumed(id, reason, None).await
+    }
+
+    /// `terminate` for an entry a failed delivery has already taken out of
+    /// the map, whose `meta` proves the connection existed. Only a
+    /// connection that existed is published and counted as terminated: a
+    /// `Proceed` with neither an entry nor its meta reports nothing.
+    async fn terminate_consumed(
+        &mut self,
+        id: ConnectionId,
+        reason: TerminateReason,
+        consumed: Option<ConnectionMeta>,
     ) -> Result<(), SignalError> {
         let policy = reason.policy();
 
-        let state = match (self.connections.remove(&id), policy.on_missing) {
-            (Some(state), _) => Some(state),
+        let conn = match (self.connections.remove(&id), policy.on_missing) {
+            (Some(conn), _) => Some(conn),
             (None, MissingEntry::Reject) => return Err(SignalError::NotFound(id)),
             (None, MissingEntry::Skip) => return Ok(()),
             (None, MissingEntry::Proceed) => None,
         };
+        let existed = conn.is_some() || consumed.is_some();
+        let established_since = conn.as_ref().and_then(|c| c.state.established_since());
+        // A connection that dies before it is established is a failed
+        // handshake, whose outcome is this reason. A consumed entry was
+        // always mid-handshake: its waiter is what went missing.
+        let handshake_started = conn
+            .as_ref()
+            .filter(|_| established_since.is_none())
+            .map(|c| c.meta.created)
+            .or(consumed.map(|meta| meta.created));
 
         match policy.teardown {
             // Teardown first; the waiter learns the connection is gone only
@@ -586,40 +1217,64 @@ impl<P: BranchControl> Coordinator<P> {
             // 404 a retried DELETE while leaking the branch.
             Teardown::Required => {
                 if let Err(e) = self.remove_branch_bounded(id.clone()).await {
-                    if let Some(state) = state {
-                        self.connections.insert(id, state);
+                    if let Some(conn) = conn {
+                        self.connections.insert(id, conn);
                     }
                     return Err(e);
                 }
-                if let Some(state) = state {
-                    state.notify(policy.waiter, &id);
+                if let Some(conn) = conn {
+                    conn.state.notify(policy.waiter, &id);
                 }
             }
             // The connection dies regardless of what the detach says:
             // notify the waiter immediately, then detach, only logging a
             // teardown failure.
             Teardown::BestEffort => {
-                if let Some(state) = state {
-                    state.notify(policy.waiter, &id);
+                if let Some(conn) = conn {
+                    conn.state.notify(policy.waiter, &id);
                 }
                 if let Err(e) = self.remove_branch_bounded(id.clone()).await {
                     tracing::error!("Failed to remove branch for {}: {}", id, e);
                 }
             }
             Teardown::Keep => {
-                if let Some(state) = state {
-                    state.notify(policy.waiter, &id);
+                if let Some(conn) = conn {
+                    conn.state.notify(policy.waiter, &id);
                 }
             }
         }
 
-        if policy.feeds_watchdog && self.watchdog.record_failure() {
-            tracing::error!("Watchdog tripped: requesting a pipeline restart");
-            self.reset_all();
-            // Non-blocking: the supervisor owns the force-quit + rerun. A full
-            // buffer means a restart is already pending, so dropping the extra
-            // request is correct.
-            let _ = self.restart_tx.try_send(());
+        if existed {
+            self.events.publish(Event::ConnectionTerminated {
+                id: id.clone(),
+                reason: reason.name(),
+                duration_secs: established_since.map(|since| since.elapsed().as_secs()),
+            });
+            self.metrics.terminated(reason.name());
+        }
+        if let Some(created) = handshake_started {
+            self.metrics.handshake(reason.name(), created.elapsed());
+        }
+        if let Some(since) = established_since {
+            tracing::info!(
+                "Session {} ended ({:?}) after {:?}",
+                id,
+                reason,
+                since.elapsed()
+            );
+        }
+
+        if policy.feeds_watchdog {
+            let tripped = self.watchdog.record_failure();
+            self.metrics.watchdog_failure(tripped);
+            if tripped {
+                tracing::error!("Watchdog tripped: requesting a pipeline restart");
+                self.reset_all();
+                // Non-blocking: the supervisor owns the force-quit + rerun. A
+                // full buffer means a restart is already pending, so dropping
+                // the extra request is correct.
+                let _ = self.restart_tx.try_send(());
+            }
         }
         Ok(())
     }
@@ -632,7 +1287,16 @@ impl<P: BranchControl> Coordinator<P> {
             return; // already gone: raced a DELETE or an expiry sweep
         }
         tracing::warn!("Reaping branch for {} after a runtime failure", id);
-        let _ = self.terminate(id, TerminateReason::Reaped).await;
+        self.terminate_in_trace(id, TerminateReason::Reaped).await;
+    }
+
+    /// Terminate on the actor's own initiative (sweep, session limits, reap).
+    /// No request carries a span for this work, so it joins the connection's
+    /// trace directly.
+    async fn terminate_in_trace(&mut self, id: ConnectionId, reason: TerminateReason) {
+        let span = tracing::info_span!("terminate", id = %id, reason = reason.name());
+        join_connection_trace(&span, &id);
+        let _ = self.terminate(id, reason).instrument(span).await;
     }
 
     /// Remove a branch, bounded by `teardown_timeout`. All teardown awaits run
@@ -640,13 +1304,23 @@ impl<P: BranchControl> Coordinator<P> {
     /// GStreamer teardown would stall every signaling command, the expiry
     /// sweep, and the watchdog. A timeout surfaces as a retryable error.
     async fn remove_branch_bounded(&self, id: ConnectionId) -> Result<(), SignalError> {
+        let started = Instant::now();
         match tokio::time::timeout(
             self.config.teardown_timeout,
-            self.pipeline.remove_branch(id.clone()),
+            self.pipeline
+                .remove_branch(id.clone())
+                .instrument(tracing::info_span!("remove_branch", id = %id)),
         )
         .await
         {
-            Ok(res) => res.map_err(SignalError::from),
+            Ok(Ok(())) => {
+                self.metrics.branch_detached(started.elapsed());
+                Ok(())
+            }
+            Ok(Err(e)) => {
+                self.metrics.pipeline_error(e.kind());
+                Err(e.into())
+            }
             Err(_) => {
                 tracing::error!(
                     "Branch teardown for {} exceeded {:?}",
@@ -660,6 +1334,29 @@ impl<P: BranchControl> Coordinator<P> {
         }
     }
 
+    /// Any other pipeline call made inline in the actor's loop, bounded by
+    /// `teardown_timeout` like [`Self::remove_branch_bounded`]: each one
+    /// waits for the pipeline state lock, and some link or unlink elements
+    /// in the running pipeline. `what` names the call in the log and the
+    /// error.
+    async fn pipeline_call_bounded<T>(
+        &self,
+        what: &str,
+        call: impl Future<Output = Result<T, PipelineError>>,
+    ) -> Result<T, SignalError> {
+        match tokio::time::timeout(self.config.teardown_timeout, call).await {
+            Ok(result) => result.map_err(SignalError::from),
+            Err(_) => {
+                tracing::error!(
+                    "Pipeline {} exceeded {:?}",
+                    what,
+                    self.config.teardown_timeout
+                );
+                Err(SignalError::PipelineBusy(format!("{} timed out", what)))
+            }
+        }
+    }
+
     /// Apply the `Reset` termination row to the whole map at once: every
     /// parked waiter learns the signaling plane is unavailable, and the
     /// branches are deliberately kept (`Teardown::Keep`) — the supervisor
@@ -668,8 +1365,20 @@ impl<P: BranchControl> Coordinator<P> {
     /// so it applies the same policy row without going through it.
     fn reset_all(&mut self) {
         let policy = TerminateReason::Reset.policy();
-        for (id, state) in self.connections.drain() {
-            state.notify(policy.waiter, &id);
+        for (id, conn) in self.connections.drain() {
+            let established_since = conn.state.established_since();
+            let duration_secs = established_since.map(|since| since.elapsed().as_secs());
+            if established_since.is_none() {
+                self.metrics
+                    .handshake(TerminateReason::Reset.name(), conn.meta.created.elapsed());
+            }
+            self.metrics.terminated(TerminateReason::Reset.name());
+            conn.state.notify(policy.waiter, &id);
+            self.events.publish(Event::ConnectionTerminated {
+                id,
+                reason: TerminateReason::Reset.name(),
+                duration_secs,
+            });
         }
     }
 }
@@ -679,8 +1388,15 @@ mod tests {
     use super::ConnectionState;
     use super::CoordinatorConfig;
     use crate::domain::{SdpAnswer, SdpOffer, VALID_WHEP_ANSWER, VALID_WHIP_OFFER};
-    use crate::signal::{spawn_coordinator, ResetHandle, ResetSignal, SignalError, SignalHandle};
-    use crate::stream::{BranchId, TestPipeline};
+    use crate::events::{Event, EventBus};
+    use crate::metrics::Metrics;
+    use crate::signal::{
+        spawn_coordinator, AdminHandle, ClientInfo, ResetHandle, ResetSignal, SignalError,
+        SignalHandle,
+    };
+    use crate::stream::{
+        BranchId, CandidatePair, IceCandidate, Passthrough, StreamStats, TestPipeline, ViewerStats,
+    };
     use std::time::Duration;
     use tokio::sync::{mpsc, oneshot};
     use tokio::time::Instant;
@@ -701,6 +1417,16 @@ mod tests {
             watchdog_window: Duration::from_secs(60),
             sweep_interval: Duration::from_secs(1),
             teardown_timeout: Duration::from_secs(5),
+            ..CoordinatorConfig::default()
+        }
+    }
+
+    fn candidate(address: &str) -> IceCandidate {
+        IceCandidate {
+            address: address.to_string(),
+            port: Some(50000),
+            candidate_type: Some("host".to_string()),
+            protocol: Some("udp".to_string()),
         }
     }
 
@@ -729,7 +1455,14 @@ mod tests {
     ) -> (SignalHandle, ResetHandle, mpsc::Receiver<()>) {
         let (_fail_tx, fail_rx) = mpsc::channel(1);
         let (restart_tx, restart_rx) = mpsc::channel(1);
-        let (handle, reset) = spawn_coordinator(pipeline, config, fail_rx, restart_tx);
+        let (handle, reset) = spawn_coordinator(
+            pipeline,
+            config,
+            fail_rx,
+            restart_tx,
+            EventBus::default(),
+            Metrics::default(),
+        );
         (handle, reset, restart_rx)
     }
 
@@ -741,7 +1474,14 @@ mod tests {
         branch_failures: mpsc::Receiver<BranchId>,
     ) -> (SignalHandle, mpsc::Receiver<()>) {
         let (restart_tx, restart_rx) = mpsc::channel(1);
-        let (handle, _reset) = spawn_coordinator(pipeline, config, branch_failures, restart_tx);
+        let (handle, _reset) = spawn_coordinator(
+            pipeline,
+            config,
+            branch_failures,
+            restart_tx,
+            EventBus::default(),
+            Metrics::default(),
+        );
         (handle, restart_rx)
     }
 
@@ -750,7 +1490,7 @@ mod tests {
         let whep = {
             let handle = handle.clone();
             let id = id.to_string();
-            tokio::spawn(async move { handle.create_connection(id).await })
+            tokio::spawn(async move { handle.create_connection(id, ClientInfo::default()).await })
         };
         tokio::task::yield_now().await; // connection registered
         let whip = {
@@ -769,7 +1509,7 @@ mod tests {
 
     async fn list_ids(handle: &SignalHandle) -> Vec<String> {
         handle
-            .list_connections()
+            .list_connections(None)
             .await
             .unwrap()
             .into_iter()
@@ -785,7 +1525,11 @@ mod tests {
         // Browser connects; create stays in-flight until the offer arrives.
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("a".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         for _ in 0..5 {
             tokio::task::yield_now().await; // let the actor register + add the branch
@@ -821,7 +1565,9 @@ mod tests {
 
         // Awaiting create parks on its reply; the paused clock auto-advances
         // through sweep ticks until the offer deadline fires.
-        let result = handle.create_connection("a".to_string()).await;
+        let result = handle
+            .create_connection("a".to_string(), ClientInfo::default())
+            .await;
         assert!(matches!(result, Err(SignalError::Timeout("SDP offer"))));
         tokio::task::yield_now().await; // let the actor finish branch cleanup
 
@@ -837,7 +1583,11 @@ mod tests {
 
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("a".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         tokio::task::yield_now().await;
         let whip = {
@@ -860,7 +1610,11 @@ mod tests {
 
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("a".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         for _ in 0..5 {
             tokio::task::yield_now().await; // let the actor register the connection
@@ -888,7 +1642,9 @@ mod tests {
             let whep = {
                 let handle = handle.clone();
                 let id = id.clone();
-                tokio::spawn(async move { handle.create_connection(id).await })
+                tokio::spawn(
+                    async move { handle.create_connection(id, ClientInfo::default()).await },
+                )
             };
             for _ in 0..5 {
                 tokio::task::yield_now().await; // register + add the branch
@@ -920,7 +1676,11 @@ mod tests {
 
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("a".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         tokio::task::yield_now().await; // connection registered
         let whip = {
@@ -955,7 +1715,10 @@ mod tests {
         pipeline.fail_next_add_branch(PipelineError::Transient("state lock timed out".into()));
         let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config());
 
-        let err = handle.create_connection("a".to_string()).await.unwrap_err();
+        let err = handle
+            .create_connection("a".to_string(), ClientInfo::default())
+            .await
+            .unwrap_err();
         // Retryable at the seam stays retryable on the wire: 503 + Retry-After.
         assert_eq!(503, err.status_code().as_u16());
         assert!(err.error_response().headers().get("Retry-After").is_some());
@@ -963,12 +1726,19 @@ mod tests {
 
     #[tokio::test(start_paused = true)]
     async fn not_ready_pipeline_rejects_creation() {
+        use crate::stream::NotReadyReason;
+
         let pipeline = TestPipeline::default(); // ready = false
         let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config());
 
         assert!(matches!(
-            handle.create_connection("a".to_string()).await,
-            Err(SignalError::NotReady)
+            handle
+                .create_connection("a".to_string(), ClientInfo::default())
+                .await,
+            Err(SignalError::NotReady {
+                reason: NotReadyReason::NoInput,
+                retry_after: None,
+            })
         ));
         assert!(pipeline.snapshot().added.is_empty());
     }
@@ -1000,13 +1770,19 @@ mod tests {
         // First create stays in-flight (awaiting its offer).
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("a".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         tokio::task::yield_now().await; // register "a"
 
         // Duplicate create is rejected.
         assert!(matches!(
-            handle.create_connection("a".to_string()).await,
+            handle
+                .create_connection("a".to_string(), ClientInfo::default())
+                .await,
             Err(SignalError::WrongState(_))
         ));
         // PATCH before the offer exists is a wrong-state command.
@@ -1038,7 +1814,9 @@ mod tests {
         for i in 0..3 {
             // Each handshake times out via auto-advance.
             assert!(matches!(
-                handle.create_connection(format!("conn-{}", i)).await,
+                handle
+                    .create_connection(format!("conn-{}", i), ClientInfo::default())
+                    .await,
                 Err(SignalError::Timeout(_))
             ));
         }
@@ -1054,13 +1832,19 @@ mod tests {
 
         // Two failures.
         for i in 0..2 {
-            let _ = handle.create_connection(format!("fail-{}", i)).await;
+            let _ = handle
+                .create_connection(format!("fail-{}", i), ClientInfo::default())
+                .await;
         }
 
         // One success resets the counter.
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("ok".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("ok".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         tokio::task::yield_now().await;
         let whip = {
@@ -1076,7 +1860,9 @@ mod tests {
 
         // Two more failures: still below threshold thanks to the reset.
         for i in 2..4 {
-            let _ = handle.create_connection(format!("fail-{}", i)).await;
+            let _ = handle
+                .create_connection(format!("fail-{}", i), ClientInfo::default())
+                .await;
         }
 
         tokio::task::yield_now().await;
@@ -1091,7 +1877,9 @@ mod tests {
 
         // Two consecutive timeout failures.
         for i in 0..2 {
-            let _ = handle.create_connection(format!("fail-{}", i)).await;
+            let _ = handle
+                .create_connection(format!("fail-{}", i), ClientInfo::default())
+                .await;
         }
 
         // Pipeline restarted: supervisor sends Reset.
@@ -1099,7 +1887,9 @@ mod tests {
 
         // Two more failures on the fresh pipeline: still below threshold.
         for i in 2..4 {
-            let _ = handle.create_connection(format!("fail-{}", i)).await;
+            let _ = handle
+                .create_connection(format!("fail-{}", i), ClientInfo::default())
+                .await;
         }
 
         tokio::task::yield_now().await;
@@ -1113,7 +1903,11 @@ mod tests {
 
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("a".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         tokio::task::yield_now().await; // register the in-flight waiter
 
@@ -1123,6 +1917,95 @@ mod tests {
         assert!(list_ids(&handle).await.is_empty());
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn kick_terminates_connections_and_drain_refuses_new_ones() {
+        let pipeline = ready_pipeline();
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config());
+        let (restart_tx, mut admin_restart_rx) = mpsc::channel(1);
+        let admin = AdminHandle::new(handle.clone(), restart_tx);
+
+        establish(&handle, "a").await;
+        let whep = {
+            let handle = handle.clone();
+            tokio::spawn(async move {
+                handle
+                    .create_connection("b".to_string(), ClientInfo::default())
+                    .await
+            })
+        };
+        tokio::task::yield_now().await; // register the in-flight waiter
+
+        admin.kick("a".to_string()).await.unwrap();
+        assert!(pipeline.snapshot().removed.contains(&"a".to_string()));
+        assert!(matches!(
+            admin.kick("a".to_string()).await,
+            Err(SignalError::NotFound(_))
+        ));
+        assert_eq!(1, admin.kick_all().await.unwrap());
+        assert!(matches!(whep.await.unwrap(), Err(SignalError::Gone(_))));
+        assert!(list_ids(&handle).await.is_empty());
+
+        admin.set_draining(true).await.unwrap();
+        assert!(matches!(
+            handle
+                .create_connection("c".to_string(), ClientInfo::default())
+                .await,
+            Err(SignalError::Draining)
+        ));
+        assert!(matches!(
+            handle.pipeline_ready().await,
+            Err(SignalError::Draining)
+        ));
+        admin.set_draining(false).await.unwrap();
+        establish(&handle, "c").await;
+
+        admin.restart().unwrap();
+        assert_eq!(Some(()), admin_restart_rx.recv().await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconfigure_swaps_the_config_for_later_handshakes() {
+        let pipeline = ready_pipeline();
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config());
+        let (restart_tx, _admin_restart_rx) = mpsc::channel(1);
+        let admin = AdminHandle::new(handle.clone(), restart_tx);
+
+        let invalid = CoordinatorConfig {
+            sweep_interval: Duration::ZERO,
+            ..test_config()
+        };
+        assert!(matches!(
+            admin.reconfigure(invalid).await,
+            Err(SignalError::InvalidConfig(_))
+        ));
+
+        let faster = CoordinatorConfig {
+            offer_timeout: Duration::from_secs(1),
+            sweep_interval: Duration::from_millis(100),
+            ..test_config()
+        };
+        let previous = admin.reconfigure(faster).await.unwrap();
+        assert_eq!(Duration::from_secs(5), previous.offer_timeout);
+        assert_eq!(
+            Duration::from_secs(1),
+            admin.config().await.unwrap().offer_timeout
+        );
+
+        // The new offer timeout and sweep cadence govern the next handshake.
+        let whep = {
+            let handle = handle.clone();
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
+        };
+        tokio::task::yield_now().await;
+        tokio::time::advance(Duration::from_millis(1200)).await;
+        assert!(matches!(whep.await.unwrap(), Err(SignalError::Timeout(_))));
+        assert_eq!(vec!["a".to_string()], pipeline.snapshot().removed);
+    }
+
     #[tokio::test(start_paused = true)]
     async fn list_reports_ids_and_state_names() {
         let pipeline = ready_pipeline();
@@ -1130,13 +2013,17 @@ mod tests {
 
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("a".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         for _ in 0..5 {
             tokio::task::yield_now().await; // register the connection
         }
 
-        let list = handle.list_connections().await.unwrap();
+        let list = handle.list_connections(None).await.unwrap();
         assert_eq!(1, list.len());
         assert_eq!("a", list[0].id);
         assert_eq!("awaiting_offer", list[0].state);
@@ -1144,6 +2031,197 @@ mod tests {
         whep.abort(); // in-flight create no longer needed
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn list_filters_by_state_and_reports_connection_metadata() {
+        let pipeline = ready_pipeline();
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config());
+        establish(&handle, "a").await;
+        let pair = CandidatePair {
+            local: candidate("10.0.0.1"),
+            remote: candidate("192.168.1.7"),
+        };
+        pipeline.select_candidate_pair("a", pair.clone());
+
+        let whep = {
+            let handle = handle.clone();
+            let client = ClientInfo {
+                remote_ip: Some("192.168.1.8".to_string()),
+                user_agent: Some("curl/8.0".to_string()),
+            };
+            tokio::spawn(async move { handle.create_connection("b".to_string(), client).await })
+        };
+        for _ in 0..5 {
+            tokio::task::yield_now().await; // register the connection
+        }
+        tokio::time::advance(Duration::from_secs(3)).await;
+        for _ in 0..5 {
+            tokio::task::yield_now().await; // let the sweep sample the pair
+        }
+
+        let established = handle
+            .list_connections(Some("established".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(1, established.len());
+        assert_eq!("a", established[0].id);
+        assert_eq!(3, established[0].state_secs);
+        assert_eq!(Some(3), established[0].established_secs);
+        assert_eq!(vec!["H264/90000".to_string()], established[0].codecs);
+        assert_eq!(Some(pair), established[0].candidate_pair);
+
+        let waiting = handle
+            .list_connections(Some("awaiting_offer".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(1, waiting.len());
+        assert_eq!("b", waiting[0].id);
+        assert_eq!(3, waiting[0].age_secs);
+        assert_eq!(Some("192.168.1.8"), waiting[0].remote_ip.as_deref());
+        assert_eq!(Some("curl/8.0"), waiting[0].user_agent.as_deref());
+        assert!(waiting[0].codecs.is_empty());
+        assert_eq!(None, waiting[0].candidate_pair);
+
+        assert_eq!(2, handle.list_connections(None).await.unwrap().len());
+        whep.abort(); // in-flight create no longer needed
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn connection_stats_report_the_bitrate_between_sweep_ticks() {
+        let pipeline = ready_pipeline();
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config());
+        establish(&handle, "a").await;
+        let sent = |bytes_sent| ViewerStats {
+            bitrate_bps: None,
+            streams: vec![StreamStats {
+                bytes_sent: Some(bytes_sent),
+                ..StreamStats::default()
+            }],
+        };
+
+        // The first sample has no previous one to measure against.
+        pipeline.set_viewer_stats("a", sent(1_000));
+        tokio::time::advance(Duration::from_secs(1)).await;
+        let first = handle.connection_stats("a".to_string()).await.unwrap();
+        assert_eq!(Some(1_000), first.streams[0].bytes_sent);
+        assert_eq!(None, first.bitrate_bps);
+
+        pipeline.set_viewer_stats("a", sent(26_000));
+        tokio::time::advance(Duration::from_secs(1)).await;
+        let second = handle.connection_stats("a".to_string()).await.unwrap();
+        assert_eq!(Some(200_000), second.bitrate_bps);
+        // Reading again does not take another sample.
+        let again = handle.connection_stats("a".to_string()).await.unwrap();
+        assert_eq!(second, again);
+
+        let aggregate = handle.aggregate_stats().await.unwrap();
+        assert_eq!(1, aggregate.viewers);
+        assert_eq!(26_000, aggregate.bytes_sent);
+
+        assert!(matches!(
+            handle.connection_stats("missing".to_string()).await,
+            Err(SignalError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn every_transition_is_published_as_an_event() {
+        let events = EventBus::default();
+        let mut rx = events.subscribe();
+        let (_fail_tx, fail_rx) = mpsc::channel(1);
+        let (restart_tx, _restart_rx) = mpsc::channel(1);
+        let (handle, _reset) = spawn_coordinator(
+            ready_pipeline(),
+            test_config(),
+            fail_rx,
+            restart_tx,
+            events,
+            Metrics::default(),
+        );
+
+        establish(&handle, "a").await;
+        tokio::time::advance(Duration::from_secs(7)).await;
+        handle.remove_connection("a".to_string()).await.unwrap();
+
+        let id = "a".to_string();
+        for expected in [
+            Event::ConnectionCreated { id: id.clone() },
+            Event::OfferDelivered { id: id.clone() },
+            Event::ConnectionEstablished { id: id.clone() },
+            Event::ConnectionTerminated {
+                id: id.clone(),
+                reason: "deleted",
+                duration_secs: Some(7),
+            },
+        ] {
+            assert_eq!(expected, rx.try_recv().unwrap());
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn vanished_waiter_publishes_one_peer_gone_termination() {
+        let events = EventBus::default();
+        let mut rx = events.subscribe();
+        let (_fail_tx, fail_rx) = mpsc::channel(1);
+        let (restart_tx, _restart_rx) = mpsc::channel(1);
+        let (handle, _reset) = spawn_coordinator(
+            ready_pipeline(),
+            test_config(),
+            fail_rx,
+            restart_tx,
+            events,
+            Metrics::default(),
+        );
+
+        let whep = {
+            let handle = handle.clone();
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
+        };
+        for _ in 0..5 {
+            tokio::task::yield_now().await; // register + add the branch
+        }
+        whep.abort();
+        tokio::task::yield_now().await;
+        assert!(matches!(
+            handle.offer_received("a".to_string(), offer()).await,
+            Err(SignalError::Gone(_))
+        ));
+        tokio::task::yield_now().await; // let the actor finish terminating
+
+        let id = "a".to_string();
+        assert_eq!(
+            Event::ConnectionCreated { id: id.clone() },
+            rx.try_recv().unwrap()
+        );
+        assert_eq!(
+            Event::ConnectionTerminated {
+                id,
+                reason: "peer_gone",
+                duration_secs: None,
+            },
+            rx.try_recv().unwrap()
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn connection_info_describes_one_connection() {
+        let pipeline = ready_pipeline();
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config());
+        establish(&handle, "a").await;
+
+        let info = handle.connection_info("a".to_string()).await.unwrap();
+        assert_eq!("a", info.id);
+        assert_eq!("established", info.state);
+        assert!(matches!(
+            handle.connection_info("missing".to_string()).await,
+            Err(SignalError::NotFound(_))
+        ));
+    }
+
     #[tokio::test(start_paused = true)]
     async fn failed_add_registers_nothing_and_needs_no_coordinator_cleanup() {
         use crate::stream::PipelineError;
@@ -1153,7 +2231,10 @@ mod tests {
         let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config());
 
         // The create fails and the id is never registered...
-        assert!(handle.create_connection("a".to_string()).await.is_err());
+        assert!(handle
+            .create_connection("a".to_string(), ClientInfo::default())
+            .await
+            .is_err());
         tokio::task::yield_now().await;
 
         // ...and the coordinator issues NO cleanup: add_branch owns detaching
@@ -1211,6 +2292,81 @@ mod tests {
         assert!(restart_rx.try_recv().is_err());
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn established_session_is_terminated_at_its_maximum_duration() {
+        let pipeline = ready_pipeline();
+        let config = CoordinatorConfig {
+            max_session_duration: Some(Duration::from_secs(30)),
+            ..test_config()
+        };
+        let (handle, mut restart_rx) = spawn_actor(pipeline.clone(), config);
+        establish(&handle, "a").await;
+
+        // Within the limit the session lives on, and reports its age.
+        tokio::time::advance(Duration::from_secs(20)).await;
+        tokio::task::yield_now().await;
+        let list = handle.list_connections(None).await.unwrap();
+        assert_eq!(1, list.len());
+        assert_eq!(Some(20), list[0].established_secs);
+
+        // Past it, the sweep terminates the session and detaches its branch.
+        tokio::time::advance(Duration::from_secs(11)).await;
+        for _ in 0..5 {
+            tokio::task::yield_now().await; // let the sweep run
+        }
+        assert!(pipeline.snapshot().removed.contains(&"a".to_string()));
+        assert!(list_ids(&handle).await.is_empty());
+        // Running out the clock is policy, not a pipeline-health failure.
+        assert!(restart_rx.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn silent_viewer_is_terminated_after_the_idle_timeout() {
+        let pipeline = ready_pipeline();
+        let config = CoordinatorConfig {
+            idle_timeout: Some(Duration::from_secs(10)),
+            ..test_config()
+        };
+        let (handle, mut restart_rx) = spawn_actor(pipeline.clone(), config);
+        establish(&handle, "a").await;
+
+        // A viewer that keeps sending receiver reports outlives the timeout.
+        for _ in 0..3 {
+            pipeline.report_receiver("a");
+            tokio::time::advance(Duration::from_secs(6)).await;
+            for _ in 0..5 {
+                tokio::task::yield_now().await; // let the sweep see the report
+            }
+        }
+        assert_eq!(vec!["a".to_string()], list_ids(&handle).await);
+
+        // Once it goes silent for the whole timeout, the session is ended.
+        tokio::time::advance(Duration::from_secs(11)).await;
+        for _ in 0..5 {
+            tokio::task::yield_now().await;
+        }
+        assert!(pipeline.snapshot().removed.contains(&"a".to_string()));
+        assert!(list_ids(&handle).await.is_empty());
+        assert!(restart_rx.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_viewer_is_not_idle_while_its_sink_cannot_be_read() {
+        let pipeline = ready_pipeline();
+        let config = CoordinatorConfig {
+            idle_timeout: Some(Duration::from_secs(10)),
+            ..test_config()
+        };
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), config);
+        establish(&handle, "a").await;
+
+        // Every tick's read times out: no tick counts as silence.
+        pipeline.block_stats();
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        assert_eq!(vec!["a".to_string()], list_ids(&handle).await);
+        assert!(pipeline.snapshot().removed.is_empty());
+    }
+
     #[tokio::test(start_paused = true)]
     async fn wedged_teardown_does_not_stall_the_actor() {
         let pipeline = ready_pipeline();
@@ -1228,6 +2384,74 @@ mod tests {
         assert_eq!(vec!["a".to_string()], list_ids(&handle).await);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn wedged_output_calls_time_out_without_stalling_the_actor() {
+        let pipeline = ready_pipeline();
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config()); // teardown_timeout 5s
+        let (restart_tx, _admin_restart_rx) = mpsc::channel(1);
+        let admin = AdminHandle::new(handle.clone(), restart_tx);
+        let output = Passthrough::parse("srt://127.0.0.1:9000").unwrap();
+        admin.add_output(output.clone()).await.unwrap();
+
+        pipeline.block_outputs();
+        assert!(matches!(
+            admin.add_output(output).await,
+            Err(SignalError::PipelineBusy(_))
+        ));
+        assert!(matches!(
+            admin.remove_output(0).await,
+            Err(SignalError::PipelineBusy(_))
+        ));
+        assert!(matches!(
+            admin.disconnect_caller(0, 0).await,
+            Err(SignalError::PipelineBusy(_))
+        ));
+
+        // The actor is still responsive.
+        establish(&handle, "a").await;
+        assert_eq!(vec!["a".to_string()], list_ids(&handle).await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wedged_viewer_sampling_times_out_without_stalling_the_actor() {
+        let pipeline = ready_pipeline();
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config()); // teardown_timeout 5s
+        establish(&handle, "a").await;
+        establish(&handle, "b").await;
+
+        // Every sweep tick now parks on the stats read until the bound
+        // fires; one bound per tick, not one per viewer.
+        pipeline.block_stats();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let started = Instant::now();
+        let mut ids = list_ids(&handle).await;
+        assert!(started.elapsed() <= Duration::from_secs(5));
+        ids.sort();
+        assert_eq!(vec!["a".to_string(), "b".to_string()], ids);
+        assert!(handle
+            .connection_stats("a".to_string())
+            .await
+            .unwrap()
+            .streams
+            .is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wedged_input_stats_time_out_without_stalling_the_actor() {
+        let pipeline = ready_pipeline();
+        let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config()); // teardown_timeout 5s
+        establish(&handle, "a").await;
+
+        pipeline.block_stats();
+        assert!(matches!(
+            handle.input_stats().await,
+            Err(SignalError::PipelineBusy(_))
+        ));
+
+        // The actor is still responsive.
+        assert_eq!(vec!["a".to_string()], list_ids(&handle).await);
+    }
+
     #[tokio::test(start_paused = true)]
     async fn wedged_add_branch_cleanup_times_out_to_a_retryable_error() {
         use actix_web::ResponseError;
@@ -1236,7 +2460,10 @@ mod tests {
         pipeline.block_add_branch(); // simulates a wedged cleanup detach
         let (handle, _restart_rx) = spawn_actor(pipeline.clone(), test_config()); // teardown_timeout 5s
 
-        let err = handle.create_connection("a".to_string()).await.unwrap_err();
+        let err = handle
+            .create_connection("a".to_string(), ClientInfo::default())
+            .await
+            .unwrap_err();
         // The bound fires (auto-advanced paused clock) and the actor recovers
         // with a retryable error instead of hanging forever.
         assert_eq!(503, err.status_code().as_u16());
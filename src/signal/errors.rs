@@ -1,9 +1,14 @@
 use crate::domain::SdpError;
-use crate::stream::PipelineError;
+use crate::stream::{NotReadyReason, PipelineError};
 use actix_web::http::StatusCode;
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{HttpResponse, HttpResponseBuilder, ResponseError};
+use serde::Serialize;
+use std::time::Duration;
 use thiserror::Error;
 
+/// Retry-After, in seconds, for a retryable failure with no better estimate.
+const DEFAULT_RETRY_AFTER_SECS: u64 = 3;
+
 #[derive(Error, Debug)]
 pub enum SignalError {
     #[error("Invalid SDP: {0}")]
@@ -25,14 +30,32 @@ pub enum SignalError {
     /// leaves the map, subsequent requests get `NotFound`.
     #[error("Connection {0} is gone")]
     Gone(String),
+    /// No passthrough output has this id.
+    #[error("Output {0} not found")]
+    OutputNotFound(usize),
+    /// The output is not an SRT listener, or has no caller with this id.
+    #[error("Output {output} has no caller {caller}")]
+    CallerNotFound { output: usize, caller: usize },
     #[error("Connection {0} is in the wrong state for this operation")]
     WrongState(String),
     #[error("Timed out waiting for the {0}")]
     Timeout(&'static str),
-    #[error("Input stream is not ready")]
-    NotReady,
+    /// The pipeline cannot take a viewer yet, for `reason`. `retry_after`
+    /// is the supervisor's remaining backoff when it is between runs —
+    /// attached by the HTTP layer, which can see the supervisor.
+    #[error("Input stream is not ready: {reason}")]
+    NotReady {
+        reason: NotReadyReason,
+        retry_after: Option<Duration>,
+    },
     #[error("Pipeline is busy: {0}")]
     PipelineBusy(String),
+    /// A runtime configuration change was refused; nothing was applied.
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    /// Drain mode: existing sessions continue, new ones are refused.
+    #[error("Server is draining; not accepting new connections")]
+    Draining,
     #[error("Signaling coordinator is unavailable")]
     Unavailable,
     #[error("Pipeline operation failed: {0}")]
@@ -40,21 +63,47 @@ pub enum SignalError {
 }
 
 impl SignalError {
+    /// Attach the time until the pipeline is rerun to a `NotReady` error, so
+    /// its Retry-After points past the backoff. Other variants are returned
+    /// unchanged.
+    pub fn with_retry_after(self, delay: Duration) -> Self {
+        match self {
+            SignalError::NotReady { reason, .. } => SignalError::NotReady {
+                reason,
+                retry_after: Some(delay),
+            },
+            other => other,
+        }
+    }
+
     /// The HTTP contract in one match: the status a failure gets, and the
-    /// Retry-After to attach when a retry is worthwhile. One arm decides
-    /// both for each variant, so the retryable set (503 + Retry-After) is
-    /// spelled exactly once and the two can never drift.
-    fn http_contract(&self) -> (StatusCode, Option<&'static str>) {
+    /// Retry-After (in seconds) to attach when a retry is worthwhile. One
+    /// arm decides both for each variant, so the retryable set (503 +
+    /// Retry-After) is spelled exactly once and the two can never drift.
+    fn http_contract(&self) -> (StatusCode, Option<u64>) {
         match self {
-            SignalError::InvalidSdp(_) | SignalError::Sdp(_) => (StatusCode::BAD_REQUEST, None),
-            SignalError::NotFound(_) => (StatusCode::NOT_FOUND, None),
+            SignalError::InvalidSdp(_) | SignalError::Sdp(_) | SignalError::InvalidConfig(_) => {
+                (StatusCode::BAD_REQUEST, None)
+            }
+            SignalError::NotFound(_)
+            | SignalError::OutputNotFound(_)
+            | SignalError::CallerNotFound { .. } => (StatusCode::NOT_FOUND, None),
             // No Retry-After: a dead session will not come back — the
             // client's move is a fresh POST, not a retry of this request.
             SignalError::Gone(_) => (StatusCode::GONE, None),
             SignalError::WrongState(_) => (StatusCode::CONFLICT, None),
-            SignalError::Timeout(_) | SignalError::NotReady | SignalError::PipelineBusy(_) => {
-                (StatusCode::SERVICE_UNAVAILABLE, Some("3"))
+            SignalError::NotReady { retry_after, .. } => {
+                // Whole seconds, rounded up, and never 0: "retry now" would
+                // land inside the backoff.
+                let seconds = retry_after.map_or(DEFAULT_RETRY_AFTER_SECS, |delay| {
+                    delay.as_secs_f64().ceil().max(1.0) as u64
+                });
+                (StatusCode::SERVICE_UNAVAILABLE, Some(seconds))
             }
+            SignalError::Timeout(_) | SignalError::PipelineBusy(_) | SignalError::Draining => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Some(DEFAULT_RETRY_AFTER_SECS),
+            ),
             SignalError::Unavailable | SignalError::Pipeline(_) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, None)
             }
@@ -71,9 +120,104 @@ impl ResponseError for SignalError {
         let (status, retry_after) = self.http_contract();
         let mut builder = HttpResponse::build(status);
         if let Some(seconds) = retry_after {
-            builder.append_header(("Retry-After", seconds));
+            builder.append_header(("Retry-After", seconds.to_string()));
+        }
+        self.problem().respond(builder)
+    }
+}
+
+/// An RFC 9457 problem details body (`application/problem+json`). `type`
+/// and `code` are stable per variant — clients branch on them, never on
+/// `detail`, which is the human-readable message. Every API error is one,
+/// including those from outside the signaling plane.
+#[derive(Debug, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    problem_type: String,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    code: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    connection_id: Option<String>,
+    /// Seconds, mirroring the Retry-After header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after: Option<u64>,
+    /// `reason` (and `caps`) for a not-ready pipeline.
+    #[serde(flatten)]
+    not_ready: Option<NotReadyReason>,
+}
+
+impl Problem {
+    /// A problem about no connection, with no retry hint.
+    pub fn new(
+        status: StatusCode,
+        code: &'static str,
+        title: &'static str,
+        detail: String,
+    ) -> Self {
+        Problem {
+            problem_type: format!("urn:srt-whep:error:{}", code),
+            title,
+            status: status.as_u16(),
+            detail,
+            code,
+            connection_id: None,
+            retry_after: None,
+            not_ready: None,
+        }
+    }
+
+    /// `builder`'s response, with this as its body. The status and any
+    /// headers are `builder`'s to set.
+    pub fn respond(&self, mut builder: HttpResponseBuilder) -> HttpResponse {
+        let body = serde_json::to_string(self).unwrap_or_default();
+        builder.content_type("application/problem+json").body(body)
+    }
+}
+
+impl SignalError {
+    /// The stable machine-readable code and short title of each variant.
+    fn problem_kind(&self) -> (&'static str, &'static str) {
+        match self {
+            SignalError::InvalidSdp(_) | SignalError::Sdp(_) => ("invalid_sdp", "Invalid SDP"),
+            SignalError::NotFound(_) => ("not_found", "Connection not found"),
+            SignalError::Gone(_) => ("gone", "Connection is gone"),
+            SignalError::OutputNotFound(_) => ("output_not_found", "Output not found"),
+            SignalError::CallerNotFound { .. } => ("caller_not_found", "Caller not found"),
+            SignalError::WrongState(_) => ("wrong_state", "Connection is in the wrong state"),
+            SignalError::Timeout(_) => ("timeout", "Timed out"),
+            SignalError::NotReady { .. } => ("not_ready", "Input stream is not ready"),
+            SignalError::PipelineBusy(_) => ("pipeline_busy", "Pipeline is busy"),
+            SignalError::InvalidConfig(_) => ("invalid_config", "Invalid configuration"),
+            SignalError::Draining => ("draining", "Server is draining"),
+            SignalError::Unavailable => ("unavailable", "Signaling is unavailable"),
+            SignalError::Pipeline(_) => ("pipeline_error", "Pipeline operation failed"),
+        }
+    }
+
+    /// The connection the failure is about, for variants that name one.
+    fn connection_id(&self) -> Option<&str> {
+        match self {
+            SignalError::NotFound(id) | SignalError::Gone(id) | SignalError::WrongState(id) => {
+                Some(id)
+            }
+            _ => None,
+        }
+    }
+
+    fn problem(&self) -> Problem {
+        let (status, retry_after) = self.http_contract();
+        let (code, title) = self.problem_kind();
+        Problem {
+            connection_id: self.connection_id().map(str::to_string),
+            retry_after,
+            not_ready: match self {
+                SignalError::NotReady { reason, .. } => Some(reason.clone()),
+                _ => None,
+            },
+            ..Problem::new(status, code, title, self.to_string())
         }
-        builder.body(self.to_string())
     }
 }
 
@@ -82,7 +226,10 @@ impl ResponseError for SignalError {
 impl From<PipelineError> for SignalError {
     fn from(e: PipelineError) -> Self {
         match e {
-            PipelineError::NotReady => SignalError::NotReady,
+            PipelineError::NotReady(reason) => SignalError::NotReady {
+                reason,
+                retry_after: None,
+            },
             PipelineError::Transient(msg) => SignalError::PipelineBusy(msg),
             PipelineError::Fatal(msg) => SignalError::Pipeline(msg),
         }
@@ -92,8 +239,17 @@ impl From<PipelineError> for SignalError {
 #[cfg(test)]
 mod tests {
     use super::SignalError;
+    use crate::stream::NotReadyReason;
     use actix_web::http::StatusCode;
     use actix_web::ResponseError;
+    use std::time::Duration;
+
+    fn not_ready() -> SignalError {
+        SignalError::NotReady {
+            reason: NotReadyReason::NoInput,
+            retry_after: None,
+        }
+    }
 
     #[test]
     fn status_codes_match_the_api_contract() {
@@ -105,6 +261,18 @@ mod tests {
             StatusCode::NOT_FOUND,
             SignalError::NotFound("x".into()).status_code()
         );
+        assert_eq!(
+            StatusCode::NOT_FOUND,
+            SignalError::OutputNotFound(0).status_code()
+        );
+        assert_eq!(
+            StatusCode::NOT_FOUND,
+            SignalError::CallerNotFound {
+                output: 0,
+                caller: 0
+            }
+            .status_code()
+        );
         assert_eq!(
             StatusCode::GONE,
             SignalError::Gone("x".into()).status_code()
@@ -117,9 +285,10 @@ mod tests {
             StatusCode::SERVICE_UNAVAILABLE,
             SignalError::Timeout("SDP offer").status_code()
         );
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, not_ready().status_code());
         assert_eq!(
             StatusCode::SERVICE_UNAVAILABLE,
-            SignalError::NotReady.status_code()
+            SignalError::Draining.status_code()
         );
         assert_eq!(
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -133,7 +302,7 @@ mod tests {
 
     #[test]
     fn retriable_errors_carry_retry_after() {
-        let resp = SignalError::NotReady.error_response();
+        let resp = not_ready().error_response();
         assert_eq!("3", resp.headers().get("Retry-After").unwrap());
 
         let resp = SignalError::Timeout("SDP answer").error_response();
@@ -151,7 +320,7 @@ mod tests {
     fn pipeline_errors_map_to_retryable_or_fatal_statuses() {
         use crate::stream::PipelineError;
 
-        let not_ready = SignalError::from(PipelineError::NotReady);
+        let not_ready = SignalError::from(PipelineError::NotReady(NotReadyReason::NoInput));
         assert_eq!(StatusCode::SERVICE_UNAVAILABLE, not_ready.status_code());
         assert_eq!(
             "3",
@@ -194,4 +363,61 @@ mod tests {
         assert_eq!(StatusCode::BAD_REQUEST, err.status_code());
         assert_eq!("Invalid SDP: v=1 unsupported", err.to_string());
     }
+
+    #[test]
+    fn not_ready_names_its_reason_and_retries_after_the_backoff() {
+        let err = not_ready();
+        assert!(err.to_string().contains("no input stream"));
+
+        // Rounded up to whole seconds: 1.2s of backoff left → 2.
+        let resp = not_ready()
+            .with_retry_after(Duration::from_millis(1200))
+            .error_response();
+        assert_eq!("2", resp.headers().get("Retry-After").unwrap());
+
+        // Never 0, even with the backoff about to end.
+        let resp = not_ready()
+            .with_retry_after(Duration::ZERO)
+            .error_response();
+        assert_eq!("1", resp.headers().get("Retry-After").unwrap());
+
+        // Only NotReady takes the hint.
+        let resp = SignalError::Timeout("SDP offer")
+            .with_retry_after(Duration::from_secs(20))
+            .error_response();
+        assert_eq!("3", resp.headers().get("Retry-After").unwrap());
+    }
+
+    #[tokio::test]
+    async fn errors_render_as_problem_json() {
+        let resp = SignalError::Gone("abc".into()).error_response();
+        assert_eq!(
+            "application/problem+json",
+            resp.headers().get("Content-Type").unwrap()
+        );
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!("urn:srt-whep:error:gone", problem["type"]);
+        assert_eq!("gone", problem["code"]);
+        assert_eq!(410, problem["status"]);
+        assert_eq!("abc", problem["connection_id"]);
+        assert_eq!("Connection abc is gone", problem["detail"]);
+        assert!(problem.get("retry_after").is_none());
+
+        let resp = SignalError::NotReady {
+            reason: NotReadyReason::UnsupportedCodec {
+                caps: "video/x-vp8".into(),
+            },
+            retry_after: Some(Duration::from_secs(4)),
+        }
+        .error_response();
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!("not_ready", problem["code"]);
+        assert_eq!(503, problem["status"]);
+        assert_eq!(4, problem["retry_after"]);
+        assert_eq!("unsupported_codec", problem["reason"]);
+        assert_eq!("video/x-vp8", problem["caps"]);
+        assert!(problem.get("connection_id").is_none());
+    }
 }
@@ -1,5 +1,9 @@
+use super::coordinator::CoordinatorConfig;
 use super::errors::SignalError;
 use crate::domain::{SdpAnswer, SdpOffer};
+use crate::stream::{
+    AggregateStats, CandidatePair, InputStats, OutputInfo, Passthrough, ViewerStats,
+};
 use serde::Serialize;
 use tokio::sync::oneshot;
 
@@ -7,13 +11,48 @@ pub type ConnectionId = String;
 pub type OfferReply = oneshot::Sender<Result<SdpOffer, SignalError>>;
 pub type AnswerReply = oneshot::Sender<Result<SdpAnswer, SignalError>>;
 pub type UnitReply = oneshot::Sender<Result<(), SignalError>>;
+pub type ReadyReply = oneshot::Sender<Result<(), SignalError>>;
 pub type SnapshotReply = oneshot::Sender<Result<Vec<ConnectionInfo>, SignalError>>;
+pub type InfoReply = oneshot::Sender<Result<ConnectionInfo, SignalError>>;
+pub type StatsReply = oneshot::Sender<Result<ViewerStats, SignalError>>;
+pub type AggregateReply = oneshot::Sender<Result<AggregateStats, SignalError>>;
+pub type InputStatsReply = oneshot::Sender<Result<InputStats, SignalError>>;
+pub type CountReply = oneshot::Sender<Result<usize, SignalError>>;
+pub type ConfigReply = oneshot::Sender<Result<CoordinatorConfig, SignalError>>;
+pub type OutputsReply = oneshot::Sender<Result<Vec<OutputInfo>, SignalError>>;
+pub type OutputReply = oneshot::Sender<Result<OutputInfo, SignalError>>;
 
-/// One entry in GET /list output.
+/// Who opened a connection, as seen by the WHEP POST handler.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub remote_ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// One entry in GET /list output, and the GET /channel/{id} document.
 #[derive(Debug, Clone, Serialize)]
 pub struct ConnectionInfo {
     pub id: String,
     pub state: String,
+    /// Wall-clock creation time, in seconds since the Unix epoch.
+    pub created_unix_secs: u64,
+    /// Whole seconds since the WHEP POST created the connection.
+    pub age_secs: u64,
+    /// Whole seconds spent in the current `state`.
+    pub state_secs: u64,
+    /// Whole seconds since the handshake completed; absent until established.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub established_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Codecs the viewer's SDP answer accepted; empty until it arrives.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub codecs: Vec<String>,
+    /// The ICE candidate pair the session selected, once established.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_pair: Option<CandidatePair>,
 }
 
 #[derive(Debug)]
@@ -22,6 +61,7 @@ pub enum Command {
     /// whipsink delivers it (or an error on timeout/failure).
     CreateConnection {
         id: ConnectionId,
+        client: ClientInfo,
         reply: OfferReply,
     },
     /// Loopback WHIP POST: the whipsink's offer; reply carries the SDP answer
@@ -38,15 +78,95 @@ pub enum Command {
         reply: UnitReply,
     },
     /// WHEP DELETE (or internal cleanup).
-    RemoveConnection {
-        id: ConnectionId,
-        reply: UnitReply,
-    },
+    RemoveConnection { id: ConnectionId, reply: UnitReply },
+    /// GET /list, optionally only the connections in `state`.
     ListConnections {
+        state: Option<String>,
         reply: SnapshotReply,
     },
+    /// GET /channel/{id}.
+    GetConnection { id: ConnectionId, reply: InfoReply },
+    /// GET /channel/{id}/stats.
+    ConnectionStats { id: ConnectionId, reply: StatsReply },
+    /// GET /stats.
+    AggregateStats { reply: AggregateReply },
+    /// GET /input/stats.
+    InputStats { reply: InputStatsReply },
+    /// GET /healthz: replied to at once, proving the actor is responsive.
+    Ping { reply: UnitReply },
+    /// GET /readyz: whether the pipeline can accept a branch.
+    PipelineReady { reply: ReadyReply },
     /// Supervisor: the pipeline restarted; fail all waiters, clear the map.
-    Reset {
+    Reset { reply: UnitReply },
+    /// Admin: terminate one connection as `Kicked`.
+    Kick { id: ConnectionId, reply: UnitReply },
+    /// Admin: terminate every connection as `Kicked`; reply carries how many.
+    KickAll { reply: CountReply },
+    /// Admin: enter or leave drain mode (refuse new connections).
+    SetDraining { draining: bool, reply: UnitReply },
+    /// Graceful shutdown: terminate every connection as `Shutdown`; reply
+    /// carries how many.
+    CloseAll { reply: CountReply },
+    /// Admin: read the coordinator's current configuration.
+    GetConfig { reply: ConfigReply },
+    /// Admin / SIGHUP: swap in a new configuration; reply carries the one
+    /// it replaced.
+    Reconfigure {
+        config: CoordinatorConfig,
+        reply: ConfigReply,
+    },
+    /// Admin: list the passthrough outputs.
+    ListOutputs { reply: OutputsReply },
+    /// Admin: add a passthrough output; reply carries it, with its id.
+    AddOutput {
+        output: Passthrough,
+        reply: OutputReply,
+    },
+    /// Admin: remove a passthrough output; `OutputNotFound` if unknown.
+    RemoveOutput { id: usize, reply: UnitReply },
+    /// Admin: drop a caller of an SRT listener output and refuse it from
+    /// then on; `CallerNotFound` if unknown.
+    DisconnectCaller {
+        output: usize,
+        caller: usize,
         reply: UnitReply,
     },
 }
+
+impl Command {
+    /// The command as its coordinator span names it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::CreateConnection { .. } => "create_connection",
+            Command::OfferReceived { .. } => "offer_received",
+            Command::AnswerReceived { .. } => "answer_received",
+            Command::RemoveConnection { .. } => "remove_connection",
+            Command::ListConnections { .. } => "list_connections",
+            Command::GetConnection { .. } => "get_connection",
+            Command::ConnectionStats { .. } => "connection_stats",
+            Command::AggregateStats { .. } => "aggregate_stats",
+            Command::InputStats { .. } => "input_stats",
+            Command::Ping { .. } => "ping",
+            Command::PipelineReady { .. } => "pipeline_ready",
+            Command::Reset { .. } => "reset",
+            Command::Kick { .. } => "kick",
+            Command::KickAll { .. } => "kick_all",
+            Command::SetDraining { .. } => "set_draining",
+            Command::CloseAll { .. } => "close_all",
+            Command::GetConfig { .. } => "get_config",
+            Command::Reconfigure { .. } => "reconfigure",
+            Command::ListOutputs { .. } => "list_outputs",
+            Command::AddOutput { .. } => "add_output",
+            Command::RemoveOutput { .. } => "remove_output",
+            Command::DisconnectCaller { .. } => "disconnect_caller",
+        }
+    }
+}
+
+/// A command plus the span of the request that sent it: the coordinator
+/// handles it in a child of that span, so its work shows up in the
+/// request's trace even though it runs on the actor's task.
+pub struct Envelope {
+    pub cmd: Command,
+    pub span: tracing::Span,
+}
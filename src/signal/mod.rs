@@ -4,12 +4,16 @@ mod messages;
 mod watchdog;
 
 pub use coordinator::{Coordinator, CoordinatorArgs, CoordinatorConfig};
-pub use errors::SignalError;
-use messages::Command;
-pub use messages::{ConnectionId, ConnectionInfo};
+pub use errors::{Problem, SignalError};
+pub use messages::{ClientInfo, ConnectionId, ConnectionInfo};
+use messages::{Command, Envelope};
 
 use crate::domain::{SdpAnswer, SdpOffer};
-use crate::stream::{BranchControl, BranchId};
+use crate::events::EventBus;
+use crate::metrics::Metrics;
+use crate::stream::{
+    AggregateStats, BranchControl, BranchId, InputStats, OutputInfo, Passthrough, ViewerStats,
+};
 use async_trait::async_trait;
 use tokio::sync::{mpsc, oneshot};
 
@@ -19,7 +23,7 @@ use tokio::sync::{mpsc, oneshot};
 /// by [`ResetHandle`].
 #[derive(Clone)]
 pub struct SignalHandle {
-    tx: mpsc::Sender<Command>,
+    tx: mpsc::Sender<Envelope>,
 }
 
 /// The supervisor's view of signaling: reset after a pipeline stop. Failing
@@ -47,20 +51,146 @@ impl ResetSignal for ResetHandle {
     }
 }
 
+/// The admin API's capabilities: kick viewers, toggle drain mode, manage
+/// the passthrough outputs, and ask the supervisor for a pipeline restart
+/// over the channel the watchdog uses. Kept off [`SignalHandle`] like
+/// [`ResetHandle`] is, so only the authenticated admin scope, which is
+/// handed this, can reach them.
+#[derive(Clone)]
+pub struct AdminHandle {
+    inner: SignalHandle,
+    restart_tx: mpsc::Sender<()>,
+}
+
+impl AdminHandle {
+    /// `restart_tx` must be the sender whose receiver the supervisor holds.
+    pub fn new(signal: SignalHandle, restart_tx: mpsc::Sender<()>) -> Self {
+        Self {
+            inner: signal,
+            restart_tx,
+        }
+    }
+
+    /// Terminate one connection as `Kicked`; `NotFound` for an unknown id.
+    pub async fn kick(&self, id: String) -> Result<(), SignalError> {
+        self.inner
+            .request(|reply| Command::Kick { id, reply })
+            .await
+    }
+
+    /// Terminate every connection as `Kicked`; returns how many there were.
+    pub async fn kick_all(&self) -> Result<usize, SignalError> {
+        self.inner.request(|reply| Command::KickAll { reply }).await
+    }
+
+    /// Enter or leave drain mode: while draining, `create_connection` and
+    /// `pipeline_ready` fail with `Draining` and existing connections are
+    /// left alone.
+    pub async fn set_draining(&self, draining: bool) -> Result<(), SignalError> {
+        self.inner
+            .request(|reply| Command::SetDraining { draining, reply })
+            .await
+    }
+
+    /// Close every connection as `Shutdown`, detaching each branch while
+    /// the pipeline still runs so its peer sees a clean close; returns how
+    /// many there were. The last step of a graceful drain before the EOS.
+    pub async fn close_all(&self) -> Result<usize, SignalError> {
+        self.inner
+            .request(|reply| Command::CloseAll { reply })
+            .await
+    }
+
+    /// The coordinator's current configuration.
+    pub async fn config(&self) -> Result<CoordinatorConfig, SignalError> {
+        self.inner
+            .request(|reply| Command::GetConfig { reply })
+            .await
+    }
+
+    /// Atomically replace the coordinator's configuration; returns the one
+    /// it replaced. `InvalidConfig` (and nothing applied) if `config` could
+    /// not run. Connections keep the deadlines they already have.
+    pub async fn reconfigure(
+        &self,
+        config: CoordinatorConfig,
+    ) -> Result<CoordinatorConfig, SignalError> {
+        self.inner
+            .request(|reply| Command::Reconfigure { config, reply })
+            .await
+    }
+
+    /// The passthrough outputs, in id order.
+    pub async fn outputs(&self) -> Result<Vec<OutputInfo>, SignalError> {
+        self.inner
+            .request(|reply| Command::ListOutputs { reply })
+            .await
+    }
+
+    /// Add a passthrough output, attached at once if the pipeline runs and
+    /// by every run after; returns it with its id.
+    pub async fn add_output(&self, output: Passthrough) -> Result<OutputInfo, SignalError> {
+        self.inner
+            .request(|reply| Command::AddOutput { output, reply })
+            .await
+    }
+
+    /// Remove a passthrough output; `OutputNotFound` for an unknown id.
+    pub async fn remove_output(&self, id: usize) -> Result<(), SignalError> {
+        self.inner
+            .request(|reply| Command::RemoveOutput { id, reply })
+            .await
+    }
+
+    /// Drop caller `caller` of SRT listener output `output`, and refuse its
+    /// host and streamid from then on; `CallerNotFound` if there is none.
+    pub async fn disconnect_caller(&self, output: usize, caller: usize) -> Result<(), SignalError> {
+        self.inner
+            .request(|reply| Command::DisconnectCaller {
+                output,
+                caller,
+                reply,
+            })
+            .await
+    }
+
+    /// Ask the supervisor to force-quit and rerun the pipeline, exactly as a
+    /// watchdog trip does. A full channel means a restart is already
+    /// pending, which is as good; `Unavailable` if the supervisor is gone.
+    pub fn restart(&self) -> Result<(), SignalError> {
+        match self.restart_tx.try_send(()) {
+            Ok(()) | Err(mpsc::error::TrySendError::Full(())) => Ok(()),
+            Err(mpsc::error::TrySendError::Closed(())) => Err(SignalError::Unavailable),
+        }
+    }
+}
+
 /// Spawn the coordinator actor and return its two handles: the route-facing
 /// [`SignalHandle`] and the supervisor's [`ResetHandle`]. `branch_failures`
 /// is the receiving end of the pipeline's bus-reap channel (its sender was
 /// handed to the pipeline at construction), so the sink is present from birth
 /// and no post-construction installer is needed. The watchdog trip sends a
-/// `()` restart request to the supervisor over `restart_tx`.
+/// `()` restart request to the supervisor over `restart_tx`. Connection
+/// transitions are published on `events` and counted in `metrics`.
 pub fn spawn_coordinator<P: BranchControl + 'static>(
     pipeline: P,
     config: CoordinatorConfig,
     branch_failures: mpsc::Receiver<BranchId>,
     restart_tx: mpsc::Sender<()>,
+    events: EventBus,
+    metrics: Metrics,
 ) -> (SignalHandle, ResetHandle) {
     let (tx, rx) = mpsc::channel(64);
-    tokio::spawn(Coordinator::new(pipeline, config, rx, branch_failures, restart_tx).run());
+    let coordinator = Coordinator::new(
+        pipeline,
+        config,
+        rx,
+        branch_failures,
+        restart_tx,
+        events,
+        metrics,
+    );
+    tokio::spawn(coordinator.run());
     let handle = SignalHandle { tx };
     let reset = ResetHandle {
         inner: handle.clone(),
@@ -74,18 +204,27 @@ impl SignalHandle {
         make: impl FnOnce(oneshot::Sender<Result<T, SignalError>>) -> Command,
     ) -> Result<T, SignalError> {
         let (reply_tx, reply_rx) = oneshot::channel();
+        let envelope = Envelope {
+            cmd: make(reply_tx),
+            span: tracing::Span::current(),
+        };
         self.tx
-            .send(make(reply_tx))
+            .send(envelope)
             .await
             .map_err(|_| SignalError::Unavailable)?;
         reply_rx.await.map_err(|_| SignalError::Unavailable)?
     }
 
-    /// Register a new connection (WHEP POST). Sends `CreateConnection` and
-    /// awaits the coordinator's reply, which resolves to the SDP offer once
-    /// the whipsink delivers it, or an error on timeout/failure.
-    pub async fn create_connection(&self, id: String) -> Result<SdpOffer, SignalError> {
-        self.request(|reply| Command::CreateConnection { id, reply })
+    /// Register a new connection (WHEP POST) opened by `client`. Sends
+    /// `CreateConnection` and awaits the coordinator's reply, which resolves
+    /// to the SDP offer once the whipsink delivers it, or an error on
+    /// timeout/failure.
+    pub async fn create_connection(
+        &self,
+        id: String,
+        client: ClientInfo,
+    ) -> Result<SdpOffer, SignalError> {
+        self.request(|reply| Command::CreateConnection { id, client, reply })
             .await
     }
 
@@ -117,19 +256,67 @@ impl SignalHandle {
             .await
     }
 
-    /// List the current connections and their states (GET /list). Sends
-    /// `ListConnections` and awaits the reply carrying the snapshot; errors
-    /// only if the coordinator is unavailable.
-    pub async fn list_connections(&self) -> Result<Vec<ConnectionInfo>, SignalError> {
-        self.request(|reply| Command::ListConnections { reply })
+    /// List the current connections and their states (GET /list), or only
+    /// those in `state` if given. Sends `ListConnections` and awaits the
+    /// reply carrying the snapshot; errors only if the coordinator is
+    /// unavailable.
+    pub async fn list_connections(
+        &self,
+        state: Option<String>,
+    ) -> Result<Vec<ConnectionInfo>, SignalError> {
+        self.request(|reply| Command::ListConnections { state, reply })
+            .await
+    }
+
+    /// Read one viewer's WebRTC statistics (GET /channel/{id}/stats), as
+    /// the coordinator last sampled them. Sends `ConnectionStats`;
+    /// `NotFound` for an unknown id.
+    pub async fn connection_stats(&self, id: String) -> Result<ViewerStats, SignalError> {
+        self.request(|reply| Command::ConnectionStats { id, reply })
+            .await
+    }
+
+    /// Read every viewer's statistics and their totals (GET /stats). Sends
+    /// `AggregateStats`; errors only if the coordinator is unavailable.
+    pub async fn aggregate_stats(&self) -> Result<AggregateStats, SignalError> {
+        self.request(|reply| Command::AggregateStats { reply })
+            .await
+    }
+
+    /// Read the ingest protocol's statistics (GET /input/stats). Sends
+    /// `InputStats`; not ready while the pipeline is stopped.
+    pub async fn input_stats(&self) -> Result<InputStats, SignalError> {
+        self.request(|reply| Command::InputStats { reply }).await
+    }
+
+    /// Round-trip to the coordinator (GET /healthz). Sends `Ping`; it
+    /// resolves as soon as the actor gets to it.
+    pub async fn ping(&self) -> Result<(), SignalError> {
+        self.request(|reply| Command::Ping { reply }).await
+    }
+
+    /// Whether the pipeline can accept a branch right now (GET /readyz).
+    /// Sends `PipelineReady`; the coordinator asks `BranchControl::ready`.
+    /// A not-ready pipeline answers `SignalError::NotReady` with its reason.
+    pub async fn pipeline_ready(&self) -> Result<(), SignalError> {
+        self.request(|reply| Command::PipelineReady { reply }).await
+    }
+
+    /// Describe one connection (GET /channel/{id}). Sends `GetConnection`;
+    /// the reply carries the same document as its GET /list entry, or
+    /// `NotFound` for an unknown id.
+    pub async fn connection_info(&self, id: String) -> Result<ConnectionInfo, SignalError> {
+        self.request(|reply| Command::GetConnection { id, reply })
             .await
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{spawn_coordinator, CoordinatorConfig};
+    use super::{spawn_coordinator, ClientInfo, CoordinatorConfig};
     use crate::domain::{SdpAnswer, SdpOffer, VALID_WHEP_ANSWER, VALID_WHIP_OFFER};
+    use crate::events::EventBus;
+    use crate::metrics::Metrics;
     use crate::stream::TestPipeline;
     use tokio::sync::mpsc;
 
@@ -146,12 +333,18 @@ mod tests {
             CoordinatorConfig::default(),
             fail_rx,
             restart_tx,
+            EventBus::default(),
+            Metrics::default(),
         );
 
         // The three legs run concurrently, exactly like the HTTP handlers do.
         let whep = {
             let handle = handle.clone();
-            tokio::spawn(async move { handle.create_connection("a".to_string()).await })
+            tokio::spawn(async move {
+                handle
+                    .create_connection("a".to_string(), ClientInfo::default())
+                    .await
+            })
         };
         tokio::task::yield_now().await; // connection registered
 
@@ -28,6 +28,13 @@ impl Watchdog {
         }
     }
 
+    /// Apply a new threshold and window. The failures counted so far stay:
+    /// the next one is judged against the new numbers.
+    pub fn reconfigure(&mut self, threshold: u32, window: Duration) {
+        self.threshold = threshold;
+        self.window = window;
+    }
+
     pub fn record_failure(&mut self) -> bool {
         let now = Instant::now();
         // Decay: a failure that arrives more than `window` after the previous
@@ -1,31 +1,150 @@
+use crate::events::EventBus;
+use crate::metrics::Metrics;
+use crate::push::{self, PushStatus};
+pub use crate::push::{PushArgs, PushConfig};
 use crate::routes::*;
-use crate::signal::{spawn_coordinator, CoordinatorConfig, SignalHandle};
-use crate::stream::{BranchControl, BranchId, PipelineLifecycle, WHIP_SINK_ROUTE};
-use crate::supervisor::Supervisor;
+pub use crate::routes::{AdminArgs, AdminConfig, PublishArgs, PublishConfig};
+use crate::signal::{spawn_coordinator, AdminHandle, CoordinatorConfig, SignalHandle};
+use crate::stream::{BranchControl, BranchId, PipelineLifecycle, PushControl, WHIP_SINK_ROUTE};
+use crate::supervisor::{GaveUp, Supervisor, SupervisorReports, SupervisorStatus};
+pub use crate::supervisor::{SupervisorArgs, SupervisorConfig};
+use crate::telemetry::join_connection_trace;
 use actix_cors::Cors;
-use actix_web::dev::Server;
+use actix_web::dev::{Server, Service, ServiceRequest};
 use actix_web::{guard, web, App, HttpServer};
 use std::future::Future;
 use std::net::TcpListener;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::sync::watch;
-use tokio::task::JoinHandle;
+use tokio::task::{JoinError, JoinHandle};
 use tracing_actix_web::TracingLogger;
 
-pub fn run(listener: TcpListener, signal: SignalHandle) -> Result<Server, std::io::Error> {
+/// The rest of the application, as the HTTP server's routes reach it.
+pub struct ServerDeps {
+    pub signal: SignalHandle,
+    /// Handed to the `/admin` scope only.
+    pub admin: AdminHandle,
+    pub events: EventBus,
+    pub metrics: Metrics,
+    /// Ends the open `/events` streams.
+    pub shutdown: watch::Receiver<bool>,
+    pub supervisor: watch::Receiver<SupervisorStatus>,
+    pub pushes: watch::Receiver<Vec<PushStatus>>,
+}
+
+pub fn run(
+    listener: TcpListener,
+    deps: ServerDeps,
+    admin_config: AdminConfig,
+    publish_config: PublishConfig,
+) -> Result<Server, std::io::Error> {
+    let ServerDeps {
+        signal,
+        admin,
+        events,
+        metrics,
+        shutdown,
+        supervisor,
+        pushes,
+    } = deps;
     let server = HttpServer::new(move || {
         let cors = Cors::permissive();
+        let http_metrics = metrics.clone();
+        let admin = admin.clone();
+        let admin_token = admin_config.token.clone();
         App::new()
+            // Innermost, so the request's root span is current: a request
+            // naming a connection joins that connection's trace, stitching
+            // its WHEP, loopback WHIP and DELETE legs together.
+            .wrap_fn(|req, srv| {
+                if let Some(id) = connection_id(&req) {
+                    join_connection_trace(&tracing::Span::current(), &id);
+                }
+                srv.call(req)
+            })
             .wrap(TracingLogger::default())
             .wrap(cors)
+            // Outermost: count every response, CORS preflights included, by
+            // the matched route pattern rather than the raw path.
+            .wrap_fn(move |req, srv| {
+                let metrics = http_metrics.clone();
+                let method = req.method().to_string();
+                let route = req
+                    .match_pattern()
+                    .unwrap_or_else(|| "unmatched".to_string());
+                let response = srv.call(req);
+                async move {
+                    let response = response.await?;
+                    metrics.http_response(&method, &route, response.status().as_u16());
+                    Ok::<_, actix_web::Error>(response)
+                }
+            })
+            .route("/healthz", web::get().to(healthz))
+            .route("/readyz", web::get().to(readyz))
+            .route("/status", web::get().to(supervisor_status))
             .route("/list", web::get().to(list))
+            .route("/stats", web::get().to(stats))
+            .route("/input/stats", web::get().to(input_stats))
+            .route("/metrics", web::get().to(metrics_export))
+            .route("/events", web::get().to(events_stream))
             .route("/channel", web::post().to(whep_handler))
             .route("/channel", web::route().guard(guard::Options()).to(options))
+            .route("/channel/{id}", web::get().to(channel_info))
             .route("/channel/{id}", web::patch().to(whep_patch_handler))
             .route("/channel/{id}", web::delete().to(terminate_session))
+            .route("/channel/{id}/stats", web::get().to(channel_stats))
             .route(WHIP_SINK_ROUTE, web::post().to(whip_handler))
             .route(WHIP_SINK_ROUTE, web::delete().to(remove_whip_sink))
+            .route("/whip", web::post().to(whip_publish))
+            .route("/whip/{id}", web::patch().to(whip_publish_patch))
+            .route("/whip/{id}", web::delete().to(whip_unpublish))
+            // Mounted only with a token configured; every request in the
+            // scope must present it.
+            .configure(move |cfg| {
+                if let Some(token) = admin_token {
+                    cfg.service(
+                        web::scope("/admin")
+                            .wrap_fn(move |req, srv| {
+                                let call = if authorized(&req, &token) {
+                                    Ok(srv.call(req))
+                                } else {
+                                    Err(req)
+                                };
+                                async move {
+                                    match call {
+                                        Ok(call) => Ok(call.await?.map_into_left_body()),
+                                        Err(req) => Ok(req
+                                            .into_response(unauthorized())
+                                            .map_into_right_body()),
+                                    }
+                                }
+                            })
+                            .app_data(web::Data::new(admin))
+                            .route("/channel", web::delete().to(admin_kick_all))
+                            .route("/channel/{id}", web::delete().to(admin_kick))
+                            .route("/restart", web::post().to(admin_restart))
+                            .route("/drain", web::post().to(admin_drain))
+                            .route("/drain", web::delete().to(admin_undrain))
+                            .route("/config", web::get().to(admin_config))
+                            .route("/config", web::put().to(admin_reconfigure))
+                            .route("/outputs", web::get().to(admin_outputs))
+                            .route("/outputs", web::post().to(admin_add_output))
+                            .route("/outputs/{output}", web::delete().to(admin_remove_output))
+                            .route(
+                                "/outputs/{output}/callers/{caller}",
+                                web::delete().to(admin_disconnect_caller),
+                            ),
+                    );
+                }
+            })
             .app_data(web::Data::new(signal.clone()))
+            .app_data(web::Data::new(events.clone()))
+            .app_data(web::Data::new(metrics.clone()))
+            .app_data(web::Data::new(shutdown.clone()))
+            .app_data(web::Data::new(supervisor.clone()))
+            .app_data(web::Data::new(pushes.clone()))
+            .app_data(web::Data::new(publish_config.clone()))
     })
     // Shutdown is owned by Application::run_until_stopped (one Ctrl-C);
     // actix must not install its own SIGINT handler.
@@ -36,20 +155,79 @@ pub fn run(listener: TcpListener, signal: SignalHandle) -> Result<Server, std::i
     Ok(server)
 }
 
+/// The `{id}` segment of a request whose route names a connection.
+fn connection_id(req: &ServiceRequest) -> Option<String> {
+    let pattern = req.match_pattern()?;
+    let position = pattern.split('/').position(|segment| segment == "{id}")?;
+    req.path().split('/').nth(position).map(str::to_string)
+}
+
+const DEFAULT_DRAIN_GRACE_SEC: u64 = 0;
+
+/// How often the drain phase checks whether the last viewer has left.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// CLI surface for the graceful shutdown.
+#[derive(clap::Args, Debug, Clone)]
+pub struct ShutdownArgs {
+    /// On a stop signal, how long to wait for viewers to leave before
+    /// closing the remaining ones and stopping the pipeline. 0 closes them
+    /// at once.
+    #[clap(long, default_value_t = DEFAULT_DRAIN_GRACE_SEC)]
+    pub drain_grace_sec: u64,
+}
+
+impl ShutdownArgs {
+    pub fn to_config(&self) -> ShutdownConfig {
+        ShutdownConfig {
+            drain_grace: Duration::from_secs(self.drain_grace_sec),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShutdownConfig {
+    /// The drain phase's upper bound; see `Application::run_until_stopped`.
+    pub drain_grace: Duration,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_grace: Duration::from_secs(DEFAULT_DRAIN_GRACE_SEC),
+        }
+    }
+}
+
 /// Bus-reap channel buffer: how many branch failures can queue while the
 /// coordinator is busy. The pipeline's bus handler `try_send`s and drops the
 /// report when full (the sweep remains a backstop), so overflow degrades to
 /// a slower reap, never a stall.
 const BRANCH_FAILURE_CAPACITY: usize = 64;
 
+/// The configuration of each part of the application, for
+/// [`Application::assemble`].
+#[derive(Debug, Clone, Default)]
+pub struct AppConfig {
+    pub coordinator: CoordinatorConfig,
+    pub supervisor: SupervisorConfig,
+    pub admin: AdminConfig,
+    pub shutdown: ShutdownConfig,
+    pub publish: PublishConfig,
+    pub push: PushConfig,
+}
+
 /// The assembled application: coordinator + supervisor + HTTP server,
 /// wired in exactly one place — used by `main`, the signaling integration
 /// tests, and the GStreamer e2e test.
 pub struct Application {
     server: Server,
-    supervisor: JoinHandle<()>,
+    supervisor: JoinHandle<Result<(), GaveUp>>,
     signal: SignalHandle,
+    admin: AdminHandle,
+    events: EventBus,
     shutdown: watch::Sender<bool>,
+    shutdown_config: ShutdownConfig,
     port: u16,
 }
 
@@ -57,17 +235,26 @@ impl Application {
     /// `make_pipeline` receives the bus-reap sender and must build the
     /// pipeline from it. The sender exists nowhere else, so the only pipeline
     /// that can be assembled is one that reports branch failures from birth.
+    /// It also receives the event bus, for the pipeline's ingest events.
     /// The constructed pipeline is returned alongside the application — tests
     /// drive their fake through it.
     pub fn assemble<P>(
         listener: TcpListener,
-        make_pipeline: impl FnOnce(mpsc::Sender<BranchId>) -> P,
-        config: CoordinatorConfig,
+        make_pipeline: impl FnOnce(mpsc::Sender<BranchId>, EventBus) -> P,
+        config: AppConfig,
         expected_whip_port: Option<u16>,
     ) -> Result<(Self, P), std::io::Error>
     where
-        P: BranchControl + PipelineLifecycle + 'static,
+        P: BranchControl + PipelineLifecycle + PushControl + 'static,
     {
+        let AppConfig {
+            coordinator: config,
+            supervisor: supervisor_config,
+            admin: admin_config,
+            shutdown: shutdown_config,
+            publish: publish_config,
+            push: push_config,
+        } = config;
         let port = listener.local_addr()?.port();
         // The pipeline's whipclientsink posts loopback WHIP offers to a fixed
         // port; if the HTTP server is bound elsewhere those offers 404 silently.
@@ -89,23 +276,76 @@ impl Application {
         // The bus-reap channel: the pipeline holds the sender (from birth —
         // the factory shape guarantees it), the coordinator the receiver.
         let (branch_failures_tx, branch_failures_rx) = mpsc::channel(BRANCH_FAILURE_CAPACITY);
-        let pipeline = make_pipeline(branch_failures_tx);
+        // One event bus for the whole application: the coordinator, the
+        // supervisor and the pipeline publish; `GET /events` subscribes.
+        let events = EventBus::default();
+        // Likewise one metrics registry, recorded into by the coordinator,
+        // the supervisor and the HTTP server, rendered by `GET /metrics`.
+        let metrics = Metrics::default();
+        let pipeline = make_pipeline(branch_failures_tx, events.clone());
         // The watchdog restart channel: the coordinator holds the sender (it
         // requests a restart on a trip), the supervisor the receiver (it owns
         // the force-quit + rerun). Created here so both ends exist before
         // either task is spawned — like the bus-reap channel above.
         let (restart_tx, restart_rx) = mpsc::channel(1);
-        let (signal, reset) =
-            spawn_coordinator(pipeline.clone(), config, branch_failures_rx, restart_tx);
+        let (signal, reset) = spawn_coordinator(
+            pipeline.clone(),
+            config,
+            branch_failures_rx,
+            restart_tx.clone(),
+            events.clone(),
+            metrics.clone(),
+        );
         let (shutdown_tx, shutdown_rx) = watch::channel(false);
-        let supervisor = Supervisor::spawn(pipeline.clone(), reset, shutdown_rx, restart_rx);
-        let server = run(listener, signal.clone())?;
+        // The supervisor reports its state here for the health probes and
+        // `GET /status`.
+        let (status_tx, status_rx) = watch::channel(SupervisorStatus::default());
+        // Subscribed before the supervisor's first start is published.
+        publish_config
+            .publisher
+            .release_on_restart(events.subscribe());
+        let supervisor = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx.clone(),
+            restart_rx,
+            SupervisorReports {
+                events: events.clone(),
+                metrics: metrics.clone(),
+                status: status_tx,
+            },
+            supervisor_config,
+        );
+        // Push outputs attach their branches themselves, once the input is
+        // linked, and again after every restart.
+        let pushes = push::spawn(pipeline.clone(), push_config, shutdown_rx.clone());
+        // Admin restarts go to the supervisor over the watchdog's channel.
+        let admin = AdminHandle::new(signal.clone(), restart_tx);
+        // The HTTP server gets the shutdown token too: open `/events`
+        // streams end on it instead of holding the graceful stop open.
+        let server = run(
+            listener,
+            ServerDeps {
+                signal: signal.clone(),
+                admin: admin.clone(),
+                events: events.clone(),
+                metrics,
+                shutdown: shutdown_rx,
+                supervisor: status_rx,
+                pushes,
+            },
+            admin_config,
+            publish_config,
+        )?;
         Ok((
             Self {
                 server,
                 supervisor,
                 signal,
+                admin,
+                events,
                 shutdown: shutdown_tx,
+                shutdown_config,
                 port,
             },
             pipeline,
@@ -120,42 +360,109 @@ impl Application {
         self.signal.clone()
     }
 
+    /// The admin capabilities, for operator controls outside the HTTP
+    /// server (the SIGHUP config reload).
+    pub fn admin(&self) -> AdminHandle {
+        self.admin.clone()
+    }
+
+    /// The application's event bus, for subscribers outside the HTTP
+    /// server (webhook delivery).
+    pub fn events(&self) -> EventBus {
+        self.events.clone()
+    }
+
     /// Serve until `stop` resolves (or the server dies on its own), then
-    /// shut down in order: one token fans out to the supervisor (EOS →
+    /// shut down in order. On `stop` the server first drains: it refuses new
+    /// viewers and reports not ready, waits up to the drain grace period for
+    /// the current ones to leave, and closes whoever remains while the
+    /// pipeline still runs. Then one token fans out to the supervisor (EOS →
     /// bounded join) and a graceful HTTP stop. The coordinator ends when
-    /// its last handle drops with the Application.
+    /// its last handle drops with the Application. If the supervisor gives
+    /// up on the pipeline instead, the server is stopped and the give-up is
+    /// returned as the error, so the process exits non-zero.
     pub async fn run_until_stopped(
         self,
         stop: impl Future<Output = ()>,
     ) -> Result<(), std::io::Error> {
         let server_handle = self.server.handle();
         let mut server_task = tokio::spawn(self.server);
+        let mut supervisor = self.supervisor;
 
-        let server_early_exit = tokio::select! {
-            _ = stop => None,
-            res = &mut server_task => Some(res),
+        let exit = tokio::select! {
+            _ = stop => Exit::Stopped,
+            res = &mut server_task => Exit::Server(res),
+            res = &mut supervisor => Exit::Supervisor(res),
         };
 
+        if let Exit::Stopped = exit {
+            drain(&self.signal, &self.admin, self.shutdown_config.drain_grace).await;
+        }
         let _ = self.shutdown.send(true);
 
-        match server_early_exit {
-            None => {
+        match exit {
+            Exit::Stopped => {
                 // Stop the pipeline and drain HTTP concurrently.
                 let (_, _) = tokio::join!(server_handle.stop(true), async {
-                    let _ = self.supervisor.await;
+                    let _ = supervisor.await;
                 });
                 let _ = server_task.await;
                 Ok(())
             }
-            Some(res) => {
+            Exit::Server(res) => {
                 // The server stopped on its own; still stop the pipeline,
                 // then surface the server's result.
-                let _ = self.supervisor.await;
+                let _ = supervisor.await;
                 match res {
                     Ok(server_result) => server_result,
                     Err(join_error) => Err(std::io::Error::other(join_error)),
                 }
             }
+            Exit::Supervisor(res) => {
+                // Nothing left to serve: drain HTTP, then surface why.
+                server_handle.stop(true).await;
+                let _ = server_task.await;
+                match res {
+                    Ok(Ok(())) => Ok(()),
+                    Ok(Err(gave_up)) => Err(std::io::Error::other(gave_up)),
+                    Err(join_error) => Err(std::io::Error::other(join_error)),
+                }
+            }
         }
     }
 }
+
+/// The drain phase of a graceful stop. Best effort throughout: if the
+/// coordinator is gone there is nothing left to drain.
+async fn drain(signal: &SignalHandle, admin: &AdminHandle, grace: Duration) {
+    if admin.set_draining(true).await.is_err() {
+        return;
+    }
+    let deadline = tokio::time::Instant::now() + grace;
+    loop {
+        let remaining = match signal.list_connections(None).await {
+            Ok(connections) => connections.len(),
+            Err(_) => return,
+        };
+        if remaining == 0 {
+            tracing::info!("Drained: no viewers left");
+            return;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            tracing::info!("Drain grace period over with {} viewers left", remaining);
+            break;
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+    match admin.close_all().await {
+        Ok(closed) => tracing::info!("Closed {} connections for shutdown", closed),
+        Err(e) => tracing::warn!("Could not close connections for shutdown: {}", e),
+    }
+}
+
+/// What ended `Application::run_until_stopped`'s serving phase.
+enum Exit {
+    Stopped,
+    Server(Result<Result<(), std::io::Error>, JoinError>),
+    Supervisor(Result<Result<(), GaveUp>, JoinError>),
+}
@@ -11,7 +11,10 @@
 //! This module is the deletion boundary for the `whepserversink` migration
 //! (ADR 0001, Future Work): moving egress to the native server-initiated
 //! `whepserversink` removes this bridge wholesale. Keep loopback-specific
-//! surface confined here so that migration stays a clean deletion.
+//! surface confined here so that migration stays a clean deletion. Push
+//! outputs ([`Branch::attach_to`] a remote WHIP endpoint) are the one use of
+//! the branch that would outlive it, and the tee-pad teardown helpers are
+//! shared with the passthrough outputs.
 //!
 //! `startup.rs` imports [`WHIP_SINK_ROUTE`] and the WHIP handler imports
 //! [`whip_sink_path`], so the HTTP contract and the whipclientsink's endpoint
@@ -20,7 +23,9 @@ use anyhow::{Context, Error, Result};
 use gst::prelude::*;
 use gstreamer as gst;
 
+use crate::stream::ingest;
 use crate::stream::naming;
+use crate::stream::stats::{self, ViewerSample};
 
 /// The actix route template for the loopback WHIP endpoint — the single
 /// definition shared by the HTTP route table, the WHIP Location header,
@@ -87,8 +92,20 @@ impl Branch {
         port: u16,
         decode_video: bool,
     ) -> Result<(), Error> {
-        let demux = pipeline
-            .by_name(naming::DEMUX)
+        self.attach_to(pipeline, &whip_endpoint(port, &self.id), None, decode_video)
+    }
+
+    /// [`Self::attach`], with the whipclientsink POSTing its offer to
+    /// `endpoint` instead of the loopback route, presenting `auth_token` as
+    /// its bearer token: a push output's remote WHIP endpoint.
+    pub(crate) fn attach_to(
+        &self,
+        pipeline: &gst::Pipeline,
+        endpoint: &str,
+        auth_token: Option<&str>,
+        decode_video: bool,
+    ) -> Result<(), Error> {
+        let demux = ingest::media_source(pipeline)
             .with_context(|| format!("Failed to find element: {}", naming::DEMUX))?;
         // WhipWebRTCSink is renamed as 'whipclientsink' since gst-plugin-webrtc version 0.13.0
         let whipsink = gst::ElementFactory::make("whipclientsink")
@@ -105,7 +122,10 @@ impl Branch {
         // the GStreamer installation provides.
         if whipsink.find_property("signaller").is_some() {
             let signaller = whipsink.property::<gst::glib::Object>("signaller");
-            signaller.set_property_from_str("whip-endpoint", &whip_endpoint(port, &self.id));
+            signaller.set_property_from_str("whip-endpoint", endpoint);
+            if let Some(token) = auth_token {
+                signaller.set_property_from_str("auth-token", token);
+            }
         }
 
         if demux
@@ -207,6 +227,24 @@ impl Branch {
         Ok(())
     }
 
+    /// What a sweep tick samples from this viewer's sink, or `None` if the
+    /// sink is gone or the plugin version exposes no stats. See
+    /// [`stats::viewer_sample`].
+    ///
+    /// Synchronous GStreamer calls only; the caller may hold the pipeline
+    /// state lock.
+    pub(crate) fn sample(&self, pipeline: &gst::Pipeline) -> Option<ViewerSample> {
+        Some(stats::viewer_sample(&self.stats(pipeline)?))
+    }
+
+    /// whipclientsink's `stats` property, if the sink exists and the plugin
+    /// version exposes it.
+    fn stats(&self, pipeline: &gst::Pipeline) -> Option<gst::Structure> {
+        let whipsink = pipeline.by_name(&self.whip_sink_name())?;
+        whipsink.find_property("stats")?;
+        Some(whipsink.property::<gst::Structure>("stats"))
+    }
+
     /// Remove element from a pipeline
     /// # Arguments
     /// * `pipeline` - Pipeline
@@ -214,7 +252,7 @@ impl Branch {
     ///
     /// To remove an element from a pipeline, one has to set the state of the element to NULL
     /// and remove it from the pipeline.
-    async fn remove_element_from_pipeline(
+    pub(crate) async fn remove_element_from_pipeline(
         pipeline: &gst::Pipeline,
         element: &gst::Element,
     ) -> Result<(), Error> {
@@ -244,7 +282,8 @@ impl Branch {
         Ok(())
     }
 
-    /// Remove one per-media branch queue from a pipeline
+    /// Remove one branch queue -- a viewer's per-media queue, or a
+    /// passthrough output's -- from a pipeline
     /// # Arguments
     /// * `pipeline` - Pipeline
     /// * `queue_name` - Name of the queue element to be removed
@@ -252,7 +291,7 @@ impl Branch {
     /// To remove a branch from a pipeline, one has to remove the src pad from the tee element
     /// and remove the queue element from the pipeline. A queue that exists but
     /// was never linked (a partial attach) has no tee pad and is removed directly.
-    async fn remove_branch_from_pipeline(
+    pub(crate) async fn remove_branch_from_pipeline(
         pipeline: &gst::Pipeline,
         queue_name: &str,
     ) -> Result<(), Error> {
@@ -9,7 +9,10 @@
 //! reset all in-flight handshakes -- the "wedge" a single bad peer must never
 //! be able to cause (ADR 0002). Instead the error source's ancestry is walked
 //! to find which viewer's branch it belongs to, so the coordinator can reap
-//! that one connection while the pipeline stays up. Errors from core
+//! that one connection while the pipeline stays up. A push output's branch
+//! is contained the same way, but handed back to its push output to
+//! reconnect rather than reaped, and so is a passthrough output's: its
+//! receiver going away costs only that output. Errors from core
 //! (viewer-independent) elements stay fatal, as does end-of-stream.
 //!
 //! The decision lives here; the mechanism (`main_loop.quit()`, the reap
@@ -29,6 +32,13 @@ pub(crate) enum BusAction {
     /// One viewer's branch failed at runtime: ask the coordinator to reap
     /// exactly that branch. The pipeline stays up.
     ReapBranch(BranchId),
+    /// Push output `index`'s branch failed (its remote WHIP endpoint refused
+    /// or dropped it): mark it failed so the push output reconnects. The
+    /// pipeline stays up.
+    FailPush(usize),
+    /// Passthrough output `id` failed (its receiver refused or dropped the
+    /// connection): take it out of the pipeline. The pipeline stays up.
+    FailOutput(usize),
     /// Not lifecycle-relevant.
     Ignore,
 }
@@ -38,9 +48,10 @@ pub(crate) enum BusAction {
 /// Pure -- no side effects, no locks -- so it is safe on the GLib loop thread
 /// and testable with a hand-built element hierarchy. For an error message the
 /// source's ancestry is walked upward until a branch-derived name
-/// ([`naming::branch_id_from_name`]) identifies the owning viewer; an error
-/// that reaches the top without a match (a core element, or no source at all)
-/// is fatal.
+/// ([`naming::branch_id_from_name`]) identifies the owning viewer, or an
+/// output-derived one ([`naming::output_id_from_name`]) the owning output; an
+/// error that reaches the top without a match (a core element, or no source
+/// at all) is fatal.
 pub(crate) fn classify_bus_message(msg: &gst::Message) -> BusAction {
     use gst::MessageView;
 
@@ -50,7 +61,13 @@ pub(crate) fn classify_bus_message(msg: &gst::Message) -> BusAction {
             let mut cursor = err.src().cloned();
             while let Some(obj) = cursor {
                 if let Some(id) = naming::branch_id_from_name(obj.name().as_str()) {
-                    return BusAction::ReapBranch(BranchId::new(id));
+                    return match naming::push_index_from_branch_id(id) {
+                        Some(index) => BusAction::FailPush(index),
+                        None => BusAction::ReapBranch(BranchId::new(id)),
+                    };
+                }
+                if let Some(id) = naming::output_id_from_name(obj.name().as_str()) {
+                    return BusAction::FailOutput(id);
                 }
                 cursor = obj.parent();
             }
@@ -99,6 +116,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn push_branch_error_fails_only_that_push() {
+        let sink = named_bin(&naming::whip_sink_name(&naming::push_branch_id(2)));
+        let inner = named_bin("whip-signaller");
+        sink.add(&inner).unwrap();
+        assert_eq!(
+            BusAction::FailPush(2),
+            classify_bus_message(&error_from(&inner))
+        );
+    }
+
+    #[test]
+    fn output_error_fails_only_that_output() {
+        let sink = named_bin(&naming::output_name(1));
+        let inner = named_bin("ristsink0");
+        sink.add(&inner).unwrap();
+        assert_eq!(
+            BusAction::FailOutput(1),
+            classify_bus_message(&error_from(&inner))
+        );
+    }
+
     #[test]
     fn core_element_error_is_fatal() {
         let queue = named_bin(naming::VIDEO_QUEUE);
@@ -137,12 +176,17 @@ mod tests {
         // (not literals), so this breaks the instant a rename splits the two.
         // Every core element error must stay fatal...
         for name in [
+            naming::INPUT_TEE,
             naming::DEMUX,
             naming::VIDEO_QUEUE,
             naming::AUDIO_QUEUE,
             naming::OUTPUT_TEE_VIDEO,
             naming::OUTPUT_TEE_AUDIO,
             naming::SRT_SOURCE,
+            naming::TEST_SOURCE,
+            naming::INPUT_SOURCE,
+            naming::RIST_SOURCE,
+            naming::WEBRTC_SOURCE,
         ] {
             let bin = named_bin(name);
             assert_eq!(
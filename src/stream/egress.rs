@@ -3,11 +3,15 @@
 //! When the demux announces its pads, each media type gets an egress chain
 //! hung off its pre-built ingest queue: video is parsed (`h264parse` /
 //! `h265parse`) and fanned out through a named output tee; audio is transcoded
-//! AAC -> Opus and fanned out the same way. The named tees
+//! AAC -> Opus and fanned out the same way, or only parsed if it is Opus
+//! already (a WHEP input). A WebRTC input's decoded media (`video/x-raw`,
+//! `audio/x-raw`) is encoded to H.264 and Opus instead. The named tees
 //! ([`naming::OUTPUT_TEE_VIDEO`] / [`naming::OUTPUT_TEE_AUDIO`]) are the
 //! attach points for WHEP branches; the terminating fakesink keeps each chain
-//! consuming buffers -- and pops EOS onto the message bus when the SRT input
-//! closes -- even with zero viewers attached.
+//! consuming buffers -- and pops EOS onto the message bus when the input
+//! closes -- even with zero viewers attached. Each codec is one row of
+//! [`CODECS`]: its element chain; the queue, tee and fakesink around it are
+//! shared.
 //!
 //! The decision (which parser, which chain, what counts as unsupported) lives
 //! here; the dispatch (walking the demux's src pads on no-more-pads) stays
@@ -19,6 +23,116 @@ use gstreamer as gst;
 
 use crate::stream::naming;
 
+/// Which output tee a codec's chain feeds, and so which ingest queue it
+/// hangs off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Media {
+    Video,
+    Audio,
+}
+
+/// One row of the codec table: the media type (prefix) it handles and the
+/// elements that sit between its ingest queue and its output tee.
+struct Codec {
+    media_type: &'static str,
+    media: Media,
+    chain: fn() -> Result<Vec<gst::Element>, Error>,
+}
+
+const CODECS: &[Codec] = &[
+    Codec {
+        media_type: "video/x-h264",
+        media: Media::Video,
+        chain: || Ok(vec![make("h264parse")?]),
+    },
+    Codec {
+        media_type: "video/x-h265",
+        media: Media::Video,
+        chain: || {
+            tracing::warn!("H.265(HEVC) streams can be linked but are not fully supported yet");
+            Ok(vec![make("h265parse")?])
+        },
+    },
+    Codec {
+        media_type: "video/x-raw",
+        media: Media::Video,
+        chain: encode_h264,
+    },
+    Codec {
+        media_type: "audio/mpeg",
+        media: Media::Audio,
+        // WHEP delivers Opus, so AAC is decoded and re-encoded.
+        chain: || {
+            Ok(vec![
+                make("aacparse")?,
+                make("avdec_aac")?,
+                make("audioconvert")?,
+                make("audioresample")?,
+                make("opusenc")?,
+            ])
+        },
+    },
+    Codec {
+        media_type: "audio/x-opus",
+        media: Media::Audio,
+        chain: || Ok(vec![make("opusparse")?]),
+    },
+    Codec {
+        media_type: "audio/x-raw",
+        media: Media::Audio,
+        chain: || {
+            Ok(vec![
+                make("audioconvert")?,
+                make("audioresample")?,
+                make("opusenc")?,
+            ])
+        },
+    },
+];
+
+/// The codec table's row for `media_type`, if it has one.
+fn codec(media_type: &str) -> Option<&'static Codec> {
+    CODECS
+        .iter()
+        .find(|codec| media_type.starts_with(codec.media_type))
+}
+
+/// A `factory` element with its default properties.
+fn make(factory: &str) -> Result<gst::Element, Error> {
+    Ok(gst::ElementFactory::make(factory).build()?)
+}
+
+/// Decoded video to constrained baseline H.264, the profile every browser
+/// decodes.
+fn encode_h264() -> Result<Vec<gst::Element>, Error> {
+    let x264enc = gst::ElementFactory::make("x264enc")
+        .property_from_str("tune", "zerolatency")
+        .property_from_str("speed-preset", "ultrafast")
+        .property("key-int-max", 60u32)
+        .build()?;
+    let profile = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("video/x-h264")
+                .field("profile", "constrained-baseline")
+                .build(),
+        )
+        .build()?;
+    Ok(vec![
+        make("videoconvert")?,
+        x264enc,
+        profile,
+        make("h264parse")?,
+    ])
+}
+
+/// Whether the codec table can build an egress chain for `media_type`:
+/// H.264/H.265 video, AAC (`audio/mpeg`) audio for the Opus transcode or
+/// Opus itself, or raw video and audio to encode.
+pub(crate) fn is_supported(media_type: &str) -> bool {
+    codec(media_type).is_some()
+}
+
 /// Build and start the egress chain for one demuxed media type.
 ///
 /// The queues are the ones `init()` already constructed and added to the
@@ -33,74 +147,34 @@ pub(crate) fn build_egress_chain(
     video_queue: &gst::Element,
     audio_queue: &gst::Element,
 ) -> Result<(), Error> {
-    // Codec table: the video arms differ only in which parser element sits
-    // between the queue and the tee.
-    let video_parser = if media_type.starts_with("video/x-h264") {
-        Some("h264parse")
-    } else if media_type.starts_with("video/x-h265") {
-        tracing::warn!("H.265(HEVC) streams can be linked but are not fully supported yet");
-        Some("h265parse")
-    } else {
-        None
+    let codec = codec(media_type).ok_or_else(|| anyhow!("Unknown media type {}", media_type))?;
+    let (queue, tee_name) = match codec.media {
+        Media::Video => (video_queue, naming::OUTPUT_TEE_VIDEO),
+        Media::Audio => (audio_queue, naming::OUTPUT_TEE_AUDIO),
     };
+    let chain = (codec.chain)()?;
+    let output_tee = gst::ElementFactory::make("tee").name(tee_name).build()?;
+    // Consumes buffers with no viewer attached, and pops EOS onto the bus
+    // when the input closes.
+    let fakesink = gst::ElementFactory::make("fakesink")
+        .property("can-activate-pull", true)
+        .build()?;
 
-    if let Some(parser) = video_parser {
-        let parse = gst::ElementFactory::make(parser).build()?;
-        let output_tee_video = gst::ElementFactory::make("tee")
-            .name(naming::OUTPUT_TEE_VIDEO)
-            .build()?;
-        // Add a fakesink to the end of pipeline to consume buffers
-        // it receives and pops EOS to message bus when the SRT input stream is closed
-        let fakesink = gst::ElementFactory::make("fakesink")
-            .property("can-activate-pull", true)
-            .build()?;
-
-        let video_elements = &[video_queue, &parse, &output_tee_video, &fakesink];
-        // 'video_queue' has been added to the pipeline already, so we don't add it again.
-        pipeline.add_many(&video_elements[1..])?;
-        gst::Element::link_many(&video_elements[..])?;
-        // This is quite important and people forget it often. Without making sure that
-        // the new elements have the same state as the pipeline, things will fail later.
-        // They would still be in Null state and can't process data.
-        for e in video_elements {
-            e.sync_state_with_parent()?;
-        }
-
-        Ok(())
-    } else if media_type.starts_with("audio") {
-        let aacparse = gst::ElementFactory::make("aacparse").build()?;
-        let avdec_aac = gst::ElementFactory::make("avdec_aac").build()?;
-        let audioconvert = gst::ElementFactory::make("audioconvert").build()?;
-        let audioresample = gst::ElementFactory::make("audioresample").build()?;
-        let opusenc = gst::ElementFactory::make("opusenc").build()?;
-        let output_tee_audio = gst::ElementFactory::make("tee")
-            .name(naming::OUTPUT_TEE_AUDIO)
-            .build()?;
-        let fakesink = gst::ElementFactory::make("fakesink")
-            .property("can-activate-pull", true)
-            .build()?;
-
-        let audio_elements = &[
-            audio_queue,
-            &aacparse,
-            &avdec_aac,
-            &audioconvert,
-            &audioresample,
-            &opusenc,
-            &output_tee_audio,
-            &fakesink,
-        ];
-        // 'audio_queue' has been added to the pipeline already, so we don't add it again.
-        pipeline.add_many(&audio_elements[1..])?;
-        gst::Element::link_many(&audio_elements[..])?;
-        for e in audio_elements {
-            e.sync_state_with_parent()?;
-        }
-
-        Ok(())
-    } else {
-        Err(anyhow!("Unknown media type {}", media_type))
+    let elements: Vec<&gst::Element> = std::iter::once(queue)
+        .chain(&chain)
+        .chain([&output_tee, &fakesink])
+        .collect();
+    // The queue has been added to the pipeline already, so we don't add it again.
+    pipeline.add_many(&elements[1..])?;
+    gst::Element::link_many(&elements)?;
+    // This is quite important and people forget it often. Without making sure that
+    // the new elements have the same state as the pipeline, things will fail later.
+    // They would still be in Null state and can't process data.
+    for e in &elements {
+        e.sync_state_with_parent()?;
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -168,6 +242,25 @@ mod tests {
         assert_eq!("aacparse", linked_factory(&audio_queue));
     }
 
+    #[test]
+    fn opus_is_only_parsed_into_the_audio_tee() {
+        let (pipeline, video_queue, audio_queue) = pipeline_with_queues();
+        build_egress_chain(&pipeline, "audio/x-opus", &video_queue, &audio_queue).unwrap();
+        assert!(pipeline.by_name(naming::OUTPUT_TEE_AUDIO).is_some());
+        assert_eq!("opusparse", linked_factory(&audio_queue));
+    }
+
+    #[test]
+    fn raw_media_is_encoded_into_the_tees() {
+        let (pipeline, video_queue, audio_queue) = pipeline_with_queues();
+        build_egress_chain(&pipeline, "video/x-raw", &video_queue, &audio_queue).unwrap();
+        build_egress_chain(&pipeline, "audio/x-raw", &video_queue, &audio_queue).unwrap();
+        assert!(pipeline.by_name(naming::OUTPUT_TEE_VIDEO).is_some());
+        assert!(pipeline.by_name(naming::OUTPUT_TEE_AUDIO).is_some());
+        assert_eq!("videoconvert", linked_factory(&video_queue));
+        assert_eq!("audioconvert", linked_factory(&audio_queue));
+    }
+
     #[test]
     fn unknown_media_is_an_error_and_builds_nothing() {
         let (pipeline, video_queue, audio_queue) = pipeline_with_queues();
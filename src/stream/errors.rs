@@ -1,18 +1,48 @@
 use crate::errors::error_chain_fmt;
+use serde::Serialize;
 use std::fmt::Debug;
 use thiserror::Error;
 use timed_locks::Error as TimedLockError;
 
+/// Why the input cannot accept a viewer branch yet, from the earliest
+/// missing stage: pipeline → input → stream info → codec → output.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum NotReadyReason {
+    /// No pipeline: it is between supervisor restarts, or not yet built.
+    #[error("the pipeline is not running")]
+    PipelineStopped,
+    /// No media has reached the demuxer: the input (SRT peer, UDP or RTP
+    /// sender, WebRTC publisher, ...) is not connected or sends nothing.
+    #[error("no input stream is connected")]
+    NoInput,
+    /// Data arrives but the demuxer has exposed no audio or video stream
+    /// yet: the PMT has not arrived.
+    #[error("waiting for the stream's program map (PMT)")]
+    AwaitingStreamInfo,
+    /// A stream's codec has no egress chain; `caps` names it.
+    #[error("unsupported codec {caps}")]
+    UnsupportedCodec { caps: String },
+    /// The streams are known but their output tees were never built:
+    /// linking is still in progress, or it failed (a split `no-more-pads`,
+    /// known limitation 6).
+    #[error("the output is not linked")]
+    OutputNotLinked,
+    /// The startup preflight found these GStreamer elements missing and the
+    /// server runs degraded (`--preflight degraded`).
+    #[error("GStreamer elements are missing: {}", elements.join(", "))]
+    MissingElements { elements: Vec<String> },
+}
+
 /// The `BranchControl` seam's error language. Three variants carry the
 /// whole policy — is a retry worthwhile? — while the detail stays a
 /// string; callers decide retry-vs-fail, never parse messages.
 #[derive(Error)]
 pub enum PipelineError {
-    /// The input stream cannot accept a branch yet (not demuxed, output
-    /// tees not built, or the pipeline is between supervisor restarts).
-    /// Retryable.
-    #[error("Pipeline is not ready")]
-    NotReady,
+    /// The input stream cannot accept a branch yet; the reason says which
+    /// stage is missing. Retryable.
+    #[error("Pipeline is not ready: {0}")]
+    NotReady(NotReadyReason),
     /// A failure expected to clear on its own — e.g. the state lock timed
     /// out behind a slow branch operation. Retryable.
     #[error("Transient pipeline failure: {0}")]
@@ -23,6 +53,17 @@ pub enum PipelineError {
     Fatal(String),
 }
 
+impl PipelineError {
+    /// The variant, as `GET /metrics` labels it.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PipelineError::NotReady(_) => "not_ready",
+            PipelineError::Transient(_) => "transient",
+            PipelineError::Fatal(_) => "fatal",
+        }
+    }
+}
+
 impl Debug for PipelineError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         error_chain_fmt(self, f)
@@ -2,18 +2,25 @@ use anyhow::{anyhow, Context, Error, Result};
 use async_trait::async_trait;
 use gst::{prelude::*, Pipeline};
 use gstreamer as gst;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 use timed_locks::Mutex;
 use tokio::sync::mpsc;
 
+use crate::events::{Event, EventBus};
 use crate::stream::branch::Branch;
 use crate::stream::bus::{classify_bus_message, BusAction};
 use crate::stream::egress;
-use crate::stream::errors::PipelineError;
+use crate::stream::errors::{NotReadyReason, PipelineError};
+use crate::stream::ingest::{self, Ingest};
 use crate::stream::naming::{self, BranchId};
-use crate::stream::pipeline::{Args, BranchControl, PipelineLifecycle, SRTMode};
-use crate::stream::utils::run_discoverer;
+use crate::stream::passthrough::{self, Outputs, Passthrough};
+use crate::stream::pipeline::{
+    Args, BranchControl, OutputInfo, PipelineLifecycle, PushControl, PushHealth, PushTarget,
+};
+use crate::stream::srt_callers::{self, Admission, CallerEvent, CallerInfo, Callers};
+use crate::stream::stats::{InputStats, ViewerSample};
 
 #[derive(Clone)]
 struct PipelineWrapper {
@@ -41,32 +48,129 @@ pub struct SharablePipeline {
     /// this wrapper, which survives pipeline reruns, so a supervisor restart
     /// keeps reaping without re-wiring.
     branch_failures: mpsc::Sender<BranchId>,
+    /// Ingest events (caps detected, stream ready) for `GET /events`.
+    events: EventBus,
+    /// Elements the startup preflight could not find, in degraded mode. No
+    /// branch can be served while any are missing.
+    missing_elements: Arc<[String]>,
+    /// Push outputs whose branch errored on the bus, by index. A plain
+    /// mutex: the bus watch writes it from the GLib thread. Cleared with
+    /// the pipeline.
+    failed_pushes: Arc<std::sync::Mutex<HashSet<usize>>>,
+    /// The passthrough outputs, attached by every pipeline run. A plain
+    /// mutex, like `failed_pushes`: the bus watch records failures in it.
+    outputs: Arc<std::sync::Mutex<Outputs>>,
+    /// The publishers of an SRT input in listener mode, kept by its
+    /// `srtsrc`'s signals. Cleared with the pipeline.
+    publishers: Arc<std::sync::Mutex<Callers>>,
 }
 
 impl SharablePipeline {
-    pub fn new(args: Args, branch_failures: mpsc::Sender<BranchId>) -> Self {
+    pub fn new(args: Args, branch_failures: mpsc::Sender<BranchId>, events: EventBus) -> Self {
         Self {
             state: Arc::new(Mutex::new_with_timeout(
                 PipelineWrapper::new(args),
                 Duration::from_secs(1),
             )),
             branch_failures,
+            events,
+            missing_elements: Arc::from([]),
+            failed_pushes: Arc::default(),
+            outputs: Arc::default(),
+            publishers: Arc::default(),
         }
     }
 
+    /// Start with `outputs` (from `--output-address`) as the passthrough
+    /// outputs, ids 0 and up in order.
+    pub fn with_outputs(mut self, outputs: Vec<Passthrough>) -> Self {
+        self.outputs = Arc::new(std::sync::Mutex::new(Outputs::new(outputs)));
+        self
+    }
+
+    /// Run degraded: report `missing` (from the preflight) as the reason the
+    /// pipeline is not ready, instead of failing each viewer at attach.
+    pub fn with_missing_elements(mut self, missing: Vec<String>) -> Self {
+        self.missing_elements = missing.into();
+        self
+    }
+
+    /// Admit the SRT input's publishers by `args`, and log them and publish
+    /// them to `GET /events` as they come and go.
+    fn watch_publishers(&self, src: &gst::Element, args: &Args) -> Result<(), Error> {
+        {
+            let mut publishers = self.publishers.lock().unwrap();
+            publishers.clear();
+            publishers.set_admission(Admission {
+                allow: args.srt_allow.clone(),
+                streamids: args.srt_streamid.clone(),
+            });
+        }
+        let events = self.events.clone();
+        srt_callers::watch(
+            src,
+            self.publishers.clone(),
+            "SRT input".to_string(),
+            move |event| {
+                events.publish(match event {
+                    CallerEvent::Connected(caller) => Event::PublisherConnected {
+                        id: caller.id,
+                        address: caller.address,
+                    },
+                    CallerEvent::Disconnected(caller) => Event::PublisherDisconnected {
+                        id: caller.id,
+                        address: caller.address,
+                    },
+                    CallerEvent::Refused { address, reason } => {
+                        Event::PublisherRefused { address, reason }
+                    }
+                })
+            },
+        )
+    }
+
+    fn elements_present(&self) -> Result<(), PipelineError> {
+        if self.missing_elements.is_empty() {
+            return Ok(());
+        }
+        Err(PipelineError::NotReady(NotReadyReason::MissingElements {
+            elements: self.missing_elements.to_vec(),
+        }))
+    }
+
     /// Whether the input is demuxed and the matching output tees exist, so a
-    /// branch can be linked. Pure check over an already-locked pipeline; the
-    /// single source of truth for both `ready()` and `add_branch()`.
-    fn input_ready(pipeline: &Pipeline) -> Result<bool, PipelineError> {
-        let demux = pipeline.by_name(naming::DEMUX).ok_or_else(|| {
+    /// branch can be linked — or, as `PipelineError::NotReady`, the first
+    /// stage that is missing. Pure check over an already-locked pipeline;
+    /// the single source of truth for both `ready()` and `add_branch()`.
+    fn input_ready(pipeline: &Pipeline) -> Result<(), PipelineError> {
+        let demux = ingest::media_source(pipeline).ok_or_else(|| {
             PipelineError::Fatal(format!("Failed to find element: {}", naming::DEMUX))
         })?;
 
-        let pads = demux.pads();
-        let has_video = pads.iter().any(|pad| pad.name().starts_with("video"));
-        let has_audio = pads.iter().any(|pad| pad.name().starts_with("audio"));
+        // typefind sets the demux's sink caps once MPEG-TS data flows in. A
+        // WebRTC source has no sink pad: it receives once a track arrives.
+        let receiving = match demux.static_pad("sink") {
+            Some(pad) => pad.current_caps().is_some(),
+            None => !demux.src_pads().is_empty(),
+        };
+        if !receiving {
+            return Err(PipelineError::NotReady(NotReadyReason::NoInput));
+        }
+
+        let media: Vec<(String, Option<String>)> = demux
+            .src_pads()
+            .iter()
+            .map(|pad| {
+                let caps = pad
+                    .current_caps()
+                    .and_then(|caps| caps.structure(0).map(|s| s.name().to_string()));
+                (pad.name().to_string(), caps)
+            })
+            .collect();
+        let has_video = media.iter().any(|(name, _)| name.starts_with("video"));
+        let has_audio = media.iter().any(|(name, _)| name.starts_with("audio"));
         if !has_video && !has_audio {
-            return Ok(false);
+            return Err(PipelineError::NotReady(NotReadyReason::AwaitingStreamInfo));
         }
 
         // The demux exposes its media pads (pad-added) before the output tees
@@ -74,20 +178,35 @@ impl SharablePipeline {
         // tees, so the input is only truly ready once the matching tee exists.
         let video_ready = !has_video || pipeline.by_name(naming::OUTPUT_TEE_VIDEO).is_some();
         let audio_ready = !has_audio || pipeline.by_name(naming::OUTPUT_TEE_AUDIO).is_some();
-        Ok(video_ready && audio_ready)
+        if video_ready && audio_ready {
+            return Ok(());
+        }
+        // A missing tee whose stream the codec table cannot link will never
+        // appear; name the codec rather than waiting on it.
+        let unsupported = media.iter().find_map(|(name, caps)| {
+            let caps = caps.as_deref()?;
+            let unlinked = (name.starts_with("video") && !video_ready)
+                || (name.starts_with("audio") && !audio_ready);
+            (unlinked && !egress::is_supported(caps)).then(|| caps.to_string())
+        });
+        Err(PipelineError::NotReady(match unsupported {
+            Some(caps) => NotReadyReason::UnsupportedCodec { caps },
+            None => NotReadyReason::OutputNotLinked,
+        }))
     }
 }
 
 #[async_trait]
 impl BranchControl for SharablePipeline {
     /// Check if SRT input stream is available
-    async fn ready(&self) -> Result<bool, PipelineError> {
+    async fn ready(&self) -> Result<(), PipelineError> {
+        self.elements_present()?;
         let pipeline_state = self.state.lock_err().await.inspect_err(|e| {
             tracing::error!("Failed to lock pipeline: {}", e);
         })?;
         let Some(pipeline) = pipeline_state.pipeline.as_ref() else {
-            tracing::error!("Pipeline is not initialized");
-            return Ok(false);
+            tracing::debug!("Pipeline is not initialized");
+            return Err(PipelineError::NotReady(NotReadyReason::PipelineStopped));
         };
         Self::input_ready(pipeline)
     }
@@ -100,6 +219,7 @@ impl BranchControl for SharablePipeline {
     /// For whipsink to work, the branch must be linked to the output tee element and synced in state
     /// Return NoSRTStream error if no input stream is available
     async fn add_branch(&self, id: String) -> Result<(), PipelineError> {
+        self.elements_present()?;
         // Attach under the state lock (attach is synchronous and may hold it).
         // Clone the pipeline handle so that, if attach fails, we can detach the
         // half-built branch AFTER releasing the lock -- detach awaits GStreamer
@@ -110,12 +230,12 @@ impl BranchControl for SharablePipeline {
             let pipeline = pipeline_state
                 .pipeline
                 .as_ref()
-                .ok_or(PipelineError::NotReady)?;
+                .ok_or(PipelineError::NotReady(NotReadyReason::PipelineStopped))?;
 
-            if !Self::input_ready(pipeline)? {
-                tracing::error!("Demux has no pad available. No connection can be added.");
-                return Err(PipelineError::NotReady); // pre-attach: nothing to clean up
-            }
+            // Pre-attach: nothing to clean up.
+            Self::input_ready(pipeline).inspect_err(|e| {
+                tracing::error!("No connection can be added: {}", e);
+            })?;
 
             tracing::debug!("Add connection {} to pipeline", id);
             let attach_result = Branch::for_id(&id).attach(
@@ -181,6 +301,196 @@ impl BranchControl for SharablePipeline {
             .await
             .map_err(|e| PipelineError::Fatal(e.to_string()))
     }
+
+    /// Every listed viewer's whipclientsink stats, read in one pass under
+    /// one take of the state lock (see [`Branch::sample`]).
+    async fn sample_viewers(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, ViewerSample>, PipelineError> {
+        let pipeline_state = self.state.lock_err().await?;
+        let pipeline = pipeline_state
+            .pipeline
+            .as_ref()
+            .ok_or(PipelineError::NotReady(NotReadyReason::PipelineStopped))?;
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| {
+                let sample = Branch::for_id(&id).sample(pipeline)?;
+                Some((id, sample))
+            })
+            .collect())
+    }
+
+    /// The ingest element's statistics (see [`Ingest::stats`]).
+    async fn input_stats(&self) -> Result<InputStats, PipelineError> {
+        let pipeline_state = self.state.lock_err().await?;
+        let pipeline = pipeline_state
+            .pipeline
+            .as_ref()
+            .ok_or(PipelineError::NotReady(NotReadyReason::PipelineStopped))?;
+        let ingest = Ingest::parse(&pipeline_state.args.input_address)
+            .map_err(|e| PipelineError::Fatal(e.to_string()))?;
+        let mut stats = ingest.stats(pipeline);
+        if ingest.takes_publishers(&pipeline_state.args) {
+            let publishers = self.publishers.lock().unwrap().list();
+            stats.publishers = Some(publishers.into_iter().map(CallerInfo::redacted).collect());
+        }
+        Ok(stats)
+    }
+
+    async fn outputs(&self) -> Result<Vec<OutputInfo>, PipelineError> {
+        let pipeline_state = self.state.lock_err().await?;
+        Ok(self
+            .outputs
+            .lock()
+            .unwrap()
+            .infos(pipeline_state.pipeline.as_ref()))
+    }
+
+    /// Add a passthrough output, and attach it if the pipeline runs; a
+    /// stopped pipeline attaches it when it runs again. An output that
+    /// fails to attach is detached and forgotten.
+    async fn add_output(&self, output: Passthrough) -> Result<OutputInfo, PipelineError> {
+        // Under the state lock, like `init` attaching the table: an output
+        // added while a run starts is attached by exactly one of the two.
+        let (pipeline, id, attach_result) = {
+            let pipeline_state = self.state.lock_err().await?;
+            let mut outputs = self.outputs.lock().unwrap();
+            let id = outputs.insert(output.clone());
+            let attach_result = pipeline_state.pipeline.as_ref().map(|pipeline| {
+                tracing::info!("Add output {} to pipeline: {}", id, output.redacted_uri());
+                outputs.attach(pipeline, id, &pipeline_state.args)
+            });
+            (pipeline_state.pipeline.clone(), id, attach_result)
+        };
+
+        if let (Some(pipeline), Some(Err(attach_err))) = (&pipeline, attach_result) {
+            self.outputs.lock().unwrap().remove(id);
+            if let Err(cleanup_err) = passthrough::detach(pipeline, id).await {
+                tracing::error!(
+                    "cleanup after failed attach for output {} also failed: {}",
+                    id,
+                    cleanup_err
+                );
+            }
+            return Err(PipelineError::Fatal(attach_err.to_string()));
+        }
+        self.outputs
+            .lock()
+            .unwrap()
+            .info(id, pipeline.as_ref())
+            .ok_or_else(|| PipelineError::Transient(format!("Output {} was removed", id)))
+    }
+
+    async fn remove_output(&self, id: usize) -> Result<bool, PipelineError> {
+        let pipeline = {
+            let pipeline_state = self.state.lock_err().await?;
+            if self.outputs.lock().unwrap().remove(id).is_none() {
+                return Ok(false);
+            }
+            pipeline_state.pipeline.clone()
+        };
+        tracing::info!("Remove output {} from pipeline", id);
+        // No pipeline: the output went with it.
+        if let Some(pipeline) = pipeline {
+            passthrough::detach(&pipeline, id)
+                .await
+                .map_err(|e| PipelineError::Fatal(e.to_string()))?;
+        }
+        Ok(true)
+    }
+
+    /// Close the caller's socket: `srtsink` drops that caller alone on its
+    /// next send, and its host and streamid are refused from then on. The
+    /// output's other callers stay connected.
+    async fn disconnect_caller(&self, output: usize, caller: usize) -> Result<bool, PipelineError> {
+        let socket = {
+            let _pipeline_state = self.state.lock_err().await?;
+            self.outputs
+                .lock()
+                .unwrap()
+                .disconnect_caller(output, caller)
+        };
+        let Some(socket) = socket else {
+            return Ok(false);
+        };
+        tracing::info!("Disconnect caller {} of output {}", caller, output);
+        srt_callers::close(socket).map_err(|e| PipelineError::Fatal(e.to_string()))?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl PushControl for SharablePipeline {
+    /// Attach push output `index`'s branch, as `add_branch` does a viewer's
+    /// but POSTing to `target`. A half-built branch is detached again.
+    async fn add_push(&self, index: usize, target: PushTarget) -> Result<(), PipelineError> {
+        self.elements_present()?;
+        let id = naming::push_branch_id(index);
+        let (pipeline, attach_result) = {
+            let pipeline_state = self.state.lock_err().await?;
+            let pipeline = pipeline_state
+                .pipeline
+                .as_ref()
+                .ok_or(PipelineError::NotReady(NotReadyReason::PipelineStopped))?;
+            Self::input_ready(pipeline)?;
+
+            tracing::debug!("Add push output {} to pipeline: {}", index, target.url);
+            let attach_result = Branch::for_id(&id).attach_to(
+                pipeline,
+                &target.url,
+                target.token.as_deref(),
+                pipeline_state.args.decode_video,
+            );
+            (pipeline.clone(), attach_result)
+        };
+        self.failed_pushes.lock().unwrap().remove(&index);
+
+        if let Err(attach_err) = attach_result {
+            if let Err(cleanup_err) = Branch::for_id(&id).detach(&pipeline).await {
+                tracing::error!(
+                    "cleanup after failed attach for push output {} also failed: {}",
+                    index,
+                    cleanup_err
+                );
+            }
+            return Err(PipelineError::Fatal(attach_err.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn remove_push(&self, index: usize) -> Result<(), PipelineError> {
+        let pipeline = {
+            let pipeline_state = self.state.lock_err().await?;
+            pipeline_state.pipeline.clone()
+        };
+        self.failed_pushes.lock().unwrap().remove(&index);
+        // No pipeline: the branch went with it.
+        let Some(pipeline) = pipeline else {
+            return Ok(());
+        };
+        tracing::debug!("Remove push output {} from pipeline", index);
+        Branch::for_id(&naming::push_branch_id(index))
+            .detach(&pipeline)
+            .await
+            .map_err(|e| PipelineError::Fatal(e.to_string()))
+    }
+
+    async fn push_health(&self, index: usize) -> Result<PushHealth, PipelineError> {
+        let pipeline_state = self.state.lock_err().await?;
+        let attached = pipeline_state.pipeline.as_ref().is_some_and(|pipeline| {
+            let id = naming::push_branch_id(index);
+            pipeline.by_name(&naming::whip_sink_name(&id)).is_some()
+        });
+        Ok(if !attached {
+            PushHealth::Absent
+        } else if self.failed_pushes.lock().unwrap().contains(&index) {
+            PushHealth::Failed
+        } else {
+            PushHealth::Attached
+        })
+    }
 }
 
 #[async_trait]
@@ -201,192 +511,48 @@ impl PipelineLifecycle for SharablePipeline {
 
         let args = self.state.lock_err().await?.args.clone();
 
-        // Create a pipeline
-        let pipeline = gst::Pipeline::default();
-
-        let uri = format!(
-            "srt://{}?mode={}",
-            args.input_address,
-            args.srt_mode.to_str()
-        );
-        let srt_mode = args.srt_mode.clone();
-        tracing::info!("SRT Input uri: {}", uri);
-
-        // Run discoverer if the source stream is in listener mode (we are the caller)
-        if srt_mode == SRTMode::Caller && args.run_discoverer {
-            tracing::info!("Running discoverer...");
-            // Swallow error if discoverer fails (This could happen When SRT client is running in Docker container)
-            let _ = run_discoverer(&uri, args.discoverer_timeout_sec);
+        let ingest = Ingest::parse(&args.input_address)?;
+        let src = ingest.build(&args)?;
+        if ingest.takes_publishers(&args) {
+            self.watch_publishers(&src, &args)?;
         }
+        let pipeline = if ingest.is_webrtc() {
+            self.webrtc_input(src, &ingest)?
+        } else {
+            self.ts_input(src, &args)?
+        };
 
-        let src = gst::ElementFactory::make("srtsrc")
-            .name(naming::SRT_SOURCE)
-            .property("uri", uri)
-            .property("latency", args.srt_latency as i32)
-            .build()?;
-        let input_tee = gst::ElementFactory::make("tee").name("input_tee").build()?;
-
-        let whep_queue = Self::create_custom_queue("whep-queue", "0", "0", "no")?;
-        let typefind = gst::ElementFactory::make("typefind")
-            .name("typefind")
-            .build()?;
-        let tsdemux = gst::ElementFactory::make("tsdemux")
-            .name(naming::DEMUX)
-            .property("latency", args.tsdemux_latency as i32)
-            .build()?;
-
-        let video_queue = Self::create_custom_queue(naming::VIDEO_QUEUE, "0", "0", "no")?;
-        let audio_queue = Self::create_custom_queue(naming::AUDIO_QUEUE, "0", "0", "no")?;
-        let srt_queue = Self::create_custom_queue("srt-queue", "0", "0", "downstream")?;
-
-        let output_uri = format!(
-            "srt://{}?mode={}",
-            args.output_address,
-            args.srt_mode.reverse().to_str()
-        );
-        tracing::info!("SRT Output uri: {}", output_uri);
-        let srtsink = gst::ElementFactory::make("srtsink")
-            .property("uri", output_uri)
-            .property("wait-for-connection", false)
-            .build()?;
-
-        pipeline.add_many([
-            &src,
-            &input_tee,
-            &whep_queue,
-            &srt_queue,
-            &typefind,
-            &tsdemux,
-            &video_queue,
-            &audio_queue,
-            &srtsink,
-        ])?;
-        gst::Element::link_many([&src, &input_tee])?;
-        gst::Element::link_many([&input_tee, &whep_queue, &typefind, &tsdemux])?;
-        gst::Element::link_many([&input_tee, &srt_queue, &srtsink])?;
-
-        let pipeline_weak = pipeline.downgrade();
-        // Connect to tsdemux's no-more-pads signal, that is emitted when the element
-        // will not generate more dynamic pads. This usually happens when the stream
-        // is fully received and decoded.
-        tsdemux.connect_no_more_pads(move |dbin| {
-            tracing::info!("No more pads from the stream. Ready to link.");
-            // Here we temporarily retrieve a strong reference on the pipeline from the weak one
-            // we moved into this callback.
-            let pipeline = match pipeline_weak.upgrade() {
-                Some(pipeline) => pipeline,
-                None => return,
-            };
-
-            let all_linked = dbin.foreach_src_pad(|_, pad| {
-                let media_type = pad
-                    .current_caps()
-                    .and_then(|caps| caps.structure(0).map(|s| s.name()));
-                if media_type.is_none() {
-                    tracing::warn!("Failed to get media type from demux pad");
-                    return false;
-                }
-
-                let media_type = media_type.unwrap().as_str();
-                tracing::debug!("linking to media {:?}", media_type);
-
-                let linked =
-                    egress::build_egress_chain(&pipeline, media_type, &video_queue, &audio_queue)
-                        .map_err(|err| {
-                            tracing::error!("Failed to link media: {}", err);
-                            err
-                        });
-
-                linked.is_ok()
-            });
-
-            if all_linked {
-                tracing::info!("Successfully linked stream. Ready to play.");
-            } else {
-                tracing::error!("Failed to link stream");
-            }
-        });
-
-        let pipeline_weak = pipeline.downgrade();
-        // Connect to decodebin's pad-added signal, that is emitted whenever
-        // it found another stream from the input file and found a way to decode it to its raw format.
-        // decodebin automatically adds a src-pad for this raw stream, which
-        // we can use to build the follow-up pipeline.
-        tsdemux.connect_pad_added(move |_dbin, src_pad| {
-            // Here we temporarily retrieve a strong reference on the pipeline from the weak one
-            // we moved into this callback.
-            let pipeline = match pipeline_weak.upgrade() {
-                Some(pipeline) => pipeline,
-                None => return,
-            };
-
-            // Try to detect whether the raw stream decodebin provided us with
-            // just now is either audio or video (or none of both, e.g. subtitles).
-            let (is_audio, is_video) = {
-                let media_type = src_pad.current_caps().and_then(|caps| {
-                    caps.structure(0).map(|s| {
-                        let name = s.name();
-                        (name.starts_with("audio/"), name.starts_with("video/"))
-                    })
-                });
-
-                match media_type {
-                    None => {
-                        tracing::error!("Unknown pad added {:?}", src_pad);
-                        return;
-                    }
-                    Some(media_type) => media_type,
-                }
-            };
-
-            let insert_sink = |is_audio, is_video| -> Result<(), Error> {
-                if is_audio {
-                    // Get the queue element's sink pad and link the decodebin's newly created
-                    // src pad for the audio stream to it.
-                    let audio_queue = pipeline.by_name(naming::AUDIO_QUEUE).with_context(|| {
-                        format!("Failed to find element: {}", naming::AUDIO_QUEUE)
-                    })?;
-                    let sink_pad = audio_queue.static_pad("sink").with_context(|| {
-                        format!("Failed to find element: {}'s sink pad", naming::AUDIO_QUEUE)
-                    })?;
-                    src_pad.link(&sink_pad)?;
-
-                    tracing::info!("Successfully inserted audio sink");
-                }
-                if is_video {
-                    // Get the queue element's sink pad and link the decodebin's newly created
-                    // src pad for the video stream to it.
-                    let video_queue = pipeline.by_name(naming::VIDEO_QUEUE).with_context(|| {
-                        format!("Failed to find element: {}", naming::VIDEO_QUEUE)
-                    })?;
-                    let sink_pad = video_queue.static_pad("sink").with_context(|| {
-                        format!("Failed to find element: {}'s sink pad", naming::VIDEO_QUEUE)
-                    })?;
-                    src_pad.link(&sink_pad)?;
-
-                    tracing::info!("Successfully inserted video sink");
-                }
-
-                Ok(())
-            };
-
-            if let Err(err) = insert_sink(is_audio, is_video) {
-                tracing::error!("Failed to insert sink: {}", err);
+        let mut pipeline_state = self.state.lock_err().await?;
+        // Under the state lock, so `add_output` cannot slip an output in
+        // between this walk of the table and the pipeline becoming visible.
+        // One that cannot be built is reported failed; it must not keep the
+        // input down.
+        {
+            let mut outputs = self.outputs.lock().unwrap();
+            let ids: Vec<usize> = outputs.iter().map(|(id, _)| id).collect();
+            let failed: Vec<(usize, String)> = ids
+                .into_iter()
+                .filter_map(|id| {
+                    let attached = outputs.attach(&pipeline, id, &args);
+                    attached.err().map(|e| (id, e.to_string()))
+                })
+                .collect();
+            for (id, error) in failed {
+                tracing::error!("Failed to attach output {}: {}", id, error);
+                outputs.fail(id, error);
             }
-        });
+        }
 
         // Set to playing
         pipeline.set_state(gst::State::Playing)?;
-        {
-            self.state.lock_err().await?.pipeline = Some(pipeline);
-        }
+        pipeline_state.pipeline = Some(pipeline);
 
         Ok(())
     }
 
     /// Run pipeline and wait until the message bus receives an EOS or error message
     async fn run(&self) -> Result<(), Error> {
-        let (bus, main_loop) = {
+        let (bus, main_loop, pipeline_weak) = {
             let mut pipeline_state = self.state.lock_err().await?;
             let pipeline = pipeline_state
                 .pipeline
@@ -395,12 +561,14 @@ impl PipelineLifecycle for SharablePipeline {
             let bus = pipeline.bus().unwrap();
             let main_loop = glib::MainLoop::new(None, false);
             pipeline_state.main_loop = Some(main_loop.clone());
-            (bus, main_loop)
+            (bus, main_loop, pipeline.downgrade())
         };
 
         // Wait until an EOS or error message appears
         let main_loop_clone = main_loop.clone();
         let branch_failures = self.branch_failures.clone();
+        let failed_pushes = self.failed_pushes.clone();
+        let outputs = self.outputs.clone();
         let bus_watch = move |_: &gst::Bus, msg: &gst::Message| {
             use gst::MessageView;
 
@@ -442,6 +610,38 @@ impl PipelineLifecycle for SharablePipeline {
                         );
                     }
                 }
+                BusAction::FailPush(index) => {
+                    if let MessageView::Error(err) = msg.view() {
+                        tracing::warn!(
+                            "Push output {} errored; it will reconnect, pipeline stays up: {} ({:?})",
+                            index,
+                            err.error(),
+                            err.debug()
+                        );
+                    }
+                    failed_pushes.lock().unwrap().insert(index);
+                }
+                BusAction::FailOutput(id) => {
+                    if let MessageView::Error(err) = msg.view() {
+                        tracing::warn!(
+                            "Output {} errored; taking it out until the next run, pipeline stays up: {} ({:?})",
+                            id,
+                            err.error(),
+                            err.debug()
+                        );
+                        outputs.lock().unwrap().fail(id, err.error().to_string());
+                    }
+                    // Its `errorignore` keeps the error off `input_tee`; the
+                    // output leaves now, on this loop: the detach only
+                    // awaits GStreamer's own async calls.
+                    if let Some(pipeline) = pipeline_weak.upgrade() {
+                        glib::MainContext::default().spawn(async move {
+                            if let Err(e) = passthrough::detach(&pipeline, id).await {
+                                tracing::error!("Failed to detach output {}: {}", id, e);
+                            }
+                        });
+                    }
+                }
                 BusAction::Ignore => (),
             };
 
@@ -500,6 +700,9 @@ impl PipelineLifecycle for SharablePipeline {
             pipeline_state.main_loop = None;
             pipeline_state.pipeline.take()
         };
+        self.failed_pushes.lock().unwrap().clear();
+        self.outputs.lock().unwrap().end_run();
+        self.publishers.lock().unwrap().clear();
         if let Some(pipeline) = pipeline {
             pipeline
                 .call_async_future(move |pipeline| {
@@ -529,6 +732,268 @@ impl PipelineLifecycle for SharablePipeline {
 
 // Helper functions
 impl SharablePipeline {
+    /// The pipeline for an MPEG-TS source: `src` feeds `input_tee`, which
+    /// feeds the demux (and from its pads the egress chains) and, once
+    /// `init` attaches them, the passthrough outputs.
+    fn ts_input(&self, src: gst::Element, args: &Args) -> Result<Pipeline, Error> {
+        // Create a pipeline
+        let pipeline = gst::Pipeline::default();
+
+        let input_tee = Self::create_input_tee()?;
+
+        let whep_queue = Self::create_custom_queue("whep-queue", "0", "0", "no")?;
+        let typefind = gst::ElementFactory::make("typefind")
+            .name("typefind")
+            .build()?;
+        let tsdemux = gst::ElementFactory::make("tsdemux")
+            .name(naming::DEMUX)
+            .property("latency", args.tsdemux_latency as i32)
+            .build()?;
+
+        let video_queue = Self::create_custom_queue(naming::VIDEO_QUEUE, "0", "0", "no")?;
+        let audio_queue = Self::create_custom_queue(naming::AUDIO_QUEUE, "0", "0", "no")?;
+
+        pipeline.add_many([
+            &src,
+            &input_tee,
+            &whep_queue,
+            &typefind,
+            &tsdemux,
+            &video_queue,
+            &audio_queue,
+        ])?;
+        gst::Element::link_many([&src, &input_tee])?;
+        gst::Element::link_many([&input_tee, &whep_queue, &typefind, &tsdemux])?;
+
+        let pipeline_weak = pipeline.downgrade();
+        let events = self.events.clone();
+        // Connect to tsdemux's no-more-pads signal, that is emitted when the element
+        // will not generate more dynamic pads. This usually happens when the stream
+        // is fully received and decoded.
+        tsdemux.connect_no_more_pads(move |dbin| {
+            tracing::info!("No more pads from the stream. Ready to link.");
+            // Here we temporarily retrieve a strong reference on the pipeline from the weak one
+            // we moved into this callback.
+            let pipeline = match pipeline_weak.upgrade() {
+                Some(pipeline) => pipeline,
+                None => return,
+            };
+
+            let all_linked = dbin.foreach_src_pad(|_, pad| {
+                let media_type = pad
+                    .current_caps()
+                    .and_then(|caps| caps.structure(0).map(|s| s.name()));
+                if media_type.is_none() {
+                    tracing::warn!("Failed to get media type from demux pad");
+                    return false;
+                }
+
+                let media_type = media_type.unwrap().as_str();
+                tracing::debug!("linking to media {:?}", media_type);
+
+                let linked =
+                    egress::build_egress_chain(&pipeline, media_type, &video_queue, &audio_queue)
+                        .map_err(|err| {
+                            tracing::error!("Failed to link media: {}", err);
+                            err
+                        });
+
+                linked.is_ok()
+            });
+
+            if all_linked {
+                tracing::info!("Successfully linked stream. Ready to play.");
+                events.publish(Event::StreamReady);
+            } else {
+                tracing::error!("Failed to link stream");
+            }
+        });
+
+        let pipeline_weak = pipeline.downgrade();
+        let events = self.events.clone();
+        // Connect to decodebin's pad-added signal, that is emitted whenever
+        // it found another stream from the input file and found a way to decode it to its raw format.
+        // decodebin automatically adds a src-pad for this raw stream, which
+        // we can use to build the follow-up pipeline.
+        tsdemux.connect_pad_added(move |_dbin, src_pad| {
+            // Here we temporarily retrieve a strong reference on the pipeline from the weak one
+            // we moved into this callback.
+            let pipeline = match pipeline_weak.upgrade() {
+                Some(pipeline) => pipeline,
+                None => return,
+            };
+
+            if let Some(caps) = src_pad.current_caps() {
+                events.publish(Event::CapsDetected {
+                    caps: caps.to_string(),
+                });
+            }
+
+            // Try to detect whether the raw stream decodebin provided us with
+            // just now is either audio or video (or none of both, e.g. subtitles).
+            let (is_audio, is_video) = {
+                let media_type = src_pad.current_caps().and_then(|caps| {
+                    caps.structure(0).map(|s| {
+                        let name = s.name();
+                        (name.starts_with("audio/"), name.starts_with("video/"))
+                    })
+                });
+
+                match media_type {
+                    None => {
+                        tracing::error!("Unknown pad added {:?}", src_pad);
+                        return;
+                    }
+                    Some(media_type) => media_type,
+                }
+            };
+
+            let insert_sink = |is_audio, is_video| -> Result<(), Error> {
+                if is_audio {
+                    // Get the queue element's sink pad and link the decodebin's newly created
+                    // src pad for the audio stream to it.
+                    let audio_queue = pipeline.by_name(naming::AUDIO_QUEUE).with_context(|| {
+                        format!("Failed to find element: {}", naming::AUDIO_QUEUE)
+                    })?;
+                    let sink_pad = audio_queue.static_pad("sink").with_context(|| {
+                        format!("Failed to find element: {}'s sink pad", naming::AUDIO_QUEUE)
+                    })?;
+                    src_pad.link(&sink_pad)?;
+
+                    tracing::info!("Successfully inserted audio sink");
+                }
+                if is_video {
+                    // Get the queue element's sink pad and link the decodebin's newly created
+                    // src pad for the video stream to it.
+                    let video_queue = pipeline.by_name(naming::VIDEO_QUEUE).with_context(|| {
+                        format!("Failed to find element: {}", naming::VIDEO_QUEUE)
+                    })?;
+                    let sink_pad = video_queue.static_pad("sink").with_context(|| {
+                        format!("Failed to find element: {}'s sink pad", naming::VIDEO_QUEUE)
+                    })?;
+                    src_pad.link(&sink_pad)?;
+
+                    tracing::info!("Successfully inserted video sink");
+                }
+
+                Ok(())
+            };
+
+            if let Err(err) = insert_sink(is_audio, is_video) {
+                tracing::error!("Failed to insert sink: {}", err);
+            }
+        });
+
+        Ok(pipeline)
+    }
+
+    /// The pipeline for a WebRTC source (`whip://`, `whep+http(s)://`):
+    /// `src` exposes one `video_%u` / `audio_%u` pad per track, carrying
+    /// what [`Ingest::webrtc_media_types`] says. Each gets its egress chain
+    /// as it appears, straight onto the output tees -- no demux -- and the
+    /// tees are muxed back to MPEG-TS into `input_tee`, for the passthrough
+    /// outputs.
+    ///
+    /// The chains serve one session. When its pads go (the WHIP publisher
+    /// left), the source errors out so the supervisor reruns the pipeline
+    /// for the next one; a second session's pads, were one to reach the
+    /// source meanwhile, are refused rather than clash with the first's.
+    fn webrtc_input(&self, src: gst::Element, ingest: &Ingest) -> Result<Pipeline, Error> {
+        let pipeline = gst::Pipeline::default();
+
+        let video_queue = Self::create_custom_queue(naming::VIDEO_QUEUE, "0", "0", "no")?;
+        let audio_queue = Self::create_custom_queue(naming::AUDIO_QUEUE, "0", "0", "no")?;
+        let mux = gst::ElementFactory::make("mpegtsmux")
+            .property("alignment", 7i32)
+            .build()?;
+        let input_tee = Self::create_input_tee()?;
+
+        pipeline.add_many([&src, &video_queue, &audio_queue, &mux, &input_tee])?;
+        gst::Element::link_many([&mux, &input_tee])?;
+
+        let (video_type, audio_type) = ingest.webrtc_media_types();
+        let pipeline_weak = pipeline.downgrade();
+        let events = self.events.clone();
+        let served = Arc::new(std::sync::Mutex::new(HashSet::<String>::new()));
+        let served_removed = served.clone();
+        src.connect_pad_added(move |_, src_pad| {
+            let Some(pipeline) = pipeline_weak.upgrade() else {
+                return;
+            };
+            if let Some(caps) = src_pad.current_caps() {
+                events.publish(Event::CapsDetected {
+                    caps: caps.to_string(),
+                });
+            }
+
+            let name = src_pad.name();
+            let (media_type, queue, tee) = if name.starts_with("video") {
+                (video_type, &video_queue, naming::OUTPUT_TEE_VIDEO)
+            } else if name.starts_with("audio") {
+                (audio_type, &audio_queue, naming::OUTPUT_TEE_AUDIO)
+            } else {
+                tracing::warn!("Ignoring WebRTC source pad {}", name);
+                return;
+            };
+            if queue.static_pad("sink").is_some_and(|pad| pad.is_linked()) {
+                tracing::warn!(
+                    "Ignoring WebRTC {}: a session is already being served",
+                    name
+                );
+                return;
+            }
+
+            // The chain goes in before the pad is linked: what it accepts
+            // (raw media, for whipserversrc) is what tells the source to
+            // decode.
+            let linked =
+                egress::build_egress_chain(&pipeline, media_type, &video_queue, &audio_queue)
+                    .and_then(|()| passthrough::tap_into_mux(&pipeline, tee, &mux))
+                    .and_then(|()| {
+                        let sink_pad = queue.static_pad("sink").with_context(|| {
+                            format!("Failed to find element: {}'s sink pad", queue.name())
+                        })?;
+                        src_pad.link(&sink_pad)?;
+                        Ok(())
+                    });
+            match linked {
+                Ok(()) => {
+                    tracing::info!("Successfully linked WebRTC {}", name);
+                    served.lock().unwrap().insert(name.to_string());
+                    events.publish(Event::StreamReady);
+                }
+                Err(err) => tracing::error!("Failed to link WebRTC {}: {}", name, err),
+            }
+        });
+
+        src.connect_pad_removed(move |src, src_pad| {
+            // A removed pad is unlinked already: only the served session's
+            // pads, remembered as they were linked, end it.
+            let served = served_removed
+                .lock()
+                .unwrap()
+                .remove(src_pad.name().as_str());
+            if served {
+                gst::element_error!(
+                    src,
+                    gst::ResourceError::Read,
+                    ("WebRTC session ended: {} removed", src_pad.name())
+                );
+            }
+        });
+
+        Ok(pipeline)
+    }
+
+    /// The tee the passthrough outputs hang off. It may have none of them
+    /// (under a WebRTC input, nothing else): unlinked is not an error.
+    fn create_input_tee() -> Result<gst::Element, Error> {
+        Ok(gst::ElementFactory::make("tee")
+            .name(naming::INPUT_TEE)
+            .property("allow-not-linked", true)
+            .build()?)
+    }
+
     /// Create a queue element with given name and properties
     /// To check if the queue is blocking, we connect to the overrun and underrun signals
     fn create_custom_queue(
@@ -0,0 +1,627 @@
+//! The ingest source: the element in front of `input_tee`, chosen by the
+//! scheme of `--input-address`.
+//!
+//! Every source produces MPEG-TS, so everything from `input_tee` on -- the
+//! demux, the egress chains, the passthrough output -- is the same whatever
+//! feeds it. Adding a source means a variant here, its element in `build`,
+//! and its factories in `required_elements`. The WebRTC sources (`whip://`,
+//! `whep+http(s)://`) are the exception: see [`Ingest::is_webrtc`].
+
+use anyhow::{anyhow, Error};
+use gst::prelude::*;
+use gstreamer as gst;
+use std::path::PathBuf;
+
+use crate::stream::naming;
+use crate::stream::pipeline::{Args, SRTMode};
+use crate::stream::preflight::{element, RequiredElement};
+use crate::stream::stats::{structure_to_json, InputStats};
+use crate::stream::utils::run_discoverer;
+
+/// `--input-address` of the built-in test source.
+pub const TEST_SOURCE_ADDRESS: &str = "test://";
+
+/// The test source's pipeline: SMPTE bars with the wall clock burnt in and
+/// a 440 Hz tone, encoded the way a typical contribution feed is.
+const TEST_SOURCE_DESCRIPTION: &str = "\
+    videotestsrc is-live=true pattern=smpte \
+    ! video/x-raw,width=1280,height=720,framerate=30/1 \
+    ! clockoverlay halignment=right valignment=bottom shaded-background=true \
+    ! videoconvert \
+    ! x264enc tune=zerolatency speed-preset=ultrafast key-int-max=30 bitrate=2000 \
+    ! video/x-h264,profile=constrained-baseline \
+    ! h264parse config-interval=-1 ! queue ! mux. \
+    audiotestsrc is-live=true wave=sine freq=440 volume=0.2 \
+    ! audioconvert ! audioresample ! avenc_aac ! aacparse ! queue ! mux. \
+    mpegtsmux name=mux alignment=7";
+
+/// Jitter buffer latency of `rtp://` input without `?latency=`.
+const DEFAULT_RTP_LATENCY_MS: u32 = 200;
+
+/// Where the MPEG-TS comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ingest {
+    /// `ip:port` or `srt://ip:port`: SRT, in `--srt-mode`.
+    Srt { address: String },
+    /// `test://`: generated in-process, no network or external process.
+    TestSource,
+    /// `udp://ip:port[?iface=<nic>]`: raw MPEG-TS over UDP, unicast or
+    /// multicast (joined on `iface` if given).
+    Udp {
+        address: String,
+        iface: Option<String>,
+    },
+    /// `rtp://ip:port[?iface=<nic>&latency=<ms>]`: MPEG-TS in RTP (RFC
+    /// 2250) over UDP, through a jitter buffer of `latency` ms.
+    Rtp {
+        address: String,
+        iface: Option<String>,
+        latency_ms: u32,
+    },
+    /// `file:///path/to.ts[?loop=true]`: a recorded TS played back in real
+    /// time, from the start again at its end if `looping`.
+    File { path: PathBuf, looping: bool },
+    /// `rist://ip:port`: RIST (Simple Profile) carrying MPEG-TS, tuned by
+    /// the `--rist-*` flags.
+    Rist { address: String },
+    /// `whip://`: a WebRTC publisher, accepted by `POST /whip`. Not MPEG-TS:
+    /// see [`Ingest::is_webrtc`].
+    Whip,
+    /// `whep+https://host/path` (or `whep+http://`): pulled from a remote
+    /// WHEP server at `endpoint`, as H.264 and Opus.
+    Whep { endpoint: String },
+}
+
+impl Ingest {
+    pub fn parse(input_address: &str) -> Result<Self, Error> {
+        match input_address.split_once("://") {
+            None => Ok(Ingest::Srt {
+                address: input_address.to_string(),
+            }),
+            Some(("srt", address)) => Ok(Ingest::Srt {
+                address: address.to_string(),
+            }),
+            Some(("test", "")) => Ok(Ingest::TestSource),
+            Some(("test", rest)) => Err(anyhow!("test:// takes no address, got {:?}", rest)),
+            Some(("udp", rest)) => {
+                let (address, mut options) = Options::split(rest)?;
+                let ingest = Ingest::Udp {
+                    address,
+                    iface: options.take("iface"),
+                };
+                options.finish(ingest)
+            }
+            Some(("rtp", rest)) => {
+                let (address, mut options) = Options::split(rest)?;
+                let latency_ms = match options.take("latency") {
+                    Some(ms) => ms
+                        .parse()
+                        .map_err(|_| anyhow!("rtp:// latency must be milliseconds, got {}", ms))?,
+                    None => DEFAULT_RTP_LATENCY_MS,
+                };
+                let ingest = Ingest::Rtp {
+                    address,
+                    iface: options.take("iface"),
+                    latency_ms,
+                };
+                options.finish(ingest)
+            }
+            Some(("whip", "")) => Ok(Ingest::Whip),
+            Some(("whip", rest)) => Err(anyhow!(
+                "whip:// takes no address (publishers POST to /whip), got {:?}",
+                rest
+            )),
+            Some((scheme @ ("whep+http" | "whep+https"), rest)) => {
+                if rest.is_empty() {
+                    return Err(anyhow!("{}:// needs the WHEP endpoint's address", scheme));
+                }
+                Ok(Ingest::Whep {
+                    endpoint: format!("{}://{}", &scheme["whep+".len()..], rest),
+                })
+            }
+            Some(("rist", rest)) => {
+                let (address, options) = Options::split(rest)?;
+                options.finish(Ingest::Rist { address })
+            }
+            Some(("file", rest)) => {
+                let (path, mut options) = Options::split(rest)?;
+                let looping = match options.take("loop").as_deref() {
+                    None | Some("false") => false,
+                    Some("true") => true,
+                    Some(other) => {
+                        return Err(anyhow!("file:// loop must be true or false, got {}", other))
+                    }
+                };
+                let ingest = Ingest::File {
+                    path: PathBuf::from(path),
+                    looping,
+                };
+                options.finish(ingest)
+            }
+            Some((scheme, _)) => Err(anyhow!("Unsupported input scheme: {}://", scheme)),
+        }
+    }
+
+    /// Whether the source is WebRTC: decoded media on one pad per track,
+    /// linked straight to the egress chains, rather than MPEG-TS for the
+    /// demux.
+    pub fn is_webrtc(&self) -> bool {
+        matches!(self, Ingest::Whip | Ingest::Whep { .. })
+    }
+
+    /// Whether publishers call in: an SRT input in `--srt-mode listener`.
+    pub(crate) fn takes_publishers(&self, args: &Args) -> bool {
+        matches!(self, Ingest::Srt { .. }) && args.srt_mode == SRTMode::Listener
+    }
+
+    /// Refuse the SRT publisher settings in `args` if they cannot apply to
+    /// this input, rather than run without them.
+    pub fn check_args(&self, args: &Args) -> Result<(), Error> {
+        if (!args.srt_allow.is_empty() || !args.srt_streamid.is_empty())
+            && !self.takes_publishers(args)
+        {
+            return Err(anyhow!(
+                "--srt-allow and --srt-streamid need an SRT input in --srt-mode listener"
+            ));
+        }
+        if let Some(passphrase) = &args.srt_passphrase {
+            if !matches!(self, Ingest::Srt { .. }) {
+                return Err(anyhow!("--srt-passphrase needs an SRT input"));
+            }
+            // libsrt refuses anything else when the socket is opened.
+            if !(10..=79).contains(&passphrase.len()) {
+                return Err(anyhow!("--srt-passphrase must be 10 to 79 characters"));
+            }
+        }
+        Ok(())
+    }
+
+    /// What a WebRTC source's `video_*` and `audio_*` pads carry: decoded
+    /// media from `whipserversrc`; the H.264 and Opus as received from a
+    /// remote WHEP server, only depayloaded.
+    pub(crate) fn webrtc_media_types(&self) -> (&'static str, &'static str) {
+        match self {
+            Ingest::Whep { .. } => ("video/x-h264", "audio/x-opus"),
+            _ => ("video/x-raw", "audio/x-raw"),
+        }
+    }
+
+    /// The protocol, as `GET /input/stats` names it.
+    pub fn protocol(&self) -> &'static str {
+        match self {
+            Ingest::Srt { .. } => "srt",
+            Ingest::TestSource => "test",
+            Ingest::Udp { .. } => "udp",
+            Ingest::Rtp { .. } => "rtp",
+            Ingest::File { .. } => "file",
+            Ingest::Rist { .. } => "rist",
+            Ingest::Whip => "whip",
+            Ingest::Whep { .. } => "whep",
+        }
+    }
+
+    /// The source's statistics, if it keeps any: `srtsrc`'s and
+    /// `ristsrc`'s `stats` property.
+    pub(crate) fn stats(&self, pipeline: &gst::Pipeline) -> InputStats {
+        let element = match self {
+            Ingest::Srt { .. } => pipeline.by_name(naming::SRT_SOURCE),
+            Ingest::Rist { .. } => pipeline.by_name(naming::RIST_SOURCE),
+            _ => None,
+        };
+        InputStats {
+            protocol: self.protocol().to_string(),
+            stats: element
+                .filter(|element| element.find_property("stats").is_some())
+                .map(|element| structure_to_json(&element.property::<gst::Structure>("stats"))),
+            publishers: None,
+        }
+    }
+
+    /// The element factories this source creates, with what each is for.
+    pub fn required_elements(&self) -> Vec<RequiredElement> {
+        match self {
+            Ingest::Srt { .. } => vec![element("srtsrc", "SRT input")],
+            Ingest::TestSource => vec![
+                element("videotestsrc", "test source video"),
+                element("clockoverlay", "test source video"),
+                element("videoconvert", "test source video"),
+                element("x264enc", "test source video"),
+                element("audiotestsrc", "test source audio"),
+                element("avenc_aac", "test source audio"),
+                element("mpegtsmux", "test source muxing"),
+            ],
+            Ingest::Udp { .. } => vec![element("udpsrc", "UDP input")],
+            Ingest::Rtp { .. } => vec![
+                element("udpsrc", "RTP input"),
+                element("rtpjitterbuffer", "RTP input"),
+                element("rtpmp2tdepay", "RTP input"),
+            ],
+            Ingest::File { looping, .. } => vec![
+                if *looping {
+                    element("multifilesrc", "looped file input")
+                } else {
+                    element("filesrc", "file input")
+                },
+                element("tsparse", "file input pacing"),
+                element("clocksync", "file input pacing"),
+            ],
+            Ingest::Rist { .. } => vec![
+                element("ristsrc", "RIST input"),
+                element("rtpmp2tdepay", "RIST input"),
+            ],
+            Ingest::Whip => vec![
+                element("whipserversrc", "WHIP input (rswebrtc)"),
+                element("videoconvert", "WebRTC input video encode"),
+                element("x264enc", "WebRTC input video encode"),
+                element("mpegtsmux", "WebRTC input pass-through output"),
+            ],
+            Ingest::Whep { .. } => vec![
+                element("whepsrc", "WHEP input (rswebrtchttp)"),
+                element("rtph264depay", "WHEP input video"),
+                element("rtpopusdepay", "WHEP input audio"),
+                element("opusparse", "WHEP input audio"),
+                element("mpegtsmux", "WebRTC input pass-through output"),
+            ],
+        }
+    }
+
+    /// Build the source element; its single src pad feeds `input_tee`.
+    pub(crate) fn build(&self, args: &Args) -> Result<gst::Element, Error> {
+        match self {
+            Ingest::Srt { address } => {
+                let uri = format!("srt://{}?mode={}", address, args.srt_mode.to_str());
+                tracing::info!("SRT Input uri: {}", uri);
+
+                // Run discoverer if the source stream is in listener mode (we are the caller)
+                if args.srt_mode == SRTMode::Caller && args.run_discoverer {
+                    tracing::info!("Running discoverer...");
+                    // Swallow error if discoverer fails (This could happen When SRT client is running in Docker container)
+                    let _ = run_discoverer(&uri, args.discoverer_timeout_sec);
+                }
+
+                let mut src = gst::ElementFactory::make("srtsrc")
+                    .name(naming::SRT_SOURCE)
+                    .property("uri", uri)
+                    .property("latency", args.srt_latency as i32);
+                if let Some(passphrase) = &args.srt_passphrase {
+                    src = src.property("passphrase", passphrase);
+                }
+                Ok(src.build()?)
+            }
+            Ingest::TestSource => {
+                tracing::info!("Input: built-in test source");
+                let bin = gst::parse::bin_from_description(TEST_SOURCE_DESCRIPTION, true)?;
+                bin.set_property("name", naming::TEST_SOURCE);
+                Ok(bin.upcast())
+            }
+            Ingest::Udp { address, iface } => {
+                let uri = format!("udp://{}", address);
+                tracing::info!("UDP Input uri: {}", uri);
+                let mut src = gst::ElementFactory::make("udpsrc")
+                    .name(naming::INPUT_SOURCE)
+                    .property("uri", uri);
+                if let Some(iface) = iface {
+                    src = src.property("multicast-iface", iface);
+                }
+                Ok(src.build()?)
+            }
+            Ingest::Rtp {
+                address,
+                iface,
+                latency_ms,
+            } => {
+                let uri = format!("udp://{}", address);
+                tracing::info!("RTP Input uri: {}", uri);
+                let mut src = gst::ElementFactory::make("udpsrc")
+                    .property("uri", uri)
+                    .property(
+                        "caps",
+                        gst::Caps::builder("application/x-rtp")
+                            .field("media", "video")
+                            .field("clock-rate", 90000i32)
+                            .field("encoding-name", "MP2T")
+                            .build(),
+                    );
+                if let Some(iface) = iface {
+                    src = src.property("multicast-iface", iface);
+                }
+                let jitterbuffer = gst::ElementFactory::make("rtpjitterbuffer")
+                    .property("latency", *latency_ms)
+                    .build()?;
+                let depay = gst::ElementFactory::make("rtpmp2tdepay").build()?;
+                source_bin([&src.build()?, &jitterbuffer, &depay])
+            }
+            Ingest::Rist { address } => {
+                tracing::info!("RIST Input: rist://{}", address);
+                let (host, port) = host_port(address)?;
+                let src = gst::ElementFactory::make("ristsrc")
+                    .name(naming::RIST_SOURCE)
+                    .property("address", host)
+                    .property("port", port)
+                    .property("encoding-name", "MP2T")
+                    .property("receiver-buffer", args.rist_receiver_buffer_ms)
+                    .property("reorder-section", args.rist_reorder_section_ms)
+                    .property("max-rtx-retries", args.rist_max_rtx_retries)
+                    .build()?;
+                let depay = gst::ElementFactory::make("rtpmp2tdepay").build()?;
+                source_bin([&src, &depay])
+            }
+            Ingest::Whip => {
+                let server = whip_server_address(args.whip_server_port);
+                tracing::info!("WHIP Input: publishers POST /whip, served by {}", server);
+                let src = gst::ElementFactory::make("whipserversrc")
+                    .name(naming::WEBRTC_SOURCE)
+                    .build()?;
+                // As for whipclientsink (see `Branch::attach`): the signaller
+                // is reached as a plain GObject, not the plugin's Rust types.
+                let signaller = src.property::<gst::glib::Object>("signaller");
+                signaller.set_property_from_str("host-addr", &server);
+                Ok(src)
+            }
+            Ingest::Whep { endpoint } => {
+                tracing::info!("WHEP Input: pulling from {}", endpoint);
+                let mut whepsrc = gst::ElementFactory::make("whepsrc")
+                    .property("whep-endpoint", endpoint)
+                    .property(
+                        "video-caps",
+                        gst::Caps::builder("application/x-rtp")
+                            .field("media", "video")
+                            .field("encoding-name", "H264")
+                            .field("clock-rate", 90000i32)
+                            .build(),
+                    )
+                    .property(
+                        "audio-caps",
+                        gst::Caps::builder("application/x-rtp")
+                            .field("media", "audio")
+                            .field("encoding-name", "OPUS")
+                            .field("clock-rate", 48000i32)
+                            .build(),
+                    );
+                if let Some(token) = &args.whep_input_token {
+                    whepsrc = whepsrc.property("auth-token", token);
+                }
+                whep_source_bin(whepsrc.build()?)
+            }
+            Ingest::File { path, looping } => {
+                tracing::info!("File Input: {} (loop: {})", path.display(), looping);
+                // A file reads as fast as the disk allows; tsparse stamps the
+                // packets from the PCR and clocksync releases them in real
+                // time, as a live source would.
+                let src = if *looping {
+                    gst::ElementFactory::make("multifilesrc")
+                        .property("location", path.to_string_lossy().as_ref())
+                        .property("loop", true)
+                        .build()?
+                } else {
+                    gst::ElementFactory::make("filesrc")
+                        .property("location", path.to_string_lossy().as_ref())
+                        .build()?
+                };
+                let tsparse = gst::ElementFactory::make("tsparse")
+                    .property("set-timestamps", true)
+                    .build()?;
+                let clocksync = gst::ElementFactory::make("clocksync").build()?;
+                source_bin([&src, &tsparse, &clocksync])
+            }
+        }
+    }
+}
+
+/// `elements` linked in a chain inside an `INPUT_SOURCE` bin, whose src pad
+/// is the last one's.
+fn source_bin<const N: usize>(elements: [&gst::Element; N]) -> Result<gst::Element, Error> {
+    let bin = gst::Bin::builder().name(naming::INPUT_SOURCE).build();
+    bin.add_many(elements)?;
+    gst::Element::link_many(elements)?;
+    let pad = elements[N - 1]
+        .static_pad("src")
+        .ok_or_else(|| anyhow!("{} has no src pad", elements[N - 1].name()))?;
+    bin.add_pad(&gst::GhostPad::with_target(&pad)?)?;
+    Ok(bin.upcast())
+}
+
+/// `whepsrc` in a `WEBRTC_SOURCE` bin that depayloads each RTP pad it
+/// adds into a `video_*` / `audio_*` ghost pad, as `whipserversrc` names
+/// its own. A session the remote server drops without an error (ICE fails,
+/// the peer closes) is turned into one on the bin, so the supervisor
+/// restarts the pipeline and pulls again.
+fn whep_source_bin(whepsrc: gst::Element) -> Result<gst::Element, Error> {
+    let bin = gst::Bin::builder().name(naming::WEBRTC_SOURCE).build();
+    bin.add(&whepsrc)?;
+
+    let bin_weak = bin.downgrade();
+    whepsrc.connect_pad_added(move |_, pad| {
+        let Some(bin) = bin_weak.upgrade() else {
+            return;
+        };
+        if let Err(err) = depayload(&bin, pad) {
+            tracing::error!("Failed to depayload WHEP {}: {}", pad.name(), err);
+        }
+    });
+
+    let webrtcbin = whepsrc.downcast_ref::<gst::Bin>().and_then(|whepsrc| {
+        whepsrc
+            .iterate_recurse()
+            .find(|e| e.factory().is_some_and(|f| f.name() == "webrtcbin"))
+    });
+    match webrtcbin {
+        Some(webrtcbin) => {
+            let bin_weak = bin.downgrade();
+            webrtcbin.connect_notify(Some("connection-state"), move |webrtcbin, _| {
+                let state = webrtcbin.property_value("connection-state");
+                let Some((_, state)) = gst::glib::EnumValue::from_value(&state) else {
+                    return;
+                };
+                if let (Some(bin), "failed" | "closed") = (bin_weak.upgrade(), state.nick()) {
+                    gst::element_error!(
+                        bin,
+                        gst::ResourceError::Read,
+                        ("Remote WHEP session {}", state.nick())
+                    );
+                }
+            });
+        }
+        None => tracing::warn!("whepsrc has no webrtcbin; only its errors end the session"),
+    }
+    Ok(bin.upcast())
+}
+
+/// Depayload `pad`, one of `whepsrc`'s RTP streams, onto a ghost pad of
+/// `bin` named after its media.
+fn depayload(bin: &gst::Bin, pad: &gst::Pad) -> Result<(), Error> {
+    let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+    let media = caps
+        .structure(0)
+        .and_then(|s| s.get::<&str>("media").ok())
+        .ok_or_else(|| anyhow!("no media in caps {}", caps))?;
+    let depay = match media {
+        "video" => "rtph264depay",
+        "audio" => "rtpopusdepay",
+        other => return Err(anyhow!("unexpected media {}", other)),
+    };
+    let depay = gst::ElementFactory::make(depay).build()?;
+    bin.add(&depay)?;
+    let sink = depay
+        .static_pad("sink")
+        .ok_or_else(|| anyhow!("{} has no sink pad", depay.name()))?;
+    pad.link(&sink)?;
+    depay.sync_state_with_parent()?;
+
+    let src = depay
+        .static_pad("src")
+        .ok_or_else(|| anyhow!("{} has no src pad", depay.name()))?;
+    let name = format!("{}_{}", media, pad.name().trim_start_matches("src_"));
+    let ghost = gst::GhostPad::builder_with_target(&src)?.name(name).build();
+    ghost.set_active(true)?;
+    bin.add_pad(&ghost)?;
+    Ok(())
+}
+
+/// The element whose src pads are the input's media: the demux for an
+/// MPEG-TS source, the source itself for a WebRTC one. Either names its
+/// pads `video_*` / `audio_*`.
+pub(crate) fn media_source(pipeline: &gst::Pipeline) -> Option<gst::Element> {
+    pipeline
+        .by_name(naming::DEMUX)
+        .or_else(|| pipeline.by_name(naming::WEBRTC_SOURCE))
+}
+
+/// Where `whipserversrc` serves WHIP, on loopback only: publishers reach it
+/// through `POST /whip`, which authenticates them first.
+pub fn whip_server_address(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
+
+/// `address` split into host and port, for elements that take them apart.
+pub(crate) fn host_port(address: &str) -> Result<(&str, u32), Error> {
+    address
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+        .ok_or_else(|| anyhow!("Address must be ip:port: {}", address))
+}
+
+/// The `?key=value&...` options of an input or output URI. Every option
+/// must be taken by its parser: an unknown one is an error, not ignored.
+pub(crate) struct Options(Vec<(String, String)>);
+
+impl Options {
+    /// Split `rest` (the URI after `scheme://`) into address and options.
+    pub(crate) fn split(rest: &str) -> Result<(String, Self), Error> {
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        if address.is_empty() {
+            return Err(anyhow!("URI has no address: {}", rest));
+        }
+        let options = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => Ok((key.to_string(), value.to_string())),
+                None => Err(anyhow!("URI option without a value: {}", pair)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((address.to_string(), Options(options)))
+    }
+
+    pub(crate) fn take(&mut self, key: &str) -> Option<String> {
+        let index = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    /// `parsed`, if every option was taken.
+    pub(crate) fn finish<T>(self, parsed: T) -> Result<T, Error> {
+        match self.0.first() {
+            None => Ok(parsed),
+            Some((key, _)) => Err(anyhow!("Unknown URI option: {}", key)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ingest;
+
+    #[test]
+    fn the_input_address_scheme_selects_the_source() {
+        let srt = |address: &str| Ingest::Srt {
+            address: address.to_string(),
+        };
+        assert_eq!(
+            srt("127.0.0.1:1234"),
+            Ingest::parse("127.0.0.1:1234").unwrap()
+        );
+        assert_eq!(
+            srt("127.0.0.1:1234"),
+            Ingest::parse("srt://127.0.0.1:1234").unwrap()
+        );
+        assert_eq!(Ingest::TestSource, Ingest::parse("test://").unwrap());
+        assert_eq!(Ingest::Whip, Ingest::parse("whip://").unwrap());
+        assert!(Ingest::parse("whip://0.0.0.0:8190").is_err());
+        assert_eq!(
+            Ingest::Whep {
+                endpoint: "https://cdn.example.com/whep/live".to_string(),
+            },
+            Ingest::parse("whep+https://cdn.example.com/whep/live").unwrap()
+        );
+        assert!(Ingest::parse("whep+http://").is_err());
+        assert!(Ingest::parse("whep://cdn.example.com/whep/live").is_err());
+        assert!(Ingest::parse("test://bars").is_err());
+        assert!(Ingest::parse("gopher://127.0.0.1:70").is_err());
+    }
+
+    #[test]
+    fn network_and_file_options_are_parsed_and_unknown_ones_rejected() {
+        assert_eq!(
+            Ingest::Udp {
+                address: "239.1.1.1:5000".to_string(),
+                iface: Some("eth1".to_string()),
+            },
+            Ingest::parse("udp://239.1.1.1:5000?iface=eth1").unwrap()
+        );
+        assert_eq!(
+            Ingest::Rtp {
+                address: "0.0.0.0:5004".to_string(),
+                iface: None,
+                latency_ms: 50,
+            },
+            Ingest::parse("rtp://0.0.0.0:5004?latency=50").unwrap()
+        );
+        assert_eq!(
+            Ingest::File {
+                path: "/media/rehearsal.ts".into(),
+                looping: true,
+            },
+            Ingest::parse("file:///media/rehearsal.ts?loop=true").unwrap()
+        );
+        assert!(Ingest::parse("udp://239.1.1.1:5000?ttl=4").is_err());
+        assert!(Ingest::parse("rtp://0.0.0.0:5004?latency=soon").is_err());
+        assert!(Ingest::parse("file:///media/rehearsal.ts?loop=yes").is_err());
+        assert!(Ingest::parse("udp://").is_err());
+        assert_eq!(
+            Ingest::Rist {
+                address: "0.0.0.0:5004".to_string(),
+            },
+            Ingest::parse("rist://0.0.0.0:5004").unwrap()
+        );
+    }
+}
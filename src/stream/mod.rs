@@ -3,12 +3,27 @@ mod bus;
 mod egress;
 mod errors;
 mod gst_pipeline;
+mod ingest;
 mod naming;
+mod passthrough;
 mod pipeline;
+mod preflight;
+mod srt_callers;
+mod stats;
 mod utils;
 
 pub use branch::{whip_sink_path, WHIP_SINK_ROUTE};
-pub use errors::PipelineError;
+pub use errors::{NotReadyReason, PipelineError};
 pub use gst_pipeline::*;
+pub use ingest::{whip_server_address, Ingest, TEST_SOURCE_ADDRESS};
 pub use naming::BranchId;
+pub use passthrough::Passthrough;
 pub use pipeline::*;
+pub use preflight::{
+    preflight, required_elements, ElementStatus, PluginInfo, PreflightMode, PreflightReport,
+    RequiredElement,
+};
+pub use srt_callers::{CallerInfo, IpNet};
+pub use stats::{
+    AggregateStats, CandidatePair, IceCandidate, InputStats, StreamStats, ViewerSample, ViewerStats,
+};
@@ -14,17 +14,34 @@
 
 // Core (viewer-independent) elements. These names are referenced from more than
 // one place, so they live here once.
+pub(crate) const INPUT_TEE: &str = "input_tee";
 pub(crate) const DEMUX: &str = "demux";
 pub(crate) const VIDEO_QUEUE: &str = "video-queue";
 pub(crate) const AUDIO_QUEUE: &str = "audio-queue";
 pub(crate) const OUTPUT_TEE_VIDEO: &str = "output_tee_video";
 pub(crate) const OUTPUT_TEE_AUDIO: &str = "output_tee_audio";
 pub(crate) const SRT_SOURCE: &str = "srt_source";
+pub(crate) const TEST_SOURCE: &str = "test_source";
+pub(crate) const INPUT_SOURCE: &str = "input_source";
+pub(crate) const RIST_SOURCE: &str = "rist_source";
+pub(crate) const WEBRTC_SOURCE: &str = "webrtc_source";
 
 // Branch-only stems (no core element shares these names).
 const WHIP_SINK_STEM: &str = "whip-sink";
 const VIDEO_DECODER_STEM: &str = "avdec-h264"; // present only under --decode-video
 
+// A push output's branch id: its elements are named like a viewer's, with
+// `push-<index>` for the connection id. Connection ids are UUIDs, so the two
+// never collide.
+const PUSH_STEM: &str = "push";
+
+// A passthrough output's elements, by output id: its leaky queue off
+// `input_tee`, the `errorignore` behind it and its sink (an element, or a
+// bin for RIST).
+const OUTPUT_QUEUE_STEM: &str = "output-queue";
+const OUTPUT_GUARD_STEM: &str = "output-guard";
+const OUTPUT_STEM: &str = "output";
+
 pub(crate) fn video_queue_name(id: &str) -> String {
     format!("{VIDEO_QUEUE}-{id}")
 }
@@ -41,6 +58,36 @@ pub(crate) fn video_decoder_name(id: &str) -> String {
     format!("{VIDEO_DECODER_STEM}-{id}")
 }
 
+/// The branch id of push output `index`.
+pub(crate) fn push_branch_id(index: usize) -> String {
+    format!("{PUSH_STEM}-{index}")
+}
+
+/// If branch `id` is a push output's, its index.
+pub(crate) fn push_index_from_branch_id(id: &str) -> Option<usize> {
+    id.strip_prefix(PUSH_STEM)?.strip_prefix('-')?.parse().ok()
+}
+
+pub(crate) fn output_queue_name(id: usize) -> String {
+    format!("{OUTPUT_QUEUE_STEM}-{id}")
+}
+
+pub(crate) fn output_guard_name(id: usize) -> String {
+    format!("{OUTPUT_GUARD_STEM}-{id}")
+}
+
+pub(crate) fn output_name(id: usize) -> String {
+    format!("{OUTPUT_STEM}-{id}")
+}
+
+/// If `name` is one of passthrough output `id`'s elements, that id. Like a
+/// branch element it is exactly `<stem>-<id>`, so no core element matches.
+pub(crate) fn output_id_from_name(name: &str) -> Option<usize> {
+    [OUTPUT_QUEUE_STEM, OUTPUT_GUARD_STEM, OUTPUT_STEM]
+        .iter()
+        .find_map(|stem| name.strip_prefix(stem)?.strip_prefix('-')?.parse().ok())
+}
+
 /// Identity of one viewer's WHEP output branch as it crosses the pipeline's
 /// bus-reap channel to the coordinator. A newtype so the seam carries a type
 /// instead of a bare `String`, and so `src/stream` owns it — the module graph
@@ -120,20 +167,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn push_branch_ids_map_back_to_their_index_and_viewer_ids_do_not() {
+        assert_eq!("push-3", push_branch_id(3));
+        assert_eq!(Some(3), push_index_from_branch_id(&push_branch_id(3)));
+        let whip_sink = whip_sink_name(&push_branch_id(0));
+        assert_eq!(Some("push-0"), branch_id_from_name(&whip_sink));
+        for viewer in [
+            "3f2c9a1e-0b7d-4c7e-9a51-6f1e2d3c4b5a",
+            "push",
+            "push-",
+            "pushy-1",
+        ] {
+            assert_eq!(None, push_index_from_branch_id(viewer), "{viewer}");
+        }
+    }
+
+    #[test]
+    fn output_element_names_map_back_to_their_id() {
+        assert_eq!("output-queue-4", output_queue_name(4));
+        assert_eq!("output-4", output_name(4));
+        assert_eq!(Some(4), output_id_from_name(&output_queue_name(4)));
+        assert_eq!(Some(4), output_id_from_name(&output_guard_name(4)));
+        assert_eq!(Some(4), output_id_from_name(&output_name(4)));
+        for name in [
+            OUTPUT_TEE_VIDEO,
+            OUTPUT_TEE_AUDIO,
+            "output",
+            "output-",
+            "outputs-1",
+        ] {
+            assert_eq!(None, output_id_from_name(name), "{name}");
+        }
+        assert_eq!(None, branch_id_from_name(&output_name(4)));
+    }
+
     #[test]
     fn core_element_names_never_classify_as_a_branch() {
         // The load-bearing invariant: a core element error must stay fatal.
         // Asserted against the consts (not literals) so this test breaks the
         // instant the derive-from-stem relationship is broken.
         for name in [
+            INPUT_TEE,
             DEMUX,
             VIDEO_QUEUE,
             AUDIO_QUEUE,
             OUTPUT_TEE_VIDEO,
             OUTPUT_TEE_AUDIO,
             SRT_SOURCE,
+            TEST_SOURCE,
+            INPUT_SOURCE,
+            RIST_SOURCE,
+            WEBRTC_SOURCE,
         ] {
             assert_eq!(None, branch_id_from_name(name), "{name} wrongly contained");
+            assert_eq!(None, output_id_from_name(name), "{name} wrongly contained");
         }
     }
 }
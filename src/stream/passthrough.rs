@@ -0,0 +1,501 @@
+//! The passthrough outputs: the input's MPEG-TS re-sent untouched from
+//! `input_tee`, by SRT or RIST, chosen by the scheme of each
+//! `--output-address`. A WebRTC input has no TS to pass through; its output
+//! tees are muxed into one instead (`tap_into_mux`).
+//!
+//! There may be none or many, each with its own leaky queue off `input_tee`:
+//! a receiver that stalls loses its own data instead of holding up the tee,
+//! and with it the demux and every WHEP viewer. Behind the queue an
+//! `errorignore` keeps a failed sink's flow error from reaching the tee; the
+//! bus watch takes the output out instead. [`Outputs`] keeps them by id
+//! across pipeline restarts, and the admin API adds and removes them at
+//! runtime.
+//!
+//! An SRT output in listener mode serves every caller that connects to it,
+//! from its one queue and its one `srtsink`: the sink sends each buffer to
+//! every caller's socket in turn. Its callers are tracked and admitted by
+//! streamid through [`srt_callers`].
+//!
+//! The callers are not isolated from each other. A caller slow enough to
+//! hold up `srtsink` holds up its other callers too, and the leaky queue
+//! drops their data along with its own. A queue and sink per caller is not
+//! possible: libsrt binds one listener per port, and `srtsink` owns the
+//! sockets it accepts, with no way to hand one to another sink. One caller
+//! can still be dropped on its own: disconnecting it closes its socket
+//! ([`srt_callers::close`]), and `srtsink` drops just that caller.
+
+use anyhow::{anyhow, Error};
+use clap::ValueEnum;
+use gst::prelude::*;
+use gstreamer as gst;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use crate::stream::branch::Branch;
+use crate::stream::ingest::{host_port, Options};
+use crate::stream::naming;
+use crate::stream::pipeline::{Args, OutputInfo, OutputState, SRTMode};
+use crate::stream::preflight::{element, RequiredElement};
+use crate::stream::srt_callers::{self, Admission, Callers, SrtSocket};
+
+/// Where a passthrough output sends the TS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Passthrough {
+    /// `ip:port`, or `srt://ip:port` with any of the options
+    /// `?mode=caller|listener&latency=<ms>&passphrase=<secret>&streamid=<id>`.
+    /// Without `mode`, the one opposite to `--srt-mode`; without `latency`,
+    /// `srtsink`'s default. A caller presents its one `streamid`; a
+    /// listener takes `streamid` any number of times, and lets in only
+    /// callers presenting one of them.
+    Srt {
+        address: String,
+        mode: Option<SRTMode>,
+        latency_ms: Option<u32>,
+        passphrase: Option<String>,
+        streamids: Vec<String>,
+    },
+    /// `rist://ip:port`: RIST (Simple Profile) to a receiver at `address`.
+    Rist { address: String },
+}
+
+impl Passthrough {
+    pub fn parse(output_address: &str) -> Result<Self, Error> {
+        match output_address.split_once("://") {
+            None => Ok(Self::srt(output_address)),
+            Some(("srt", rest)) => {
+                let (address, mut options) = Options::split(rest)?;
+                let mode = options
+                    .take("mode")
+                    .map(|mode| {
+                        SRTMode::from_str(&mode, true).map_err(|_| {
+                            anyhow!("srt:// mode must be caller or listener, got {}", mode)
+                        })
+                    })
+                    .transpose()?;
+                let latency_ms = options
+                    .take("latency")
+                    .map(|ms| {
+                        ms.parse()
+                            .map_err(|_| anyhow!("srt:// latency must be milliseconds, got {}", ms))
+                    })
+                    .transpose()?;
+                // libsrt refuses anything else when the socket is opened.
+                let passphrase = options.take("passphrase");
+                if let Some(passphrase) = &passphrase {
+                    if !(10..=79).contains(&passphrase.len()) {
+                        return Err(anyhow!("srt:// passphrase must be 10 to 79 characters"));
+                    }
+                }
+                let streamids: Vec<String> =
+                    std::iter::from_fn(|| options.take("streamid")).collect();
+                if mode == Some(SRTMode::Caller) && streamids.len() > 1 {
+                    return Err(anyhow!("srt:// caller presents one streamid"));
+                }
+                let output = Passthrough::Srt {
+                    address,
+                    mode,
+                    latency_ms,
+                    passphrase,
+                    streamids,
+                };
+                options.finish(output)
+            }
+            Some(("rist", rest)) => {
+                let (address, options) = Options::split(rest)?;
+                options.finish(Passthrough::Rist { address })
+            }
+            Some((scheme, _)) => Err(anyhow!("Unsupported output scheme: {}://", scheme)),
+        }
+    }
+
+    /// An SRT output to `address` with every option left at its default.
+    fn srt(address: &str) -> Self {
+        Passthrough::Srt {
+            address: address.to_string(),
+            mode: None,
+            latency_ms: None,
+            passphrase: None,
+            streamids: Vec::new(),
+        }
+    }
+
+    /// Whether the output is an SRT listener, which callers connect to.
+    pub(crate) fn listens(&self, args: &Args) -> bool {
+        match self {
+            Passthrough::Srt { mode, .. } => {
+                mode.clone().unwrap_or_else(|| args.srt_mode.reverse()) == SRTMode::Listener
+            }
+            Passthrough::Rist { .. } => false,
+        }
+    }
+
+    /// The output's URI as `parse` takes it, with the passphrase redacted.
+    pub fn redacted_uri(&self) -> String {
+        match self {
+            Passthrough::Srt {
+                address,
+                mode,
+                latency_ms,
+                passphrase,
+                streamids,
+            } => {
+                let options: Vec<String> = [
+                    mode.as_ref().map(|mode| format!("mode={}", mode.to_str())),
+                    latency_ms.map(|ms| format!("latency={}", ms)),
+                    passphrase
+                        .as_ref()
+                        .map(|_| "passphrase=<redacted>".to_string()),
+                ]
+                .into_iter()
+                .flatten()
+                .chain(streamids.iter().map(|id| format!("streamid={}", id)))
+                .collect();
+                if options.is_empty() {
+                    format!("srt://{}", address)
+                } else {
+                    format!("srt://{}?{}", address, options.join("&"))
+                }
+            }
+            Passthrough::Rist { address } => format!("rist://{}", address),
+        }
+    }
+
+    /// The element factories this output creates, with what each is for.
+    pub fn required_elements(&self) -> Vec<RequiredElement> {
+        let mut elements = vec![element("errorignore", "pass-through output containment")];
+        match self {
+            Passthrough::Srt { .. } => elements.push(element("srtsink", "SRT pass-through output")),
+            Passthrough::Rist { .. } => elements.extend([
+                element("rtpmp2tpay", "RIST pass-through output"),
+                element("ristsink", "RIST pass-through output"),
+            ]),
+        }
+        elements
+    }
+
+    /// Build the output, named `name`; its single sink pad takes the TS from
+    /// `input_tee`.
+    pub(crate) fn build(&self, name: &str, args: &Args) -> Result<gst::Element, Error> {
+        match self {
+            Passthrough::Srt {
+                address,
+                mode,
+                latency_ms,
+                passphrase,
+                streamids,
+            } => {
+                let mode = mode.clone().unwrap_or_else(|| args.srt_mode.reverse());
+                let output_uri = format!("srt://{}?mode={}", address, mode.to_str());
+                tracing::info!("SRT Output uri: {}", output_uri);
+                let mut sink = gst::ElementFactory::make("srtsink")
+                    .name(name)
+                    .property("uri", output_uri)
+                    .property("wait-for-connection", false);
+                if let Some(latency_ms) = latency_ms {
+                    sink = sink.property("latency", *latency_ms as i32);
+                }
+                if let Some(passphrase) = passphrase {
+                    sink = sink.property("passphrase", passphrase);
+                }
+                // A listener checks its callers' streamids instead (`attach`).
+                if mode == SRTMode::Caller {
+                    match streamids.as_slice() {
+                        [] => (),
+                        [streamid] => sink = sink.property("streamid", streamid),
+                        _ => return Err(anyhow!("An SRT caller presents one streamid")),
+                    }
+                }
+                Ok(sink.build()?)
+            }
+            Passthrough::Rist { address } => {
+                tracing::info!("RIST Output: rist://{}", address);
+                let (host, port) = host_port(address)?;
+                let pay = gst::ElementFactory::make("rtpmp2tpay").build()?;
+                let sink = gst::ElementFactory::make("ristsink")
+                    .property("address", host)
+                    .property("port", port)
+                    .build()?;
+
+                let bin = gst::Bin::builder().name(name).build();
+                bin.add_many([&pay, &sink])?;
+                pay.link(&sink)?;
+                let pad = pay
+                    .static_pad("sink")
+                    .ok_or_else(|| anyhow!("rtpmp2tpay has no sink pad"))?;
+                bin.add_pad(&gst::GhostPad::with_target(&pad)?)?;
+                Ok(bin.upcast())
+            }
+        }
+    }
+}
+
+/// The passthrough outputs by id: those from `--output-address`, numbered
+/// from 0 in order, and those added since. Lives as long as the pipeline
+/// wrapper, so every pipeline run attaches the same outputs.
+#[derive(Debug, Default)]
+pub(crate) struct Outputs {
+    outputs: BTreeMap<usize, Passthrough>,
+    /// Outputs whose sink errored during this pipeline run, with the error;
+    /// they are out of the pipeline until the next run.
+    failed: HashMap<usize, String>,
+    /// The callers of each output attached as an SRT listener. Kept with
+    /// the output, so its bans outlast a pipeline run.
+    callers: HashMap<usize, Arc<Mutex<Callers>>>,
+    next_id: usize,
+}
+
+impl Outputs {
+    pub(crate) fn new(outputs: Vec<Passthrough>) -> Self {
+        let mut table = Self::default();
+        for output in outputs {
+            table.insert(output);
+        }
+        table
+    }
+
+    /// Add `output`; returns its id, never reused.
+    pub(crate) fn insert(&mut self, output: Passthrough) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.outputs.insert(id, output);
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: usize) -> Option<Passthrough> {
+        self.failed.remove(&id);
+        self.callers.remove(&id);
+        self.outputs.remove(&id)
+    }
+
+    pub(crate) fn contains(&self, id: usize) -> bool {
+        self.outputs.contains_key(&id)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, &Passthrough)> {
+        self.outputs.iter().map(|(&id, output)| (id, output))
+    }
+
+    /// Record that output `id` failed with `error` in this pipeline run.
+    pub(crate) fn fail(&mut self, id: usize, error: String) {
+        if self.outputs.contains_key(&id) {
+            self.failed.insert(id, error);
+        }
+    }
+
+    /// Forget the failures and the callers of a pipeline run that is over.
+    pub(crate) fn end_run(&mut self) {
+        self.failed.clear();
+        for callers in self.callers.values() {
+            callers.lock().unwrap().clear();
+        }
+    }
+
+    /// Refuse caller `caller` of listener output `id` from now on, and
+    /// return its socket to close; `None` if it has no such caller.
+    pub(crate) fn disconnect_caller(&mut self, id: usize, caller: usize) -> Option<SrtSocket> {
+        self.callers
+            .get(&id)
+            .and_then(|callers| callers.lock().unwrap().disconnect(caller))
+    }
+
+    /// Describe output `id` as it stands in `pipeline` (`None`: stopped).
+    pub(crate) fn info(&self, id: usize, pipeline: Option<&gst::Pipeline>) -> Option<OutputInfo> {
+        let output = self.outputs.get(&id)?;
+        let error = self.failed.get(&id).cloned();
+        let state = if error.is_some() {
+            OutputState::Failed
+        } else if pipeline
+            .is_some_and(|pipeline| pipeline.by_name(&naming::output_name(id)).is_some())
+        {
+            OutputState::Attached
+        } else {
+            OutputState::Detached
+        };
+        let callers = self.callers.get(&id).map(|callers| match state {
+            OutputState::Attached => callers.lock().unwrap().list(),
+            OutputState::Failed | OutputState::Detached => Vec::new(),
+        });
+        Some(OutputInfo {
+            id,
+            uri: output.redacted_uri(),
+            state,
+            error,
+            callers,
+        })
+    }
+
+    /// Describe every output, in id order.
+    pub(crate) fn infos(&self, pipeline: Option<&gst::Pipeline>) -> Vec<OutputInfo> {
+        self.outputs
+            .keys()
+            .filter_map(|&id| self.info(id, pipeline))
+            .collect()
+    }
+
+    /// Hang output `id` off `input_tee` behind a leaky queue and an
+    /// `errorignore` of its own, and bring them up to the pipeline's state.
+    /// An SRT listener gets its callers watched. On error, whatever was
+    /// added is left for [`detach`].
+    pub(crate) fn attach(
+        &mut self,
+        pipeline: &gst::Pipeline,
+        id: usize,
+        args: &Args,
+    ) -> Result<(), Error> {
+        let output = self
+            .outputs
+            .get(&id)
+            .ok_or_else(|| anyhow!("No output {}", id))?;
+        let tee = pipeline
+            .by_name(naming::INPUT_TEE)
+            .ok_or_else(|| anyhow!("Failed to find element: {}", naming::INPUT_TEE))?;
+        let queue = gst::ElementFactory::make("queue")
+            .name(naming::output_queue_name(id))
+            .property("max-size-buffers", 0u32)
+            .property("max-size-time", 0u64)
+            .property_from_str("leaky", "downstream")
+            .build()?;
+        let guard = gst::ElementFactory::make("errorignore")
+            .name(naming::output_guard_name(id))
+            .build()?;
+        let sink = output.build(&naming::output_name(id), args)?;
+        if let Passthrough::Srt { streamids, .. } = output {
+            if output.listens(args) {
+                let callers = self.callers.entry(id).or_default();
+                {
+                    let mut callers = callers.lock().unwrap();
+                    callers.clear();
+                    callers.set_admission(Admission {
+                        allow: Vec::new(),
+                        streamids: streamids.clone(),
+                    });
+                }
+                srt_callers::watch(&sink, callers.clone(), format!("Output {}", id), |_| ())?;
+            }
+        }
+
+        pipeline.add_many([&queue, &guard, &sink])?;
+        gst::Element::link_many([&tee, &queue, &guard, &sink])?;
+        for element in [&sink, &guard, &queue] {
+            element.sync_state_with_parent()?;
+        }
+        Ok(())
+    }
+}
+
+/// Take output `id` out of the pipeline: release its `input_tee` pad and
+/// remove its elements. Tolerates a partial attach, or none.
+///
+/// Awaits GStreamer state changes; the caller must NOT hold the pipeline
+/// state lock.
+pub(crate) async fn detach(pipeline: &gst::Pipeline, id: usize) -> Result<(), Error> {
+    Branch::remove_branch_from_pipeline(pipeline, &naming::output_queue_name(id)).await?;
+    for name in [naming::output_guard_name(id), naming::output_name(id)] {
+        if let Some(element) = pipeline.by_name(&name) {
+            Branch::remove_element_from_pipeline(pipeline, &element).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Feed the output tee `tee_name` into `mux` through a queue of its own, so
+/// the passthrough cannot hold up the tee's WHEP viewers.
+pub(crate) fn tap_into_mux(
+    pipeline: &gst::Pipeline,
+    tee_name: &str,
+    mux: &gst::Element,
+) -> Result<(), Error> {
+    let tee = pipeline
+        .by_name(tee_name)
+        .ok_or_else(|| anyhow!("Failed to find element: {}", tee_name))?;
+    let queue = gst::ElementFactory::make("queue")
+        .property_from_str("leaky", "downstream")
+        .build()?;
+    pipeline.add(&queue)?;
+    gst::Element::link_many([&tee, &queue, mux])?;
+    queue.sync_state_with_parent()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outputs, Passthrough};
+    use crate::stream::pipeline::{OutputState, SRTMode};
+
+    #[test]
+    fn the_output_address_scheme_selects_srt_or_rist() {
+        let srt = Passthrough::srt("127.0.0.1:8888");
+        assert_eq!(srt, Passthrough::parse("127.0.0.1:8888").unwrap());
+        assert_eq!(srt, Passthrough::parse("srt://127.0.0.1:8888").unwrap());
+        assert_eq!(
+            Passthrough::Rist {
+                address: "10.0.0.9:5004".to_string(),
+            },
+            Passthrough::parse("rist://10.0.0.9:5004").unwrap()
+        );
+        assert!(Passthrough::parse("udp://10.0.0.9:5004").is_err());
+    }
+
+    #[test]
+    fn srt_output_options_are_parsed_checked_and_redacted() {
+        let output = Passthrough::parse(
+            "srt://0.0.0.0:9000?mode=listener&latency=200&passphrase=0123456789&streamid=feed",
+        )
+        .unwrap();
+        assert_eq!(
+            Passthrough::Srt {
+                address: "0.0.0.0:9000".to_string(),
+                mode: Some(SRTMode::Listener),
+                latency_ms: Some(200),
+                passphrase: Some("0123456789".to_string()),
+                streamids: vec!["feed".to_string()],
+            },
+            output
+        );
+        assert_eq!(
+            "srt://0.0.0.0:9000?mode=listener&latency=200&passphrase=<redacted>&streamid=feed",
+            output.redacted_uri()
+        );
+        assert!(Passthrough::parse("srt://0.0.0.0:9000?mode=rendezvous").is_err());
+        assert!(Passthrough::parse("srt://0.0.0.0:9000?latency=low").is_err());
+        assert!(Passthrough::parse("srt://0.0.0.0:9000?passphrase=short").is_err());
+        assert!(Passthrough::parse("srt://0.0.0.0:9000?pbkeylen=16").is_err());
+        assert!(Passthrough::parse("rist://0.0.0.0:9000?mode=listener").is_err());
+    }
+
+    #[test]
+    fn a_listener_takes_many_streamids_and_a_caller_one() {
+        let output =
+            Passthrough::parse("srt://0.0.0.0:9000?mode=listener&streamid=dec-a&streamid=dec-b")
+                .unwrap();
+        assert_eq!(
+            "srt://0.0.0.0:9000?mode=listener&streamid=dec-a&streamid=dec-b",
+            output.redacted_uri()
+        );
+        assert!(
+            Passthrough::parse("srt://10.0.0.5:9000?mode=caller&streamid=a&streamid=b").is_err()
+        );
+    }
+
+    #[test]
+    fn output_ids_are_never_reused_and_failures_last_one_run() {
+        let mut outputs = Outputs::new(vec![
+            Passthrough::srt("127.0.0.1:8888"),
+            Passthrough::srt("127.0.0.1:8889"),
+        ]);
+        assert_eq!(Some(Passthrough::srt("127.0.0.1:8889")), outputs.remove(1));
+        assert_eq!(2, outputs.insert(Passthrough::srt("127.0.0.1:8890")));
+        assert_eq!(
+            vec![0, 2],
+            outputs.iter().map(|(id, _)| id).collect::<Vec<_>>()
+        );
+
+        outputs.fail(0, "Connection refused".to_string());
+        let info = outputs.info(0, None).unwrap();
+        assert_eq!(OutputState::Failed, info.state);
+        assert_eq!(Some("Connection refused".to_string()), info.error);
+        outputs.end_run();
+        assert_eq!(OutputState::Detached, outputs.info(0, None).unwrap().state);
+        assert!(outputs.info(1, None).is_none());
+    }
+}
@@ -1,15 +1,28 @@
-use crate::stream::errors::PipelineError;
+use crate::stream::errors::{NotReadyReason, PipelineError};
 use crate::stream::naming::BranchId;
+use crate::stream::passthrough::Passthrough;
+use crate::stream::preflight::PreflightMode;
+use crate::stream::srt_callers::{CallerInfo, IpNet};
+use crate::stream::stats::{CandidatePair, InputStats, ViewerSample, ViewerStats};
 use anyhow::Error;
 use async_trait::async_trait;
 use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
 #[derive(clap::Args, Debug, Clone)]
 pub struct Args {
-    /// SRT source stream address(ip:port)
-    #[clap(short, long)]
+    /// SRT source stream address(ip:port, or srt://ip:port). Other sources:
+    /// udp://ip:port[?iface=<nic>] - MPEG-TS over UDP, unicast or multicast;
+    /// rtp://ip:port[?iface=<nic>&latency=<ms>] - MPEG-TS in RTP;
+    /// file:///path/to.ts[?loop=true] - a recorded TS, played in real time;
+    /// rist://ip:port - RIST (Simple Profile), see the --rist-* flags;
+    /// whip:// - a WebRTC publisher, accepted at POST /whip;
+    /// whep+https://host/path - pulled from a remote WHEP server (or whep+http://);
+    /// test:// - bars, a clock and a tone generated in-process.
+    #[clap(short, long, verbatim_doc_comment)]
     pub input_address: String,
 
     /// SRT mode to use:
@@ -24,6 +37,21 @@ pub struct Args {
     #[clap(long, default_value_t = 0)]
     pub srt_latency: u32,
 
+    /// In --srt-mode listener, accept SRT publishers only from this address
+    /// or CIDR network (repeatable); from anywhere if unset.
+    #[clap(long)]
+    pub srt_allow: Vec<IpNet>,
+
+    /// In --srt-mode listener, accept only SRT publishers presenting this
+    /// streamid (repeatable); any if unset.
+    #[clap(long)]
+    pub srt_streamid: Vec<String>,
+
+    /// Passphrase (10 to 79 characters) the SRT input is encrypted with; in
+    /// --srt-mode listener, publishers without it are refused.
+    #[clap(long)]
+    pub srt_passphrase: Option<String>,
+
     /// TSDemux latency in milliseconds
     /// Latency to add for smooth demuxing MPEG2 transport streams
     #[clap(long, default_value_t = 0)]
@@ -37,9 +65,14 @@ pub struct Args {
     #[clap(short, long, default_value_t = 10)]
     pub discoverer_timeout_sec: u64,
 
-    /// SRT output stream address(ip:port)
-    #[clap(short, long, default_value_t = String::from("127.0.0.1:8888"))]
-    pub output_address: String,
+    /// Passthrough output address, repeatable; none by default:
+    /// srt://ip:port[?mode=<caller|listener>&latency=<ms>&passphrase=<secret>&streamid=<id>]
+    /// (or ip:port) - SRT, by default in the mode opposite to --srt-mode; a listener
+    /// serves any number of callers, and with streamid given (repeatable) only those presenting one;
+    /// rist://ip:port - RIST (Simple Profile).
+    /// More can be added and removed at runtime at /admin/outputs.
+    #[clap(short, long, verbatim_doc_comment)]
+    pub output_address: Vec<String>,
 
     /// Port for whep client
     #[clap(short, long, default_value_t = 8000)]
@@ -51,9 +84,36 @@ pub struct Args {
     /// When enabled, avdec_h264 decodes to raw video and webrtcsink re-encodes.
     #[clap(short = 'D', long, default_value_t = false)]
     pub decode_video: bool,
+
+    /// RIST input receiver buffer in milliseconds: how long lost packets
+    /// can be waited for (retransmitted) before they are given up on.
+    #[clap(long, default_value_t = 1000)]
+    pub rist_receiver_buffer_ms: u32,
+
+    /// RIST input reorder section in milliseconds, within the receiver buffer.
+    #[clap(long, default_value_t = 70)]
+    pub rist_reorder_section_ms: u32,
+
+    /// RIST input retransmission requests per lost packet.
+    #[clap(long, default_value_t = 7)]
+    pub rist_max_rtx_retries: u32,
+
+    /// Loopback port of the WHIP server behind POST /whip (whip:// input).
+    #[clap(long, default_value_t = 8190)]
+    pub whip_server_port: u16,
+
+    /// Bearer token for the remote WHEP server of a whep+http(s):// input.
+    #[clap(long)]
+    pub whep_input_token: Option<String>,
+
+    /// What to do when the startup check finds GStreamer elements missing:
+    /// strict - refuse to start; degraded - start, reporting them as why
+    /// no viewer can be served; skip - do not check.
+    #[clap(long, value_enum, verbatim_doc_comment, default_value_t = PreflightMode::Strict)]
+    pub preflight: PreflightMode,
 }
 
-#[derive(ValueEnum, Clone, Debug, PartialEq)]
+#[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
 pub enum SRTMode {
     Caller,
     Listener,
@@ -78,17 +138,102 @@ impl SRTMode {
 /// The coordinator's view of the pipeline: per-viewer branch control.
 ///
 /// `ready` gates branch creation: `add_branch` may only succeed once the
-/// input stream is demuxed and the output tees exist. `add_branch` /
+/// input stream is demuxed and the output tees exist. Until then both fail
+/// with [`PipelineError::NotReady`], naming the missing stage. `add_branch` /
 /// `remove_branch` attach and detach one viewer's WHEP output branch.
+/// `sample_viewers` reads the WebRTC statistics of every listed branch in
+/// one pass, leaving out those whose sink is gone. Each sample's
+/// receiver-report marker lets the coordinator tell a live viewer from a
+/// silent one: it changes whenever the branch's peer sends a new RTCP
+/// receiver report, and is `None` until the first one arrives. Its
+/// candidate pair is the one the branch's session settled on, for
+/// operators inspecting a connection.
+/// `input_stats` reports the ingest protocol's own statistics. `outputs`,
+/// `add_output` and `remove_output` manage the passthrough outputs, which
+/// outlive pipeline restarts; `remove_output` answers `false` for an
+/// unknown id.
 ///
 /// Errors are typed for policy: [`PipelineError::NotReady`] and
 /// [`PipelineError::Transient`] are worth a retry, [`PipelineError::Fatal`]
 /// is not.
 #[async_trait]
 pub trait BranchControl: Clone + Send + Sync {
-    async fn ready(&self) -> Result<bool, PipelineError>;
+    async fn ready(&self) -> Result<(), PipelineError>;
     async fn add_branch(&self, id: String) -> Result<(), PipelineError>;
     async fn remove_branch(&self, id: String) -> Result<(), PipelineError>;
+    async fn sample_viewers(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, ViewerSample>, PipelineError>;
+    async fn input_stats(&self) -> Result<InputStats, PipelineError>;
+    async fn outputs(&self) -> Result<Vec<OutputInfo>, PipelineError>;
+    async fn add_output(&self, output: Passthrough) -> Result<OutputInfo, PipelineError>;
+    async fn remove_output(&self, id: usize) -> Result<bool, PipelineError>;
+    /// Drop caller `caller` of SRT listener output `output` and refuse it
+    /// from then on; false if there is no such caller.
+    async fn disconnect_caller(&self, output: usize, caller: usize) -> Result<bool, PipelineError>;
+}
+
+/// One passthrough output, as the admin API lists it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OutputInfo {
+    pub id: usize,
+    /// Its `--output-address`, passphrase redacted.
+    pub uri: String,
+    pub state: OutputState,
+    /// Why it failed, while `state` is `failed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The callers connected to an SRT listener output; absent for others.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callers: Option<Vec<CallerInfo>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputState {
+    /// In the pipeline, fed from `input_tee`.
+    Attached,
+    /// Its sink errored and was taken out; it is attached again with the
+    /// next pipeline run.
+    Failed,
+    /// Not in the pipeline: it is stopped, and attaches the output when it
+    /// runs again.
+    Detached,
+}
+
+/// Where a push output sends the stream: a remote WHIP endpoint (an SFU or
+/// CDN ingest), and the bearer token it expects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PushTarget {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+/// What the pipeline knows of one push output's branch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushHealth {
+    /// Attached, and no error from it so far.
+    Attached,
+    /// Attached, but it errored on the bus: remove it before attaching again.
+    Failed,
+    /// Not in the pipeline: never attached, removed, or lost with a
+    /// pipeline restart.
+    Absent,
+}
+
+/// The push outputs' view of the pipeline: permanent branches that POST the
+/// stream to a remote WHIP endpoint, keyed by the push output's index.
+///
+/// `add_push` attaches one like a viewer's branch and fails the same ways
+/// (`NotReady` until the input is linked). `push_health` is how the push
+/// output learns its branch failed or vanished with a restart; errors from
+/// the branch itself never stop the pipeline (see `bus::classify_bus_message`).
+#[async_trait]
+pub trait PushControl: Clone + Send + Sync {
+    async fn add_push(&self, index: usize, target: PushTarget) -> Result<(), PipelineError>;
+    async fn remove_push(&self, index: usize) -> Result<(), PipelineError>;
+    async fn push_health(&self, index: usize) -> Result<PushHealth, PipelineError>;
 }
 
 /// The supervisor's view of the pipeline: whole-pipeline lifecycle.
@@ -110,6 +255,8 @@ pub trait PipelineLifecycle: Clone + Send + Sync {
 #[derive(Clone, Debug, Default)]
 pub struct TestPipelineState {
     pub ready: bool,
+    /// What a not-ready fake reports; `NoInput` when unset.
+    pub not_ready_reason: Option<NotReadyReason>,
     pub added: Vec<String>,
     pub removed: Vec<String>,
     pub quit_count: u32,
@@ -117,9 +264,28 @@ pub struct TestPipelineState {
     pub run_count: u32,
     pub end_count: u32,
     pub cleanup_count: u32,
+    /// Per-branch receiver-report marker, bumped by `report_receiver`.
+    pub receiver_reports: HashMap<String, u64>,
+    /// Per-branch selected candidate pair, set by `select_candidate_pair`.
+    pub candidate_pairs: HashMap<String, CandidatePair>,
+    /// Per-branch WebRTC statistics, set by `set_viewer_stats`.
+    pub viewer_stats: HashMap<String, ViewerStats>,
+    /// The input's statistics, set by `set_input_stats`; unset, the fake
+    /// answers like a stopped pipeline.
+    pub input_stats: Option<InputStats>,
+    /// Push branches by index, as `add_push` / `fail_push` / `clean_up`
+    /// leave them; absent ones are not in the map.
+    pub pushes: HashMap<usize, PushHealth>,
+    /// Every `add_push` that succeeded, in order.
+    pub pushes_added: Vec<usize>,
+    /// Passthrough outputs, as `add_output` / `remove_output` leave them.
+    pub outputs: Vec<OutputInfo>,
+    next_output: usize,
     next_run_error: Option<String>,
     block_remove_branch: bool,
     block_add_branch: bool,
+    block_outputs: bool,
+    block_stats: bool,
 }
 
 /// A recording fake for unit and integration tests: `ready` is settable,
@@ -171,6 +337,25 @@ impl TestPipeline {
         self.state.lock().unwrap().ready = ready;
     }
 
+    /// Make the fake not ready for `reason`.
+    pub fn set_not_ready(&self, reason: NotReadyReason) {
+        let mut state = self.state.lock().unwrap();
+        state.ready = false;
+        state.not_ready_reason = Some(reason);
+    }
+
+    fn check_ready(&self) -> Result<(), PipelineError> {
+        let state = self.state.lock().unwrap();
+        if state.ready {
+            return Ok(());
+        }
+        let reason = state
+            .not_ready_reason
+            .clone()
+            .unwrap_or(NotReadyReason::NoInput);
+        Err(PipelineError::NotReady(reason))
+    }
+
     pub fn snapshot(&self) -> TestPipelineState {
         self.state.lock().unwrap().clone()
     }
@@ -198,6 +383,27 @@ impl TestPipeline {
         self.state.lock().unwrap().block_add_branch = true;
     }
 
+    /// Make every `add_output`, `remove_output` and `disconnect_caller` call
+    /// hang forever, simulating a wedged link or unlink in the running
+    /// pipeline.
+    pub fn block_outputs(&self) {
+        self.state.lock().unwrap().block_outputs = true;
+    }
+
+    /// Make every `sample_viewers` and `input_stats` call hang forever,
+    /// simulating a stats read stuck behind the pipeline state lock.
+    pub fn block_stats(&self) {
+        self.state.lock().unwrap().block_stats = true;
+    }
+
+    /// Never resolves once `block_stats` was called: a read that never
+    /// gets the lock, which only the coordinator's teardown timeout ends.
+    async fn wedge_stats(&self) {
+        if self.state.lock().unwrap().block_stats {
+            std::future::pending::<()>().await;
+        }
+    }
+
     /// Simulate the bus watch reporting a per-viewer branch's runtime
     /// failure (its whipsink errored / its peer went away), exactly as the
     /// real pipeline does, so the coordinator reaps that connection.
@@ -205,6 +411,68 @@ impl TestPipeline {
         let _ = self.branch_failures.try_send(BranchId::new(id));
     }
 
+    /// Simulate the viewer on branch `id` sending an RTCP receiver report:
+    /// its marker changes, exactly as the real whipclientsink's stats do.
+    pub fn report_receiver(&self, id: &str) {
+        *self
+            .state
+            .lock()
+            .unwrap()
+            .receiver_reports
+            .entry(id.to_string())
+            .or_default() += 1;
+    }
+
+    /// Simulate ICE on branch `id` settling on `pair`.
+    pub fn select_candidate_pair(&self, id: &str, pair: CandidatePair) {
+        self.state
+            .lock()
+            .unwrap()
+            .candidate_pairs
+            .insert(id.to_string(), pair);
+    }
+
+    /// Simulate branch `id`'s sink reporting `stats`.
+    pub fn set_viewer_stats(&self, id: &str, stats: ViewerStats) {
+        self.state
+            .lock()
+            .unwrap()
+            .viewer_stats
+            .insert(id.to_string(), stats);
+    }
+
+    /// Simulate the ingest element reporting `stats`.
+    pub fn set_input_stats(&self, stats: InputStats) {
+        self.state.lock().unwrap().input_stats = Some(stats);
+    }
+
+    /// Simulate push output `index`'s branch erroring on the bus.
+    pub fn fail_push(&self, index: usize) {
+        if let Some(health) = self.state.lock().unwrap().pushes.get_mut(&index) {
+            *health = PushHealth::Failed;
+        }
+    }
+
+    /// Simulate `caller` connecting to listener output `output`.
+    pub fn connect_caller(&self, output: usize, caller: CallerInfo) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(callers) = state
+            .outputs
+            .iter_mut()
+            .find(|info| info.id == output)
+            .and_then(|info| info.callers.as_mut())
+        {
+            callers.push(caller);
+        }
+    }
+
+    /// Never resolves once `block_outputs` was called.
+    async fn wedge_outputs(&self) {
+        if self.state.lock().unwrap().block_outputs {
+            std::future::pending::<()>().await;
+        }
+    }
+
     /// Release a parked `run()` as a clean EOS.
     pub fn finish_run(&self) {
         self.run_gate.notify_one();
@@ -219,8 +487,8 @@ impl TestPipeline {
 
 #[async_trait]
 impl BranchControl for TestPipeline {
-    async fn ready(&self) -> Result<bool, PipelineError> {
-        Ok(self.state.lock().unwrap().ready)
+    async fn ready(&self) -> Result<(), PipelineError> {
+        self.check_ready()
     }
 
     async fn add_branch(&self, id: String) -> Result<(), PipelineError> {
@@ -228,9 +496,7 @@ impl BranchControl for TestPipeline {
             return Err(err);
         }
         // Mirror the real adapter: a branch cannot be added to a not-ready input.
-        if !self.state.lock().unwrap().ready {
-            return Err(PipelineError::NotReady);
-        }
+        self.check_ready()?;
         if self.state.lock().unwrap().block_add_branch {
             // A wedged cleanup detach that never resolves; the coordinator's
             // teardown timeout is what unblocks the actor.
@@ -252,6 +518,118 @@ impl BranchControl for TestPipeline {
         self.state.lock().unwrap().removed.push(id);
         Ok(())
     }
+
+    async fn sample_viewers(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, ViewerSample>, PipelineError> {
+        self.wedge_stats().await;
+        let state = self.state.lock().unwrap();
+        Ok(ids
+            .into_iter()
+            .map(|id| {
+                let sample = ViewerSample {
+                    stats: state.viewer_stats.get(&id).cloned().unwrap_or_default(),
+                    receiver_report_marker: state.receiver_reports.get(&id).copied(),
+                    candidate_pair: state.candidate_pairs.get(&id).cloned(),
+                };
+                (id, sample)
+            })
+            .collect())
+    }
+
+    async fn input_stats(&self) -> Result<InputStats, PipelineError> {
+        self.wedge_stats().await;
+        self.state
+            .lock()
+            .unwrap()
+            .input_stats
+            .clone()
+            .ok_or(PipelineError::NotReady(NotReadyReason::PipelineStopped))
+    }
+
+    async fn outputs(&self) -> Result<Vec<OutputInfo>, PipelineError> {
+        Ok(self.state.lock().unwrap().outputs.clone())
+    }
+
+    async fn add_output(&self, output: Passthrough) -> Result<OutputInfo, PipelineError> {
+        self.wedge_outputs().await;
+        let mut state = self.state.lock().unwrap();
+        let info = OutputInfo {
+            id: state.next_output,
+            uri: output.redacted_uri(),
+            state: if state.ready {
+                OutputState::Attached
+            } else {
+                OutputState::Detached
+            },
+            error: None,
+            // The fake has no `--srt-mode` to go by: only an explicit
+            // listener is one.
+            callers: matches!(
+                output,
+                Passthrough::Srt {
+                    mode: Some(SRTMode::Listener),
+                    ..
+                }
+            )
+            .then(Vec::new),
+        };
+        state.next_output += 1;
+        state.outputs.push(info.clone());
+        Ok(info)
+    }
+
+    async fn remove_output(&self, id: usize) -> Result<bool, PipelineError> {
+        self.wedge_outputs().await;
+        let mut state = self.state.lock().unwrap();
+        let before = state.outputs.len();
+        state.outputs.retain(|output| output.id != id);
+        Ok(state.outputs.len() < before)
+    }
+
+    async fn disconnect_caller(&self, output: usize, caller: usize) -> Result<bool, PipelineError> {
+        self.wedge_outputs().await;
+        let mut state = self.state.lock().unwrap();
+        let Some(callers) = state
+            .outputs
+            .iter_mut()
+            .find(|info| info.id == output)
+            .and_then(|info| info.callers.as_mut())
+        else {
+            return Ok(false);
+        };
+        let before = callers.len();
+        callers.retain(|info| info.id != caller);
+        Ok(callers.len() < before)
+    }
+}
+
+#[async_trait]
+impl PushControl for TestPipeline {
+    async fn add_push(&self, index: usize, _target: PushTarget) -> Result<(), PipelineError> {
+        self.check_ready()?;
+        let mut state = self.state.lock().unwrap();
+        state.pushes.insert(index, PushHealth::Attached);
+        state.pushes_added.push(index);
+        Ok(())
+    }
+
+    async fn remove_push(&self, index: usize) -> Result<(), PipelineError> {
+        self.state.lock().unwrap().pushes.remove(&index);
+        Ok(())
+    }
+
+    async fn push_health(&self, index: usize) -> Result<PushHealth, PipelineError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .pushes
+            .get(&index)
+            .copied()
+            .unwrap_or(PushHealth::Absent))
+    }
 }
 
 #[async_trait]
@@ -277,7 +655,10 @@ impl PipelineLifecycle for TestPipeline {
     }
 
     async fn clean_up(&self) -> Result<(), Error> {
-        self.state.lock().unwrap().cleanup_count += 1;
+        let mut state = self.state.lock().unwrap();
+        state.cleanup_count += 1;
+        // Like the real pipeline: push branches go with it.
+        state.pushes.clear();
         Ok(())
     }
 
@@ -291,14 +672,14 @@ impl PipelineLifecycle for TestPipeline {
 #[cfg(test)]
 mod tests {
     use super::{BranchControl, PipelineLifecycle, TestPipeline};
-    use crate::stream::errors::PipelineError;
+    use crate::stream::errors::{NotReadyReason, PipelineError};
 
     #[tokio::test]
     async fn add_branch_on_a_not_ready_fake_is_not_ready() {
         let pipeline = TestPipeline::default(); // ready = false
         assert!(matches!(
             pipeline.add_branch("a".to_string()).await,
-            Err(PipelineError::NotReady)
+            Err(PipelineError::NotReady(NotReadyReason::NoInput))
         ));
         assert!(pipeline.snapshot().added.is_empty());
     }
@@ -321,10 +702,10 @@ mod tests {
     #[tokio::test]
     async fn test_pipeline_records_calls() {
         let pipeline = TestPipeline::default();
-        assert!(!pipeline.ready().await.unwrap());
+        assert!(pipeline.ready().await.is_err());
 
         pipeline.set_ready(true);
-        assert!(pipeline.ready().await.unwrap());
+        pipeline.ready().await.unwrap();
 
         pipeline.add_branch("a".to_string()).await.unwrap();
         pipeline.remove_branch("a".to_string()).await.unwrap();
@@ -0,0 +1,213 @@
+//! Startup preflight: is every GStreamer element the pipeline builds
+//! available, and from which plugin?
+//!
+//! Without it a missing or shadowed element only shows up when it is first
+//! created -- `whipclientsink` at the first viewer's `Branch::attach`, long
+//! after the SRT input came up fine (known limitation 7). The preflight
+//! looks every factory up once at startup (and in `srt-whep check`) and
+//! reports the plugin, version and file each one would load from.
+
+use clap::ValueEnum;
+use gst::prelude::*;
+use gstreamer as gst;
+use std::fmt;
+
+use crate::stream::ingest::Ingest;
+use crate::stream::passthrough::Passthrough;
+
+/// What to do at startup when the preflight finds elements missing.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreflightMode {
+    /// Refuse to start.
+    Strict,
+    /// Start anyway; `/readyz` and `POST /channel` report the missing
+    /// elements as the reason no viewer can be served.
+    Degraded,
+    /// Do not run the preflight.
+    Skip,
+}
+
+/// One element factory the pipeline creates, and what it is for.
+#[derive(Debug, Clone, Copy)]
+pub struct RequiredElement {
+    pub factory: &'static str,
+    pub purpose: &'static str,
+}
+
+pub(crate) const fn element(factory: &'static str, purpose: &'static str) -> RequiredElement {
+    RequiredElement { factory, purpose }
+}
+
+/// Every element factory the pipeline and its viewer branches create, for
+/// input from `ingest` and passthrough to `outputs` (those configured at
+/// startup; one added later brings its own). `decode_video` adds the H.264
+/// decoder of `--decode-video`.
+pub fn required_elements(
+    ingest: &Ingest,
+    outputs: &[Passthrough],
+    decode_video: bool,
+) -> Vec<RequiredElement> {
+    let mut elements = ingest.required_elements();
+    for output in outputs {
+        for element in output.required_elements() {
+            if !elements.iter().any(|e| e.factory == element.factory) {
+                elements.push(element);
+            }
+        }
+    }
+    elements.extend([
+        element("typefind", "input type detection"),
+        element("tsdemux", "MPEG-TS demuxing"),
+        element("tee", "input and output fan-out"),
+        element("queue", "ingest and branch queues"),
+        element("fakesink", "output keep-alive"),
+        element("h264parse", "H.264 video"),
+        element("h265parse", "H.265 video"),
+        element("aacparse", "AAC audio"),
+        element("avdec_aac", "AAC decode for the Opus transcode"),
+        element("audioconvert", "Opus transcode"),
+        element("audioresample", "Opus transcode"),
+        element("opusenc", "Opus transcode"),
+        element("whipclientsink", "WHEP viewer branches (rswebrtc)"),
+    ]);
+    if decode_video {
+        elements.push(element("avdec_h264", "H.264 decode (--decode-video)"));
+    }
+    elements
+}
+
+/// Where an available element comes from.
+#[derive(Debug, Clone)]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: String,
+    /// The plugin's shared library; `None` for a statically linked plugin.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElementStatus {
+    pub element: RequiredElement,
+    /// `None` if the registry has no such factory.
+    pub plugin: Option<PluginInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PreflightReport {
+    pub gstreamer_version: String,
+    pub elements: Vec<ElementStatus>,
+}
+
+impl PreflightReport {
+    /// The factories the registry does not have.
+    pub fn missing(&self) -> Vec<String> {
+        self.elements
+            .iter()
+            .filter(|status| status.plugin.is_none())
+            .map(|status| status.element.factory.to_string())
+            .collect()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.elements.iter().all(|status| status.plugin.is_some())
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.gstreamer_version)?;
+        for status in &self.elements {
+            let RequiredElement { factory, purpose } = status.element;
+            match &status.plugin {
+                Some(plugin) => writeln!(
+                    f,
+                    "  ok       {:<16} {} {} ({})",
+                    factory,
+                    plugin.name,
+                    plugin.version,
+                    plugin.path.as_deref().unwrap_or("static")
+                )?,
+                None => writeln!(f, "  MISSING  {:<16} needed for {}", factory, purpose)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Initialize GStreamer and look up every element the pipeline needs.
+pub fn preflight(
+    ingest: &Ingest,
+    outputs: &[Passthrough],
+    decode_video: bool,
+) -> Result<PreflightReport, glib::Error> {
+    gst::init()?;
+    Ok(check(&required_elements(ingest, outputs, decode_video)))
+}
+
+fn check(elements: &[RequiredElement]) -> PreflightReport {
+    let elements = elements
+        .iter()
+        .map(|&element| ElementStatus {
+            element,
+            plugin: gst::ElementFactory::find(element.factory)
+                .and_then(|factory| factory.plugin())
+                .map(|plugin| PluginInfo {
+                    name: plugin.plugin_name().to_string(),
+                    version: plugin.version().to_string(),
+                    path: plugin.filename().map(|path| path.display().to_string()),
+                }),
+        })
+        .collect();
+    PreflightReport {
+        gstreamer_version: gst::version_string().to_string(),
+        elements,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, element, required_elements};
+    use crate::stream::ingest::Ingest;
+    use crate::stream::passthrough::Passthrough;
+    use gstreamer as gst;
+
+    #[test]
+    fn required_elements_follow_the_ingest_and_decode_video() {
+        let srt = Ingest::Srt {
+            address: "127.0.0.1:1234".to_string(),
+        };
+        let outputs = [Passthrough::parse("127.0.0.1:8888").unwrap()];
+        let has = |ingest, decode, factory| {
+            required_elements(ingest, &outputs, decode)
+                .iter()
+                .any(|e| e.factory == factory)
+        };
+        assert!(!has(&srt, false, "avdec_h264"));
+        assert!(has(&srt, true, "avdec_h264"));
+        assert!(has(&srt, false, "whipclientsink"));
+        assert!(has(&srt, false, "srtsrc"));
+        assert!(!has(&Ingest::TestSource, false, "srtsrc"));
+        assert!(has(&Ingest::TestSource, false, "x264enc"));
+        assert!(has(&srt, false, "srtsink"));
+        assert!(!required_elements(&srt, &[], false)
+            .iter()
+            .any(|e| e.factory == "srtsink"));
+    }
+
+    #[test]
+    fn the_report_names_missing_elements_and_where_present_ones_come_from() {
+        gst::init().unwrap();
+        let report = check(&[
+            element("queue", "core"),
+            element("no_such_element", "nothing"),
+        ]);
+
+        assert!(!report.is_ok());
+        assert_eq!(vec!["no_such_element".to_string()], report.missing());
+        let queue = report.elements[0].plugin.as_ref().unwrap();
+        assert_eq!("coreelements", queue.name);
+        let text = report.to_string();
+        assert!(text.contains("MISSING  no_such_element"));
+        assert!(text.contains("ok       queue"));
+    }
+}
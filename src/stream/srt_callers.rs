@@ -0,0 +1,547 @@
+//! The callers of an SRT element in listener mode: the viewers' decoders
+//! pulling a listener output from `srtsink`, or the publishers of an SRT
+//! input calling `srtsrc`. Both elements announce each caller with
+//! signals; [`watch`] connects to those, refuses at `caller-connecting`
+//! whoever [`Callers::admit`] does not let in, and keeps the rest in a
+//! [`Callers`] table for the API.
+//!
+//! The signals fire on libsrt's threads, hence the plain mutex.
+//!
+//! Neither element can drop one caller, but libsrt can: [`close`] shuts a
+//! caller's socket, and the element drops that caller alone once its next
+//! send or receive on it fails.
+
+use anyhow::{anyhow, Error};
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{c_int, c_void, CStr};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// The names libsrt goes by as the SRT plugin loads it: Debian and Ubuntu
+/// ship an OpenSSL and a GnuTLS build under different names.
+const LIBSRT: [&CStr; 5] = [
+    c"libsrt.so.1.5",
+    c"libsrt-openssl.so.1.5",
+    c"libsrt-gnutls.so.1.5",
+    c"libsrt.so.1",
+    c"libsrt.so",
+];
+
+/// A caller's SRT socket, as `caller-added` passes it.
+pub(crate) type SrtSocket = c_int;
+
+/// How many admitted callers may wait for `caller-added` at once. One that
+/// libsrt refuses after admission (a wrong passphrase) never gets there;
+/// past this many, the waiting ones are forgotten rather than piling up.
+const MAX_CONNECTING: usize = 256;
+
+/// How long a caller disconnected through the admin API stays refused.
+const BAN_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// An address, or a network in CIDR notation (`10.0.0.0/8`, `2001:db8::/32`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpNet {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // An IPv4 peer on a dual-stack socket shows up IPv4-mapped.
+        match (self.address, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = s.split_once('/').unwrap_or((s, ""));
+        let address: IpAddr = address
+            .parse()
+            .map_err(|_| format!("not an IP address: {}", address))?;
+        let bits = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = if prefix.is_empty() {
+            bits
+        } else {
+            prefix
+                .parse()
+                .ok()
+                .filter(|prefix| *prefix <= bits)
+                .ok_or_else(|| format!("not a prefix length for {}: {}", address, prefix))?
+        };
+        Ok(IpNet { address, prefix })
+    }
+}
+
+impl fmt::Display for IpNet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// Who a listener lets in; with nothing set, every caller.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Admission {
+    /// The hosts a caller must come from, if any are given.
+    pub allow: Vec<IpNet>,
+    /// The streamids a caller must present one of, if any are given.
+    pub streamids: Vec<String>,
+}
+
+/// Something that happened to a listener's callers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CallerEvent {
+    Connected(CallerInfo),
+    Disconnected(CallerInfo),
+    /// Refused at `caller-connecting`, for `reason`.
+    Refused {
+        address: String,
+        reason: &'static str,
+    },
+}
+
+/// A connected caller, as the admin API lists it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CallerInfo {
+    /// Unique to its listener, never reused.
+    pub id: usize,
+    /// Its `ip:port`.
+    pub address: String,
+    /// The SRT streamid it presented, if any. It is what admitted the
+    /// caller, so only the admin API shows it; see [`CallerInfo::redacted`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streamid: Option<String>,
+}
+
+impl CallerInfo {
+    /// The caller without its streamid, for the unauthenticated API.
+    pub(crate) fn redacted(self) -> Self {
+        CallerInfo {
+            streamid: None,
+            ..self
+        }
+    }
+}
+
+/// A caller between `caller-added` and `caller-removed`.
+#[derive(Debug)]
+struct Connected {
+    peer: SocketAddr,
+    streamid: Option<String>,
+    socket: SrtSocket,
+}
+
+impl Connected {
+    fn info(&self, id: usize) -> CallerInfo {
+        CallerInfo {
+            id,
+            address: self.peer.to_string(),
+            streamid: self.streamid.clone(),
+        }
+    }
+}
+
+/// One listener's callers: who may connect, and who is connected.
+#[derive(Debug, Default)]
+pub(crate) struct Callers {
+    admission: Admission,
+    /// Let in at `caller-connecting`, not yet added: their streamid by
+    /// address, until `caller-added` says which one it was.
+    connecting: HashMap<SocketAddr, Option<String>>,
+    connected: BTreeMap<usize, Connected>,
+    /// Disconnected through the admin API, by host and streamid: refused
+    /// until the given time. Expired bans are dropped at the next one.
+    banned: HashMap<(IpAddr, Option<String>), Instant>,
+    next_id: usize,
+}
+
+impl Callers {
+    pub(crate) fn set_admission(&mut self, admission: Admission) {
+        self.admission = admission;
+    }
+
+    /// Whether `peer`, presenting `streamid`, may connect; if not, why not.
+    pub(crate) fn admit(
+        &mut self,
+        peer: SocketAddr,
+        streamid: Option<String>,
+    ) -> Result<(), &'static str> {
+        let Admission { allow, streamids } = &self.admission;
+        if !allow.is_empty() && !allow.iter().any(|net| net.contains(peer.ip())) {
+            return Err("address_not_allowed");
+        }
+        if !streamids.is_empty()
+            && !streamid
+                .as_ref()
+                .is_some_and(|streamid| streamids.contains(streamid))
+        {
+            return Err("streamid_not_allowed");
+        }
+        if self
+            .banned
+            .get(&(peer.ip(), streamid.clone()))
+            .is_some_and(|until| *until > Instant::now())
+        {
+            return Err("banned");
+        }
+        if self.connecting.len() >= MAX_CONNECTING {
+            self.connecting.clear();
+        }
+        self.connecting.insert(peer, streamid);
+        Ok(())
+    }
+
+    /// `peer` connected on `socket`.
+    pub(crate) fn added(&mut self, peer: SocketAddr, socket: SrtSocket) -> CallerInfo {
+        let streamid = self.connecting.remove(&peer).flatten();
+        let id = self.next_id;
+        self.next_id += 1;
+        let caller = Connected {
+            peer,
+            streamid,
+            socket,
+        };
+        let info = caller.info(id);
+        self.connected.insert(id, caller);
+        info
+    }
+
+    /// `peer` went away; returns it, if it was connected.
+    pub(crate) fn removed(&mut self, peer: SocketAddr) -> Option<CallerInfo> {
+        let id = self
+            .connected
+            .iter()
+            .find_map(|(&id, caller)| (caller.peer == peer).then_some(id))?;
+        self.connected.remove(&id).map(|caller| caller.info(id))
+    }
+
+    /// Refuse caller `id`'s host and streamid for [`BAN_DURATION`], and
+    /// return its socket for [`close`]; `None` if no such caller is
+    /// connected. The other callers are left alone. It stays listed until
+    /// its element reports it removed.
+    pub(crate) fn disconnect(&mut self, id: usize) -> Option<SrtSocket> {
+        let caller = self.connected.get(&id)?;
+        let now = Instant::now();
+        self.banned.retain(|_, until| *until > now);
+        self.banned.insert(
+            (caller.peer.ip(), caller.streamid.clone()),
+            now + BAN_DURATION,
+        );
+        Some(caller.socket)
+    }
+
+    /// Forget the connected callers: their element is gone. Bans stay.
+    pub(crate) fn clear(&mut self) {
+        self.connecting.clear();
+        self.connected.clear();
+    }
+
+    /// The connected callers, in id order.
+    pub(crate) fn list(&self) -> Vec<CallerInfo> {
+        self.connected
+            .iter()
+            .map(|(&id, caller)| caller.info(id))
+            .collect()
+    }
+}
+
+/// Close a caller's SRT socket. The element that accepted it sees its next
+/// send or receive on it fail, and drops that caller alone, with a
+/// `caller-removed`. libsrt is looked up in the SRT plugin, already
+/// loaded, rather than linked: it must be the plugin's own copy.
+pub(crate) fn close(socket: SrtSocket) -> Result<(), Error> {
+    let srt_close = srt_close().ok_or_else(|| anyhow!("libsrt is not loaded"))?;
+    // SAFETY: `srt_close` takes any socket id, and fails on a stale one.
+    if unsafe { srt_close(socket) } != 0 {
+        return Err(anyhow!("libsrt failed to close socket {}", socket));
+    }
+    Ok(())
+}
+
+type SrtClose = unsafe extern "C" fn(SrtSocket) -> c_int;
+
+/// libsrt's `srt_close`, found in whichever libsrt the process has loaded.
+/// Kept once found: the SRT plugin is never unloaded. Until an SRT element
+/// has loaded it, there is none to find.
+fn srt_close() -> Option<SrtClose> {
+    static SRT_CLOSE: OnceLock<SrtClose> = OnceLock::new();
+    if let Some(srt_close) = SRT_CLOSE.get() {
+        return Some(*srt_close);
+    }
+    // SAFETY: RTLD_NOLOAD only finds a library that is already loaded, and
+    // the handle is let go once the symbol is read; the plugin keeps it.
+    let symbol = unsafe {
+        let symbol = libc::dlsym(libc::RTLD_DEFAULT, c"srt_close".as_ptr());
+        if symbol.is_null() {
+            LIBSRT.iter().find_map(|name| {
+                let library = libc::dlopen(name.as_ptr(), libc::RTLD_LAZY | libc::RTLD_NOLOAD);
+                if library.is_null() {
+                    return None;
+                }
+                let symbol = libc::dlsym(library, c"srt_close".as_ptr());
+                libc::dlclose(library);
+                (!symbol.is_null()).then_some(symbol)
+            })?
+        } else {
+            symbol
+        }
+    };
+    // SAFETY: libsrt declares `int srt_close(SRTSOCKET u)`, SRTSOCKET an int.
+    let srt_close = unsafe { std::mem::transmute::<*mut c_void, SrtClose>(symbol) };
+    Some(*SRT_CLOSE.get_or_init(|| srt_close))
+}
+
+/// Keep `callers` in step with the SRT listener `element`, refusing those it
+/// does not admit, and tell `on_event` of every change. `what` names the
+/// listener in the logs. Fails if there are rules to admit callers by but
+/// this GStreamer's SRT plugin cannot refuse any (`caller-connecting` is new
+/// in 1.22); without rules, the callers are still listed.
+pub(crate) fn watch(
+    element: &gst::Element,
+    callers: Arc<Mutex<Callers>>,
+    what: String,
+    on_event: impl Fn(CallerEvent) + Send + Sync + 'static,
+) -> Result<(), Error> {
+    let on_event = Arc::new(on_event);
+
+    let (table, name, notify) = (callers.clone(), what.clone(), on_event.clone());
+    let refusing = element.try_connect("caller-connecting", false, move |values| {
+        let streamid = streamid(values);
+        let admitted = match peer(values) {
+            Some(peer) => table.lock().unwrap().admit(peer, streamid),
+            None => Err("address_unknown"),
+        };
+        if let Err(reason) = admitted {
+            let address =
+                peer(values).map_or_else(|| "unknown".to_string(), |peer| peer.to_string());
+            // Not the streamid: it is a credential, and this one may be a
+            // near miss of a good one.
+            tracing::warn!("{}: refused caller from {}: {}", name, address, reason);
+            notify(CallerEvent::Refused { address, reason });
+        }
+        Some(admitted.is_ok().to_value())
+    });
+    if let Err(e) = refusing {
+        if callers.lock().unwrap().admission != Admission::default() {
+            return Err(anyhow!("{} cannot refuse SRT callers: {}", what, e));
+        }
+        tracing::warn!("{} cannot refuse SRT callers: {}", what, e);
+    }
+
+    let (table, name, notify) = (callers.clone(), what.clone(), on_event.clone());
+    element.connect("caller-added", false, move |values| {
+        if let (Some(peer), Some(socket)) = (peer(values), socket(values)) {
+            let caller = table.lock().unwrap().added(peer, socket);
+            tracing::info!("{}: caller {} connected from {}", name, caller.id, peer);
+            notify(CallerEvent::Connected(caller));
+        }
+        None
+    });
+    element.connect("caller-removed", false, move |values| {
+        if let Some(peer) = peer(values) {
+            let removed = callers.lock().unwrap().removed(peer);
+            if let Some(caller) = removed {
+                tracing::info!("{}: caller {} from {} disconnected", what, caller.id, peer);
+                on_event(CallerEvent::Disconnected(caller));
+            }
+        }
+        None
+    });
+    Ok(())
+}
+
+/// The caller's address among a signal's arguments (a `GSocketAddress`).
+fn peer(values: &[glib::Value]) -> Option<SocketAddr> {
+    values.iter().find_map(|value| {
+        let address = value.get::<gio::SocketAddress>().ok()?;
+        let address = address.downcast::<gio::InetSocketAddress>().ok()?;
+        Some(SocketAddr::from(address))
+    })
+}
+
+/// The caller's SRT socket among a signal's arguments: its only integer.
+fn socket(values: &[glib::Value]) -> Option<SrtSocket> {
+    values
+        .iter()
+        .find_map(|value| value.get::<SrtSocket>().ok())
+}
+
+/// The streamid among `caller-connecting`'s arguments; none if empty.
+fn streamid(values: &[glib::Value]) -> Option<String> {
+    values
+        .iter()
+        .find_map(|value| value.get::<Option<String>>().ok())
+        .flatten()
+        .filter(|streamid| !streamid.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Admission, CallerInfo, Callers, IpNet};
+    use std::net::SocketAddr;
+    use std::time::Instant;
+
+    fn addr(address: &str) -> SocketAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn callers_need_a_listed_streamid_and_a_ban_outlasts_the_connection() {
+        let id = |streamid: &str| Some(streamid.to_string());
+        let mut callers = Callers::default();
+        callers.set_admission(Admission {
+            allow: Vec::new(),
+            streamids: vec!["decoder-a".to_string(), "decoder-b".to_string()],
+        });
+        let refused = Err("streamid_not_allowed");
+        assert_eq!(refused, callers.admit(addr("10.0.0.7:4000"), None));
+        assert_eq!(refused, callers.admit(addr("10.0.0.7:4000"), id("guest")));
+        assert_eq!(
+            Ok(()),
+            callers.admit(addr("10.0.0.7:4000"), id("decoder-a"))
+        );
+        assert_eq!(
+            Ok(()),
+            callers.admit(addr("10.0.0.8:4000"), id("decoder-b"))
+        );
+        let first = callers.added(addr("10.0.0.7:4000"), 11);
+        assert_eq!(
+            CallerInfo {
+                id: 0,
+                address: "10.0.0.7:4000".to_string(),
+                streamid: id("decoder-a"),
+            },
+            first
+        );
+        assert_eq!(1, callers.added(addr("10.0.0.8:4000"), 12).id);
+        assert_eq!(first, callers.list()[0]);
+
+        assert_eq!(Some(11), callers.disconnect(0));
+        assert_eq!(None, callers.disconnect(5));
+        assert_eq!(Some(first), callers.removed(addr("10.0.0.7:4000")));
+        assert_eq!(None, callers.removed(addr("10.0.0.7:4000")));
+        // Refused from any port; the same host with another streamid is not.
+        assert_eq!(
+            Err("banned"),
+            callers.admit(addr("10.0.0.7:4001"), id("decoder-a"))
+        );
+        assert_eq!(
+            Ok(()),
+            callers.admit(addr("10.0.0.7:4001"), id("decoder-b"))
+        );
+
+        callers.clear();
+        assert!(callers.list().is_empty());
+        assert_eq!(2, callers.added(addr("10.0.0.9:4000"), 13).id);
+    }
+
+    #[test]
+    fn disconnecting_one_caller_leaves_the_others_attached() {
+        let mut callers = Callers::default();
+        for (address, socket) in [("10.0.0.7:4000", 21), ("10.0.0.8:4000", 22)] {
+            callers.admit(addr(address), None).unwrap();
+            callers.added(addr(address), socket);
+        }
+
+        // Only the disconnected caller's socket is handed out to be closed...
+        assert_eq!(Some(22), callers.disconnect(1));
+        // ...and once its sink reports it gone, the other is still there.
+        assert_eq!(1, callers.removed(addr("10.0.0.8:4000")).unwrap().id);
+        let left: Vec<usize> = callers.list().iter().map(|caller| caller.id).collect();
+        assert_eq!(vec![0], left);
+        assert_eq!(Ok(()), callers.admit(addr("10.0.0.7:4001"), None));
+        assert_eq!(Err("banned"), callers.admit(addr("10.0.0.8:4001"), None));
+    }
+
+    #[test]
+    fn a_ban_expires_and_is_then_dropped() {
+        let mut callers = Callers::default();
+        callers.admit(addr("10.0.0.7:4000"), None).unwrap();
+        callers.added(addr("10.0.0.7:4000"), 31);
+        assert_eq!(Some(31), callers.disconnect(0));
+        assert_eq!(Err("banned"), callers.admit(addr("10.0.0.7:4001"), None));
+
+        // Past its expiry, the host is let in again...
+        for until in callers.banned.values_mut() {
+            *until = Instant::now();
+        }
+        assert_eq!(Ok(()), callers.admit(addr("10.0.0.7:4001"), None));
+        // ...and the next ban sweeps the stale one out.
+        callers.added(addr("10.0.0.8:4000"), 32);
+        callers.disconnect(1);
+        assert_eq!(1, callers.banned.len());
+    }
+
+    #[test]
+    fn a_redacted_caller_serializes_without_its_streamid() {
+        let caller = CallerInfo {
+            id: 3,
+            address: "10.0.0.7:4000".to_string(),
+            streamid: Some("decoder-a".to_string()),
+        };
+        assert_eq!(
+            serde_json::json!({"id": 3, "address": "10.0.0.7:4000"}),
+            serde_json::to_value(caller.redacted()).unwrap()
+        );
+    }
+
+    #[test]
+    fn without_rules_any_caller_is_admitted() {
+        let mut callers = Callers::default();
+        assert_eq!(Ok(()), callers.admit(addr("192.0.2.1:9000"), None));
+        assert_eq!(None, callers.added(addr("192.0.2.1:9000"), 1).streamid);
+    }
+
+    #[test]
+    fn the_allowlist_admits_addresses_and_networks() {
+        let mut callers = Callers::default();
+        callers.set_admission(Admission {
+            allow: vec![
+                "10.1.0.0/16".parse().unwrap(),
+                "2001:db8::7".parse().unwrap(),
+            ],
+            streamids: Vec::new(),
+        });
+        assert_eq!(Ok(()), callers.admit(addr("10.1.200.3:5000"), None));
+        assert_eq!(Ok(()), callers.admit(addr("[::ffff:10.1.0.9]:5000"), None));
+        assert_eq!(Ok(()), callers.admit(addr("[2001:db8::7]:5000"), None));
+        let refused = Err("address_not_allowed");
+        assert_eq!(refused, callers.admit(addr("10.2.0.1:5000"), None));
+        assert_eq!(refused, callers.admit(addr("[2001:db8::8]:5000"), None));
+    }
+
+    #[test]
+    fn ip_nets_parse_with_or_without_a_prefix() {
+        assert_eq!(
+            "192.0.2.1/32",
+            "192.0.2.1".parse::<IpNet>().unwrap().to_string()
+        );
+        assert_eq!(
+            "0.0.0.0/0",
+            "0.0.0.0/0".parse::<IpNet>().unwrap().to_string()
+        );
+        assert!("0.0.0.0/0"
+            .parse::<IpNet>()
+            .unwrap()
+            .contains("203.0.113.9".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<IpNet>().is_err());
+        assert!("example.com".parse::<IpNet>().is_err());
+    }
+}
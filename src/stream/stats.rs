@@ -0,0 +1,539 @@
+//! Reading one viewer's WebRTC statistics out of its whipclientsink.
+//!
+//! whipclientsink exposes webrtcbin's statistics through its `stats`
+//! property: one structure per consumer session, with one nested entry per
+//! stats kind (`rtp-outbound-stream-stats_*`, `ice-candidate-local_*`, ...).
+//! The exact nesting depth has changed between plugin versions, so every
+//! lookup here searches the tree by entry-name prefix rather than by path.
+//!
+//! Pure functions over a `gst::Structure`: the element lookup and the
+//! property read stay with `Branch`, so these are testable with hand-built
+//! structures and no live peer.
+//!
+//! The ingest element's statistics (`srtsrc`, `ristsrc`) are passed through
+//! as JSON instead: each protocol keeps its own counters, and operators know
+//! them by the element's names.
+
+use gstreamer as gst;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+use crate::stream::srt_callers::CallerInfo;
+
+/// One end of an ICE candidate pair, as webrtcbin reports it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IceCandidate {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+}
+
+/// The ICE candidate pair a viewer's session selected for its media.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CandidatePair {
+    pub local: IceCandidate,
+    pub remote: IceCandidate,
+}
+
+/// One outgoing RTP stream (audio or video) of a viewer's session: what we
+/// sent, joined by SSRC with what the viewer's receiver reports say arrived.
+/// Every field is optional because webrtcbin only fills in what it has seen.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StreamStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssrc: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_sent: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packets_sent: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nack_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pli_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fir_count: Option<u64>,
+    /// From the viewer's receiver reports; absent until the first one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packets_lost: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fraction_lost: Option<f64>,
+    /// Seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<f64>,
+    /// Seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round_trip_time: Option<f64>,
+}
+
+/// The WebRTC statistics of one viewer's session (`GET /channel/{id}/stats`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ViewerStats {
+    /// Send rate between the coordinator's last two samples of this
+    /// viewer's stats, one per sweep tick; absent after the first. Filled
+    /// in by the coordinator — the sink only reports running totals.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate_bps: Option<u64>,
+    pub streams: Vec<StreamStats>,
+}
+
+impl ViewerStats {
+    /// Bytes sent across all of this viewer's streams.
+    pub fn bytes_sent(&self) -> u64 {
+        self.streams.iter().filter_map(|s| s.bytes_sent).sum()
+    }
+}
+
+/// What one sweep tick reads from a viewer's sink, all from one read of its
+/// `stats` property.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViewerSample {
+    pub stats: ViewerStats,
+    /// See [`receiver_report_marker`].
+    pub receiver_report_marker: Option<u64>,
+    /// See [`selected_candidate_pair`].
+    pub candidate_pair: Option<CandidatePair>,
+}
+
+/// Totals across every viewer (`GET /stats`), plus each viewer's own stats.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AggregateStats {
+    pub viewers: usize,
+    pub bitrate_bps: u64,
+    pub bytes_sent: u64,
+    pub packets_sent: u64,
+    pub packets_lost: i64,
+    pub nack_count: u64,
+    pub pli_count: u64,
+    /// Mean over every stream that has a receiver report, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_round_trip_time: Option<f64>,
+    /// Worst jitter over every stream, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_jitter: Option<f64>,
+    pub per_viewer: BTreeMap<String, ViewerStats>,
+}
+
+impl AggregateStats {
+    pub fn from_viewers(per_viewer: BTreeMap<String, ViewerStats>) -> Self {
+        let streams = || per_viewer.values().flat_map(|v| v.streams.iter());
+        let rtts: Vec<f64> = streams().filter_map(|s| s.round_trip_time).collect();
+        Self {
+            viewers: per_viewer.len(),
+            bitrate_bps: per_viewer.values().filter_map(|v| v.bitrate_bps).sum(),
+            bytes_sent: streams().filter_map(|s| s.bytes_sent).sum(),
+            packets_sent: streams().filter_map(|s| s.packets_sent).sum(),
+            packets_lost: streams().filter_map(|s| s.packets_lost).sum(),
+            nack_count: streams().filter_map(|s| s.nack_count).sum(),
+            pli_count: streams().filter_map(|s| s.pli_count).sum(),
+            mean_round_trip_time: (!rtts.is_empty())
+                .then(|| rtts.iter().sum::<f64>() / rtts.len() as f64),
+            max_jitter: streams().filter_map(|s| s.jitter).reduce(f64::max),
+            per_viewer,
+        }
+    }
+}
+
+/// The input's statistics (`GET /input/stats`): which protocol feeds the
+/// pipeline, and its source element's own `stats`, field by field. `stats`
+/// is absent for a source that keeps none (UDP, a file, the test source).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InputStats {
+    pub protocol: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<serde_json::Value>,
+    /// The publishers connected to an SRT input in listener mode, without
+    /// their streamids.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publishers: Option<Vec<CallerInfo>>,
+}
+
+/// Every outgoing stream in `stats`, joined by SSRC with the matching
+/// receiver-report entry.
+pub(crate) fn viewer_stats(stats: &gst::StructureRef) -> ViewerStats {
+    let remote_inbound = collect_nested(stats, "rtp-remote-inbound-stream-stats");
+    let streams = collect_nested(stats, "rtp-outbound-stream-stats")
+        .iter()
+        .map(|outbound| {
+            let ssrc = unsigned(outbound, "ssrc");
+            let report = remote_inbound
+                .iter()
+                .find(|inbound| ssrc.is_some() && unsigned(inbound, "ssrc") == ssrc);
+            StreamStats {
+                ssrc,
+                kind: outbound.get::<String>("kind").ok(),
+                bytes_sent: unsigned(outbound, "bytes-sent"),
+                packets_sent: unsigned(outbound, "packets-sent"),
+                nack_count: unsigned(outbound, "nack-count"),
+                pli_count: unsigned(outbound, "pli-count"),
+                fir_count: unsigned(outbound, "fir-count"),
+                packets_lost: report.and_then(|r| signed(r, "packets-lost")),
+                fraction_lost: report.and_then(|r| r.get::<f64>("fraction-lost").ok()),
+                jitter: report.and_then(|r| r.get::<f64>("jitter").ok()),
+                round_trip_time: report.and_then(|r| r.get::<f64>("round-trip-time").ok()),
+            }
+        })
+        .collect();
+    ViewerStats {
+        bitrate_bps: None,
+        streams,
+    }
+}
+
+/// Everything a sweep tick samples from one read of a sink's `stats`.
+pub(crate) fn viewer_sample(stats: &gst::StructureRef) -> ViewerSample {
+    ViewerSample {
+        stats: viewer_stats(stats),
+        receiver_report_marker: receiver_report_marker(stats),
+        candidate_pair: selected_candidate_pair(stats),
+    }
+}
+
+/// The selected candidate pair, if ICE has settled on one. webrtcbin only
+/// reports candidates for the pair its ICE agent selected.
+pub(crate) fn selected_candidate_pair(stats: &gst::StructureRef) -> Option<CandidatePair> {
+    let local = find_nested(stats, "ice-candidate-local")?;
+    let remote = find_nested(stats, "ice-candidate-remote")?;
+    Some(CandidatePair {
+        local: candidate(&local)?,
+        remote: candidate(&remote)?,
+    })
+}
+
+/// The `rtp-remote-inbound-stream-stats` fields a receiver report sets. Not
+/// the whole entry: its `timestamp` is when the stats were read, and moves
+/// on every read whether or not the peer said anything.
+const RECEIVER_REPORT_FIELDS: [&str; 5] = [
+    "packets-lost",
+    "fraction-lost",
+    "jitter",
+    "round-trip-time",
+    "reports-received",
+];
+
+/// A marker that changes whenever the viewer sends a new RTCP receiver
+/// report, or `None` before the first one. The
+/// `rtp-remote-inbound-stream-stats` entries are rebuilt from each receiver
+/// report (round-trip time, jitter, loss, the report count), so hashing just
+/// those fields yields a value that moves with every report and stays put
+/// while the peer is silent.
+pub(crate) fn receiver_report_marker(stats: &gst::StructureRef) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    hash_remote_inbound_stats(stats, &mut hasher).then(|| hasher.finish())
+}
+
+/// Feed the receiver report fields of every `rtp-remote-inbound-stream-stats`
+/// structure nested anywhere in `stats` into `hasher`. Returns whether at
+/// least one was found.
+fn hash_remote_inbound_stats(stats: &gst::StructureRef, hasher: &mut impl Hasher) -> bool {
+    if stats
+        .name()
+        .as_str()
+        .starts_with("rtp-remote-inbound-stream-stats")
+    {
+        stats.name().as_str().hash(hasher);
+        for field in RECEIVER_REPORT_FIELDS {
+            let value = stats
+                .value(field)
+                .ok()
+                .and_then(|value| value.serialize().ok());
+            (field, value.as_deref()).hash(hasher);
+        }
+        return true;
+    }
+    let mut found = false;
+    for (_, value) in stats.iter() {
+        if let Ok(nested) = value.get::<gst::Structure>() {
+            found |= hash_remote_inbound_stats(&nested, hasher);
+        }
+    }
+    found
+}
+
+/// `stats` as a JSON object, nested structures and arrays included. A value
+/// of no JSON type is kept as its GStreamer serialization.
+pub(crate) fn structure_to_json(stats: &gst::StructureRef) -> serde_json::Value {
+    serde_json::Value::Object(
+        stats
+            .iter()
+            .map(|(name, value)| (name.to_string(), value_to_json(value)))
+            .collect(),
+    )
+}
+
+fn value_to_json(value: &glib::Value) -> serde_json::Value {
+    use serde_json::Value;
+    if let Ok(nested) = value.get::<gst::Structure>() {
+        return structure_to_json(&nested);
+    }
+    if let Ok(array) = value.get::<gst::Array>() {
+        return Value::Array(array.iter().map(|v| value_to_json(v)).collect());
+    }
+    if let Ok(array) = value.get::<glib::ValueArray>() {
+        return Value::Array(array.iter().map(value_to_json).collect());
+    }
+    if let Ok(v) = value.get::<bool>() {
+        return v.into();
+    }
+    if let Ok(v) = value.get::<String>() {
+        return v.into();
+    }
+    if let Ok(v) = value.get::<f64>() {
+        return v.into();
+    }
+    if let Ok(v) = value.get::<f32>() {
+        return f64::from(v).into();
+    }
+    if let Ok(v) = value.get::<i64>() {
+        return v.into();
+    }
+    if let Ok(v) = value.get::<u64>() {
+        return v.into();
+    }
+    if let Ok(v) = value.get::<i32>() {
+        return v.into();
+    }
+    if let Ok(v) = value.get::<u32>() {
+        return v.into();
+    }
+    value
+        .serialize()
+        .map_or(Value::Null, |serialized| serialized.to_string().into())
+}
+
+fn candidate(stats: &gst::StructureRef) -> Option<IceCandidate> {
+    Some(IceCandidate {
+        address: stats.get::<String>("address").ok()?,
+        port: stats.get::<u32>("port").ok(),
+        candidate_type: stats.get::<String>("candidate-type").ok(),
+        protocol: stats.get::<String>("protocol").ok(),
+    })
+}
+
+/// A counter field, whichever integer type this plugin version stores it as.
+fn unsigned(stats: &gst::StructureRef, field: &str) -> Option<u64> {
+    stats
+        .get::<u64>(field)
+        .ok()
+        .or_else(|| stats.get::<u32>(field).ok().map(u64::from))
+        .or_else(|| stats.get::<i64>(field).ok().and_then(|v| v.try_into().ok()))
+        .or_else(|| stats.get::<i32>(field).ok().and_then(|v| v.try_into().ok()))
+}
+
+/// A signed field (cumulative loss can go negative with duplicates).
+fn signed(stats: &gst::StructureRef, field: &str) -> Option<i64> {
+    stats
+        .get::<i64>(field)
+        .ok()
+        .or_else(|| stats.get::<i32>(field).ok().map(i64::from))
+        .or_else(|| unsigned(stats, field).and_then(|v| v.try_into().ok()))
+}
+
+/// The first structure nested anywhere in `stats` (itself included) whose
+/// name starts with `prefix`.
+fn find_nested(stats: &gst::StructureRef, prefix: &str) -> Option<gst::Structure> {
+    if stats.name().as_str().starts_with(prefix) {
+        return Some(stats.to_owned());
+    }
+    stats.iter().find_map(|(_, value)| {
+        value
+            .get::<gst::Structure>()
+            .ok()
+            .and_then(|nested| find_nested(&nested, prefix))
+    })
+}
+
+/// Every structure nested anywhere in `stats` (itself included) whose name
+/// starts with `prefix`.
+fn collect_nested(stats: &gst::StructureRef, prefix: &str) -> Vec<gst::Structure> {
+    if stats.name().as_str().starts_with(prefix) {
+        return vec![stats.to_owned()];
+    }
+    stats
+        .iter()
+        .filter_map(|(_, value)| value.get::<gst::Structure>().ok())
+        .flat_map(|nested| collect_nested(&nested, prefix))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The shape whipclientsink's `stats` property nests: one webrtcbin
+    /// stats structure per session, one entry per stats kind.
+    fn sink_stats(entries: Vec<gst::Structure>) -> gst::Structure {
+        gst::init().unwrap();
+        let mut session = gst::Structure::builder("application/x-webrtc-stats").build();
+        for entry in entries {
+            session.set(entry.name().to_string().as_str(), entry);
+        }
+        gst::Structure::builder("application/x-webrtcsink-stats")
+            .field("session-1", session)
+            .build()
+    }
+
+    fn remote_inbound(round_trip_time: f64) -> gst::Structure {
+        gst::Structure::builder("rtp-remote-inbound-stream-stats_1")
+            .field("round-trip-time", round_trip_time)
+            .build()
+    }
+
+    fn outbound(packets_sent: u64) -> gst::Structure {
+        gst::Structure::builder("rtp-outbound-stream-stats_1")
+            .field("packets-sent", packets_sent)
+            .build()
+    }
+
+    #[test]
+    fn receiver_report_marker_moves_only_with_remote_inbound_stats() {
+        let marker = |rtt, sent| {
+            receiver_report_marker(&sink_stats(vec![remote_inbound(rtt), outbound(sent)]))
+        };
+        // Sending media alone does not count as the viewer being alive...
+        assert_eq!(marker(0.01, 100), marker(0.01, 200));
+        // ...a new receiver report does.
+        assert_ne!(marker(0.01, 100), marker(0.02, 100));
+        // No report yet: no marker.
+        assert_eq!(None, receiver_report_marker(&sink_stats(vec![outbound(1)])));
+    }
+
+    #[test]
+    fn receiver_report_marker_ignores_when_the_stats_were_read() {
+        let marker = |timestamp: f64, packets_lost: i64| {
+            let entry = gst::Structure::builder("rtp-remote-inbound-stream-stats_1")
+                .field("timestamp", timestamp)
+                .field("packets-lost", packets_lost)
+                .field("round-trip-time", 0.01)
+                .build();
+            receiver_report_marker(&sink_stats(vec![entry]))
+        };
+        // Read again later, with no new report: the same marker.
+        assert_eq!(marker(1_000.0, 3), marker(2_500.0, 3));
+        assert_ne!(marker(1_000.0, 3), marker(2_500.0, 4));
+    }
+
+    #[test]
+    fn viewer_stats_join_outbound_streams_with_their_receiver_reports() {
+        let video_out = gst::Structure::builder("rtp-outbound-stream-stats_1")
+            .field("ssrc", 1111u32)
+            .field("kind", "video")
+            .field("bytes-sent", 50_000u64)
+            .field("packets-sent", 60u64)
+            .field("nack-count", 2u32)
+            .field("pli-count", 1u32)
+            .build();
+        let audio_out = gst::Structure::builder("rtp-outbound-stream-stats_2")
+            .field("ssrc", 2222u32)
+            .field("bytes-sent", 1_000u64)
+            .build();
+        let video_in = gst::Structure::builder("rtp-remote-inbound-stream-stats_1")
+            .field("ssrc", 1111u32)
+            .field("packets-lost", 3i64)
+            .field("jitter", 0.004)
+            .field("round-trip-time", 0.02)
+            .build();
+
+        let stats = viewer_stats(&sink_stats(vec![video_out, audio_out, video_in]));
+        assert_eq!(2, stats.streams.len());
+        let video = stats.streams.iter().find(|s| s.ssrc == Some(1111)).unwrap();
+        assert_eq!(Some("video".to_string()), video.kind);
+        assert_eq!(Some(2), video.nack_count);
+        assert_eq!(Some(3), video.packets_lost);
+        assert_eq!(Some(0.02), video.round_trip_time);
+        // No receiver report for audio yet: sender-side counters only.
+        let audio = stats.streams.iter().find(|s| s.ssrc == Some(2222)).unwrap();
+        assert_eq!(Some(1_000), audio.bytes_sent);
+        assert_eq!(None, audio.round_trip_time);
+        assert_eq!(51_000, stats.bytes_sent());
+    }
+
+    #[test]
+    fn aggregate_stats_total_across_viewers() {
+        let viewer = |bytes_sent, rtt: Option<f64>, jitter: Option<f64>| ViewerStats {
+            bitrate_bps: Some(1_000),
+            streams: vec![StreamStats {
+                bytes_sent: Some(bytes_sent),
+                packets_lost: Some(1),
+                round_trip_time: rtt,
+                jitter,
+                ..StreamStats::default()
+            }],
+        };
+        let per_viewer = BTreeMap::from([
+            ("a".to_string(), viewer(100, Some(0.01), Some(0.002))),
+            ("b".to_string(), viewer(300, Some(0.03), None)),
+        ]);
+
+        let aggregate = AggregateStats::from_viewers(per_viewer);
+        assert_eq!(2, aggregate.viewers);
+        assert_eq!(2_000, aggregate.bitrate_bps);
+        assert_eq!(400, aggregate.bytes_sent);
+        assert_eq!(2, aggregate.packets_lost);
+        assert_eq!(Some(0.02), aggregate.mean_round_trip_time);
+        assert_eq!(Some(0.002), aggregate.max_jitter);
+
+        // No viewers: zero totals, and no mean to speak of.
+        let empty = AggregateStats::from_viewers(BTreeMap::new());
+        assert_eq!(0, empty.viewers);
+        assert_eq!(None, empty.mean_round_trip_time);
+    }
+
+    #[test]
+    fn selected_candidate_pair_is_read_from_the_candidate_entries() {
+        let local = gst::Structure::builder("ice-candidate-local_10.0.0.1")
+            .field("address", "10.0.0.1")
+            .field("port", 50000u32)
+            .field("candidate-type", "host")
+            .field("protocol", "udp")
+            .build();
+        let remote = gst::Structure::builder("ice-candidate-remote_192.168.1.7")
+            .field("address", "192.168.1.7")
+            .field("port", 61000u32)
+            .field("candidate-type", "srflx")
+            .field("protocol", "udp")
+            .build();
+
+        let pair = selected_candidate_pair(&sink_stats(vec![local, remote])).unwrap();
+        assert_eq!("10.0.0.1", pair.local.address);
+        assert_eq!(Some(50000), pair.local.port);
+        assert_eq!("192.168.1.7", pair.remote.address);
+        assert_eq!(Some("srflx".to_string()), pair.remote.candidate_type);
+
+        // ICE has not settled yet: no pair.
+        assert_eq!(
+            None,
+            selected_candidate_pair(&sink_stats(vec![outbound(1)]))
+        );
+    }
+
+    #[test]
+    fn input_stats_keep_the_source_elements_own_fields() {
+        gst::init().unwrap();
+        let link = gst::Structure::builder("link")
+            .field("rtt-ms", 12.5f64)
+            .field("packets-received-lost", 3i32)
+            .build();
+        let stats = gst::Structure::builder("application/x-srt-statistics")
+            .field("bytes-received", 1_000u64)
+            .field("link", link)
+            .field("callers", gst::Array::new([10u32, 20u32]))
+            .build();
+
+        assert_eq!(
+            serde_json::json!({
+                "bytes-received": 1000,
+                "link": { "rtt-ms": 12.5, "packets-received-lost": 3 },
+                "callers": [10, 20],
+            }),
+            structure_to_json(&stats)
+        );
+    }
+}
@@ -3,79 +3,323 @@
 //!
 //! This is the one place that knows the restart policy, the cleanup/reset
 //! contract with the coordinator, and the shutdown ordering (EOS → join).
+use crate::events::{Event, EventBus};
+use crate::metrics::Metrics;
 use crate::signal::ResetSignal;
 use crate::stream::PipelineLifecycle;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
 use std::time::Duration;
 use tokio::sync::{mpsc, watch};
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Defaults shared by `Default for SupervisorConfig` and the
+/// `SupervisorArgs` clap attributes, as for the coordinator's knobs.
+const DEFAULT_RESTART_DELAY_MS: u64 = 1000;
+const DEFAULT_MAX_RESTART_DELAY_SEC: u64 = 30;
+const DEFAULT_SHUTDOWN_JOIN_TIMEOUT_SEC: u64 = 5;
+const DEFAULT_RESET_TIMEOUT_SEC: u64 = 5;
+const DEFAULT_RESTART_JITTER: f64 = 0.0;
+
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// Delay before a rerun after a clean stop, a watchdog restart, or a
+    /// first failure; doubled per further consecutive failure.
+    pub base_restart_delay: Duration,
+    pub max_restart_delay: Duration,
+    /// Upper bound on joining a run asked to stop (EOS or forced quit).
+    pub shutdown_join_timeout: Duration,
+    /// Upper bound on the signaling reset after each stop.
+    pub reset_timeout: Duration,
+    /// Give up — and exit non-zero — after this many failed runs in a row.
+    /// A run that stayed up for `max_restart_delay` or longer before failing
+    /// starts a new streak. `None` (the default) retries forever.
+    pub max_consecutive_failures: Option<u32>,
+    /// Randomize each backoff by up to this fraction either way, so a fleet
+    /// restarted together does not retry in lockstep. `0.0` (the default)
+    /// disables it.
+    pub restart_jitter: f64,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            base_restart_delay: Duration::from_millis(DEFAULT_RESTART_DELAY_MS),
+            max_restart_delay: Duration::from_secs(DEFAULT_MAX_RESTART_DELAY_SEC),
+            shutdown_join_timeout: Duration::from_secs(DEFAULT_SHUTDOWN_JOIN_TIMEOUT_SEC),
+            reset_timeout: Duration::from_secs(DEFAULT_RESET_TIMEOUT_SEC),
+            max_consecutive_failures: None,
+            restart_jitter: DEFAULT_RESTART_JITTER,
+        }
+    }
+}
+
+/// CLI surface for the restart policy.
+#[derive(clap::Args, Debug, Clone)]
+pub struct SupervisorArgs {
+    /// Milliseconds before rerunning the pipeline; doubled per consecutive failure.
+    #[clap(long, default_value_t = DEFAULT_RESTART_DELAY_MS)]
+    pub restart_delay_ms: u64,
+    /// Upper bound, in seconds, on the delay before a rerun.
+    #[clap(long, default_value_t = DEFAULT_MAX_RESTART_DELAY_SEC)]
+    pub max_restart_delay_sec: u64,
+    /// Seconds to wait for a stopping pipeline to finish before abandoning it.
+    #[clap(long, default_value_t = DEFAULT_SHUTDOWN_JOIN_TIMEOUT_SEC)]
+    pub shutdown_join_timeout_sec: u64,
+    /// Seconds to wait for the signaling reset after each pipeline stop.
+    #[clap(long, default_value_t = DEFAULT_RESET_TIMEOUT_SEC)]
+    pub reset_timeout_sec: u64,
+    /// Exit non-zero after this many consecutive failed runs (unset: retry forever).
+    /// A run up for at least --max-restart-delay-sec starts a new count.
+    #[clap(long)]
+    pub max_consecutive_failures: Option<u32>,
+    /// Fraction (0–1) by which each restart delay is randomly varied.
+    #[clap(long, default_value_t = DEFAULT_RESTART_JITTER)]
+    pub restart_jitter: f64,
+}
+
+impl SupervisorArgs {
+    pub fn to_config(&self) -> SupervisorConfig {
+        SupervisorConfig {
+            base_restart_delay: Duration::from_millis(self.restart_delay_ms),
+            max_restart_delay: Duration::from_secs(self.max_restart_delay_sec),
+            shutdown_join_timeout: Duration::from_secs(self.shutdown_join_timeout_sec),
+            reset_timeout: Duration::from_secs(self.reset_timeout_sec),
+            max_consecutive_failures: self.max_consecutive_failures,
+            restart_jitter: self.restart_jitter.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The supervisor stopped rerunning the pipeline: it failed
+/// `max_consecutive_failures` times in a row.
+#[derive(Debug, thiserror::Error)]
+#[error("Pipeline failed {failures} times in a row; giving up. Last error: {last_error}")]
+pub struct GaveUp {
+    pub failures: u32,
+    pub last_error: String,
+}
+
+/// What the supervisor is doing right now.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupervisorPhase {
+    /// Initializing the pipeline for a run.
+    #[default]
+    Starting,
+    /// The pipeline is running.
+    Running,
+    /// The run ended; cleaning up and resetting signaling.
+    Restarting,
+    /// Waiting out the backoff before the next run.
+    BackingOff,
+    /// Shut down: the pipeline will not run again.
+    Stopped,
+    /// Gave up after too many consecutive failures; the process exits.
+    Failed,
+}
+
+/// The supervisor's state, shared over a watch channel for the health
+/// probes and `GET /status`. The channel closing means the supervisor task
+/// is gone.
+#[derive(Debug, Clone, Default)]
+pub struct SupervisorStatus {
+    pub phase: SupervisorPhase,
+    /// When the current run came up; `None` while none is running.
+    pub run_started: Option<Instant>,
+    /// When the current backoff ends; `None` unless backing off.
+    pub backoff_until: Option<Instant>,
+    /// Reruns since startup, whatever their cause.
+    pub restarts: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+/// `GET /status`: the supervisor's state with durations resolved.
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub phase: SupervisorPhase,
+    pub uptime_secs: Option<u64>,
+    pub backoff_remaining_ms: Option<u64>,
+    pub restarts: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+impl SupervisorStatus {
+    /// Time left before the next run, while backing off.
+    pub fn backoff_remaining(&self, now: Instant) -> Option<Duration> {
+        self.backoff_until
+            .map(|until| until.saturating_duration_since(now))
+    }
 
-const BASE_RESTART_DELAY: Duration = Duration::from_secs(1);
-const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
-const SHUTDOWN_JOIN_TIMEOUT: Duration = Duration::from_secs(5);
-const RESET_TIMEOUT: Duration = Duration::from_secs(5);
+    pub fn report(&self, now: Instant) -> StatusReport {
+        StatusReport {
+            phase: self.phase,
+            uptime_secs: self.run_started.map(|since| (now - since).as_secs()),
+            backoff_remaining_ms: self
+                .backoff_remaining(now)
+                .map(|left| left.as_millis().try_into().unwrap_or(u64::MAX)),
+            restarts: self.restarts,
+            consecutive_failures: self.consecutive_failures,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
 
 pub struct Supervisor<P: PipelineLifecycle, S: ResetSignal> {
     pipeline: P,
     signal: S,
     shutdown: watch::Receiver<bool>,
     restart_rx: mpsc::Receiver<()>,
+    events: EventBus,
+    metrics: Metrics,
+    status: watch::Sender<SupervisorStatus>,
+    config: SupervisorConfig,
+}
+
+/// Where the supervisor reports what it does: run and restart events, the
+/// restart metrics, and its state for the health probes and `GET /status`.
+pub struct SupervisorReports {
+    pub events: EventBus,
+    pub metrics: Metrics,
+    pub status: watch::Sender<SupervisorStatus>,
 }
 
 impl<P: PipelineLifecycle + 'static, S: ResetSignal + 'static> Supervisor<P, S> {
     /// Spawn the supervision loop. It runs until the shutdown channel reads
-    /// `true` (or its sender is dropped).
+    /// `true` (or its sender is dropped), or until it gives up after
+    /// `max_consecutive_failures` failed runs in a row.
     pub fn spawn(
         pipeline: P,
         signal: S,
         shutdown: watch::Receiver<bool>,
         restart_rx: mpsc::Receiver<()>,
-    ) -> JoinHandle<()> {
+        reports: SupervisorReports,
+        config: SupervisorConfig,
+    ) -> JoinHandle<Result<(), GaveUp>> {
+        let SupervisorReports {
+            events,
+            metrics,
+            status,
+        } = reports;
         tokio::spawn(
             Self {
                 pipeline,
                 signal,
                 shutdown,
                 restart_rx,
+                events,
+                metrics,
+                status,
+                config,
             }
             .run(),
         )
     }
 
-    async fn run(mut self) {
+    async fn run(mut self) -> Result<(), GaveUp> {
         let mut consecutive_failures: u32 = 0;
         loop {
             if *self.shutdown.borrow() {
                 break;
             }
 
+            self.update(|status| {
+                status.phase = SupervisorPhase::Starting;
+                status.backoff_until = None;
+            });
             let outcome = self.run_pipeline_until_stopped().await;
+            // How long the run was up; `None` if it never came up.
+            let up_for = self
+                .status
+                .borrow()
+                .run_started
+                .map(|since| since.elapsed());
+            self.update(|status| {
+                status.phase = SupervisorPhase::Restarting;
+                status.run_started = None;
+            });
 
             // Explicit, always: clean the pipeline so it can be rerun, and
             // fail all in-flight handshakes so no waiter outlives the run.
             self.cleanup().await;
 
-            match outcome {
+            let cause = match outcome {
                 RunOutcome::ShuttingDown => break,
                 RunOutcome::Restart => {
                     consecutive_failures = 0; // base-delay rerun, like a clean run
                     tracing::info!("Watchdog requested a restart. Reset and rerun the pipeline.");
+                    self.events.publish(Event::WatchdogRestart);
+                    "watchdog"
                 }
                 RunOutcome::Completed(Ok(())) => {
                     consecutive_failures = 0;
                     tracing::info!("Pipeline reached EOS. Reset and rerun the pipeline.");
+                    self.events.publish(Event::PipelineEos);
+                    "eos"
                 }
                 RunOutcome::Completed(Err(e)) => {
+                    // A run that outlasted the longest backoff was healthy:
+                    // its failure is not part of the streak before it, as
+                    // for a push output.
+                    if up_for.is_some_and(|up| up >= self.config.max_restart_delay) {
+                        consecutive_failures = 0;
+                    }
                     consecutive_failures = consecutive_failures.saturating_add(1);
                     tracing::error!("Pipeline stopped with an error: {}", e);
+                    self.events.publish(Event::PipelineError {
+                        message: e.to_string(),
+                    });
+                    self.update(|status| status.last_error = Some(e.to_string()));
+                    if self
+                        .config
+                        .max_consecutive_failures
+                        .is_some_and(|max| consecutive_failures >= max)
+                    {
+                        let gave_up = GaveUp {
+                            failures: consecutive_failures,
+                            last_error: e.to_string(),
+                        };
+                        tracing::error!("{}", gave_up);
+                        self.update(|status| {
+                            status.phase = SupervisorPhase::Failed;
+                            status.consecutive_failures = consecutive_failures;
+                        });
+                        return Err(gave_up);
+                    }
+                    "error"
                 }
-            }
-
+            };
+
+            let delay = backoff_delay(&self.config, consecutive_failures);
+            self.metrics.restart(cause, delay);
+            self.update(|status| {
+                status.phase = SupervisorPhase::BackingOff;
+                status.backoff_until = Some(Instant::now() + delay);
+                status.restarts += 1;
+                status.consecutive_failures = consecutive_failures;
+            });
+            self.events.publish(Event::Backoff {
+                delay_ms: delay.as_millis().try_into().unwrap_or(u64::MAX),
+            });
             tokio::select! {
-                _ = tokio::time::sleep(backoff_delay(consecutive_failures)) => {}
+                _ = tokio::time::sleep(delay) => {}
                 _ = wait_for_shutdown(&mut self.shutdown) => break,
             }
         }
+        self.update(|status| {
+            status.phase = SupervisorPhase::Stopped;
+            status.backoff_until = None;
+        });
         tracing::info!("Pipeline supervisor stopped");
+        Ok(())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut SupervisorStatus)) {
+        // `send_modify` updates even with no receiver left.
+        self.status.send_modify(f);
     }
 
     /// One init→run cycle. Resolves when the pipeline stops on its own
@@ -93,6 +337,11 @@ impl<P: PipelineLifecycle + 'static, S: ResetSignal + 'static> Supervisor<P, S>
         if let Err(e) = self.pipeline.init().await {
             return RunOutcome::Completed(Err(e));
         }
+        self.events.publish(Event::PipelineStarted);
+        self.update(|status| {
+            status.phase = SupervisorPhase::Running;
+            status.run_started = Some(Instant::now());
+        });
 
         // The real pipeline's run() parks its worker thread in the GLib main
         // loop, so it must live on its own task; only something from outside
@@ -107,7 +356,7 @@ impl<P: PipelineLifecycle + 'static, S: ResetSignal + 'static> Supervisor<P, S>
             res = &mut run_task => RunOutcome::Completed(flatten_join(res)),
             _ = wait_for_shutdown(&mut self.shutdown) => {
                 let _ = self.pipeline.end().await;
-                match tokio::time::timeout(SHUTDOWN_JOIN_TIMEOUT, &mut run_task).await {
+                match tokio::time::timeout(self.config.shutdown_join_timeout, &mut run_task).await {
                     Ok(joined) => {
                         if let Err(e) = flatten_join(joined) {
                             tracing::warn!("Pipeline stopped with an error during shutdown: {}", e);
@@ -117,7 +366,7 @@ impl<P: PipelineLifecycle + 'static, S: ResetSignal + 'static> Supervisor<P, S>
                         run_task.abort();
                         tracing::error!(
                             "Pipeline did not stop within {:?} after EOS; abandoning it",
-                            SHUTDOWN_JOIN_TIMEOUT
+                            self.config.shutdown_join_timeout
                         );
                     }
                 }
@@ -127,7 +376,7 @@ impl<P: PipelineLifecycle + 'static, S: ResetSignal + 'static> Supervisor<P, S>
                 // Watchdog asked for a restart: force the run down, then join
                 // (bounded — a wedged quit must not hang the loop).
                 let _ = self.pipeline.quit().await;
-                match tokio::time::timeout(SHUTDOWN_JOIN_TIMEOUT, &mut run_task).await {
+                match tokio::time::timeout(self.config.shutdown_join_timeout, &mut run_task).await {
                     Ok(joined) => {
                         if let Err(e) = flatten_join(joined) {
                             tracing::warn!("Pipeline errored during watchdog restart: {}", e);
@@ -137,7 +386,7 @@ impl<P: PipelineLifecycle + 'static, S: ResetSignal + 'static> Supervisor<P, S>
                         run_task.abort();
                         tracing::error!(
                             "Pipeline did not stop within {:?} after watchdog quit; abandoning it",
-                            SHUTDOWN_JOIN_TIMEOUT
+                            self.config.shutdown_join_timeout
                         );
                     }
                 }
@@ -153,10 +402,11 @@ impl<P: PipelineLifecycle + 'static, S: ResetSignal + 'static> Supervisor<P, S>
         // Bounded: the reset command shares the coordinator's single-threaded
         // queue, so a wedged coordinator could otherwise hang the restart loop
         // indefinitely (the shutdown path is already bounded; this one wasn't).
-        match tokio::time::timeout(RESET_TIMEOUT, self.signal.reset()).await {
+        let reset_timeout = self.config.reset_timeout;
+        match tokio::time::timeout(reset_timeout, self.signal.reset()).await {
             Ok(Ok(())) => {}
             Ok(Err(e)) => tracing::error!("Failed to reset signaling state: {}", e),
-            Err(_) => tracing::error!("Signaling reset timed out after {:?}", RESET_TIMEOUT),
+            Err(_) => tracing::error!("Signaling reset timed out after {:?}", reset_timeout),
         }
     }
 }
@@ -176,15 +426,43 @@ async fn wait_for_shutdown(shutdown: &mut watch::Receiver<bool>) {
     let _ = shutdown.wait_for(|&stop| stop).await;
 }
 
-fn backoff_delay(consecutive_failures: u32) -> Duration {
+fn backoff_delay(config: &SupervisorConfig, consecutive_failures: u32) -> Duration {
+    let delay = exponential_backoff(
+        config.base_restart_delay,
+        config.max_restart_delay,
+        consecutive_failures,
+    );
+    jittered(delay, config.restart_jitter)
+}
+
+/// `base` after a first failure, doubled for each further consecutive one,
+/// up to `max`. Shared with the push outputs' reconnects.
+pub(crate) fn exponential_backoff(
+    base: Duration,
+    max: Duration,
+    consecutive_failures: u32,
+) -> Duration {
     match consecutive_failures {
-        0 | 1 => BASE_RESTART_DELAY,
-        n => BASE_RESTART_DELAY
+        0 | 1 => base,
+        n => base
             .saturating_mul(2u32.saturating_pow(n - 1).min(64))
-            .min(MAX_RESTART_DELAY),
+            .min(max),
     }
 }
 
+/// `delay` scaled by a random factor in `[1 - jitter, 1 + jitter]`.
+fn jittered(delay: Duration, jitter: f64) -> Duration {
+    if jitter <= 0.0 {
+        return delay;
+    }
+    let mut bytes = [0; 4];
+    if SystemRandom::new().fill(&mut bytes).is_err() {
+        return delay;
+    }
+    let unit = f64::from(u32::from_le_bytes(bytes)) / f64::from(u32::MAX);
+    delay.mul_f64(1.0 + jitter.min(1.0) * (2.0 * unit - 1.0))
+}
+
 fn flatten_join(
     res: Result<Result<(), anyhow::Error>, tokio::task::JoinError>,
 ) -> Result<(), anyhow::Error> {
@@ -193,7 +471,11 @@ fn flatten_join(
 
 #[cfg(test)]
 mod tests {
-    use super::Supervisor;
+    use super::{
+        Supervisor, SupervisorConfig, SupervisorPhase, SupervisorReports, SupervisorStatus,
+    };
+    use crate::events::{Event, EventBus};
+    use crate::metrics::Metrics;
     use crate::signal::{ResetSignal, SignalError};
     use crate::stream::{TestPipeline, TestPipelineState};
     use async_trait::async_trait;
@@ -244,8 +526,22 @@ mod tests {
         (RecordingReset::default(), restart_tx, restart_rx)
     }
 
+    /// A status sender for tests that do not watch the phase.
+    fn status() -> watch::Sender<SupervisorStatus> {
+        watch::channel(SupervisorStatus::default()).0
+    }
+
+    /// Reports for tests that watch none of them.
+    fn reports() -> SupervisorReports {
+        SupervisorReports {
+            events: EventBus::default(),
+            metrics: Metrics::default(),
+            status: status(),
+        }
+    }
+
     /// Poll under the paused clock until `f` holds. 1000 × 10ms sleeps give
-    /// a 10s virtual-time budget — enough to cross RESET_TIMEOUT (5s) plus a
+    /// a 10s virtual-time budget — enough to cross the reset timeout (5s) plus a
     /// restart backoff, which the wedged-reset test needs.
     async fn wait_until(pipeline: &TestPipeline, f: impl Fn(&TestPipelineState) -> bool) {
         for _ in 0..1000 {
@@ -262,7 +558,14 @@ mod tests {
         let pipeline = TestPipeline::default();
         let (reset, _restart_tx, restart_rx) = wire();
         let (_shutdown_tx, shutdown_rx) = watch::channel(false);
-        let _sup = Supervisor::spawn(pipeline.clone(), reset, shutdown_rx, restart_rx);
+        let _sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx,
+            restart_rx,
+            reports(),
+            SupervisorConfig::default(),
+        );
 
         wait_until(&pipeline, |s| s.run_count == 1).await;
         pipeline.fail_run("gst blew up");
@@ -273,6 +576,46 @@ mod tests {
         assert_eq!(2, pipeline.snapshot().init_count);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn a_failed_run_is_published_with_its_backoff() {
+        let pipeline = TestPipeline::default();
+        let (reset, _restart_tx, restart_rx) = wire();
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let events = EventBus::default();
+        let mut rx = events.subscribe();
+        let metrics = Metrics::default();
+        let _sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx,
+            restart_rx,
+            SupervisorReports {
+                events,
+                metrics: metrics.clone(),
+                status: status(),
+            },
+            SupervisorConfig::default(),
+        );
+
+        wait_until(&pipeline, |s| s.run_count == 1).await;
+        pipeline.fail_run("gst blew up");
+        wait_until(&pipeline, |s| s.run_count == 2).await;
+
+        for expected in [
+            Event::PipelineStarted,
+            Event::PipelineError {
+                message: "gst blew up".to_string(),
+            },
+            Event::Backoff { delay_ms: 1000 },
+            Event::PipelineStarted,
+        ] {
+            assert_eq!(expected, rx.try_recv().unwrap());
+        }
+        let text = metrics.render();
+        assert!(text.contains("srt_whep_supervisor_restarts_total{cause=\"error\"} 1"));
+        assert!(text.contains("srt_whep_supervisor_backoff_seconds_count 1"));
+    }
+
     #[tokio::test(start_paused = true)]
     async fn reset_is_requested_after_every_stop() {
         // The supervisor's side of the restart contract: signaling is reset
@@ -284,7 +627,14 @@ mod tests {
         let pipeline = TestPipeline::default();
         let (reset, restart_tx, restart_rx) = wire();
         let (shutdown_tx, shutdown_rx) = watch::channel(false);
-        let sup = Supervisor::spawn(pipeline.clone(), reset.clone(), shutdown_rx, restart_rx);
+        let sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset.clone(),
+            shutdown_rx,
+            restart_rx,
+            reports(),
+            SupervisorConfig::default(),
+        );
         wait_until(&pipeline, |s| s.run_count == 1).await;
 
         pipeline.fail_run("gst blew up"); // stop 1: error
@@ -300,13 +650,13 @@ mod tests {
         assert_eq!(3, reset.count());
 
         shutdown_tx.send(true).unwrap(); // stop 4: shutdown
-        sup.await.unwrap();
+        sup.await.unwrap().unwrap();
         assert_eq!(4, reset.count());
     }
 
     #[tokio::test(start_paused = true)]
     async fn a_wedged_reset_does_not_hang_the_restart_loop() {
-        // RESET_TIMEOUT is the bound at the cleanup site: a coordinator that
+        // The reset timeout is the bound at the cleanup site: a coordinator that
         // never answers must not wedge the supervisor. The rerun still
         // happens once the timeout fires (paused clock — the 5s elapse
         // virtually).
@@ -314,7 +664,14 @@ mod tests {
         let reset = RecordingReset::wedged();
         let (_restart_tx, restart_rx) = mpsc::channel(1);
         let (_shutdown_tx, shutdown_rx) = watch::channel(false);
-        let _sup = Supervisor::spawn(pipeline.clone(), reset.clone(), shutdown_rx, restart_rx);
+        let _sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset.clone(),
+            shutdown_rx,
+            restart_rx,
+            reports(),
+            SupervisorConfig::default(),
+        );
         wait_until(&pipeline, |s| s.run_count == 1).await;
 
         pipeline.fail_run("gst blew up");
@@ -328,11 +685,24 @@ mod tests {
         let pipeline = TestPipeline::default();
         let (reset, _restart_tx, restart_rx) = wire();
         let (shutdown_tx, shutdown_rx) = watch::channel(false);
-        let sup = Supervisor::spawn(pipeline.clone(), reset, shutdown_rx, restart_rx);
+        let (status_tx, status_rx) = watch::channel(SupervisorStatus::default());
+        let sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx,
+            restart_rx,
+            SupervisorReports {
+                status: status_tx,
+                ..reports()
+            },
+            SupervisorConfig::default(),
+        );
         wait_until(&pipeline, |s| s.run_count == 1).await;
+        assert_eq!(SupervisorPhase::Running, status_rx.borrow().phase);
 
         shutdown_tx.send(true).unwrap();
-        sup.await.unwrap();
+        sup.await.unwrap().unwrap();
+        assert_eq!(SupervisorPhase::Stopped, status_rx.borrow().phase);
 
         let snap = pipeline.snapshot();
         assert_eq!(1, snap.end_count); // graceful EOS was requested
@@ -345,11 +715,18 @@ mod tests {
         let pipeline = TestPipeline::default();
         let (reset, _restart_tx, restart_rx) = wire();
         let (shutdown_tx, shutdown_rx) = watch::channel(false);
-        let sup = Supervisor::spawn(pipeline.clone(), reset, shutdown_rx, restart_rx);
+        let sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx,
+            restart_rx,
+            reports(),
+            SupervisorConfig::default(),
+        );
         wait_until(&pipeline, |s| s.run_count == 1).await;
 
         drop(shutdown_tx);
-        sup.await.unwrap();
+        sup.await.unwrap().unwrap();
         assert_eq!(1, pipeline.snapshot().cleanup_count);
     }
 
@@ -360,7 +737,14 @@ mod tests {
         let pipeline = TestPipeline::default();
         let (reset, restart_tx, restart_rx) = wire();
         let (_shutdown_tx, shutdown_rx) = watch::channel(false);
-        let _sup = Supervisor::spawn(pipeline.clone(), reset, shutdown_rx, restart_rx);
+        let _sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx,
+            restart_rx,
+            reports(),
+            SupervisorConfig::default(),
+        );
         wait_until(&pipeline, |s| s.run_count == 1).await;
 
         restart_tx.send(()).await.unwrap();
@@ -375,7 +759,14 @@ mod tests {
         let pipeline = TestPipeline::default();
         let (reset, _restart_tx, restart_rx) = wire();
         let (_shutdown_tx, shutdown_rx) = watch::channel(false);
-        let _sup = Supervisor::spawn(pipeline.clone(), reset, shutdown_rx, restart_rx);
+        let _sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx,
+            restart_rx,
+            reports(),
+            SupervisorConfig::default(),
+        );
 
         wait_until(&pipeline, |s| s.run_count == 1).await;
         let t0 = tokio::time::Instant::now();
@@ -409,4 +800,98 @@ mod tests {
             second_gap
         );
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_max_consecutive_failures_and_reports_status() {
+        let pipeline = TestPipeline::default();
+        let (reset, _restart_tx, restart_rx) = wire();
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (status_tx, status_rx) = watch::channel(SupervisorStatus::default());
+        let sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx,
+            restart_rx,
+            SupervisorReports {
+                status: status_tx,
+                ..reports()
+            },
+            SupervisorConfig {
+                max_consecutive_failures: Some(2),
+                ..SupervisorConfig::default()
+            },
+        );
+
+        wait_until(&pipeline, |s| s.run_count == 1).await;
+        pipeline.fail_run("1st");
+        wait_until(&pipeline, |s| s.run_count == 2).await;
+        let report = status_rx.borrow().report(tokio::time::Instant::now());
+        assert_eq!(SupervisorPhase::Running, report.phase);
+        assert_eq!(1, report.restarts);
+        assert_eq!(1, report.consecutive_failures);
+        assert_eq!(Some("1st".to_string()), report.last_error);
+        assert_eq!(Some(0), report.uptime_secs);
+        assert_eq!(None, report.backoff_remaining_ms);
+
+        pipeline.fail_run("2nd");
+        let gave_up = sup.await.unwrap().unwrap_err();
+        assert_eq!(2, gave_up.failures);
+        assert_eq!("2nd", gave_up.last_error);
+        assert_eq!(SupervisorPhase::Failed, status_rx.borrow().phase);
+        assert_eq!(2, pipeline.snapshot().run_count); // no third run
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_run_that_was_up_for_the_max_delay_starts_a_new_failure_streak() {
+        let pipeline = TestPipeline::default();
+        let (reset, _restart_tx, restart_rx) = wire();
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (status_tx, status_rx) = watch::channel(SupervisorStatus::default());
+        let _sup = Supervisor::spawn(
+            pipeline.clone(),
+            reset,
+            shutdown_rx,
+            restart_rx,
+            SupervisorReports {
+                status: status_tx,
+                ..reports()
+            },
+            SupervisorConfig {
+                max_consecutive_failures: Some(2),
+                ..SupervisorConfig::default()
+            },
+        );
+
+        wait_until(&pipeline, |s| s.run_count == 1).await;
+        pipeline.fail_run("1st");
+        wait_until(&pipeline, |s| s.run_count == 2).await;
+
+        // Healthy for longer than the 30s max delay, then failing: the
+        // count starts over instead of reaching the limit of 2.
+        tokio::time::sleep(Duration::from_secs(31)).await;
+        pipeline.fail_run("2nd");
+        wait_until(&pipeline, |s| s.run_count == 3).await;
+        assert_eq!(1, status_rx.borrow().consecutive_failures);
+        assert_eq!(SupervisorPhase::Running, status_rx.borrow().phase);
+    }
+
+    #[test]
+    fn jitter_stays_within_its_fraction() {
+        let config = SupervisorConfig {
+            restart_jitter: 0.5,
+            ..SupervisorConfig::default()
+        };
+        for _ in 0..100 {
+            let delay = super::backoff_delay(&config, 1);
+            assert!(
+                (Duration::from_millis(500)..=Duration::from_millis(1500)).contains(&delay),
+                "{:?}",
+                delay
+            );
+        }
+        assert_eq!(
+            Duration::from_secs(1),
+            super::backoff_delay(&SupervisorConfig::default(), 1)
+        );
+    }
 }
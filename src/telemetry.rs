@@ -1,11 +1,31 @@
+use opentelemetry::trace::{
+    SpanContext, SpanId, TraceContextExt, TraceError, TraceFlags, TraceId, TraceState,
+    TracerProvider as _,
+};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use ring::digest;
 use tracing::subscriber::set_global_default;
-use tracing::Subscriber;
+use tracing::{Span, Subscriber};
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_log::LogTracer;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Registry};
 
-/// Compose multiple layers into a `tracing`'s subscriber.
+/// CLI surface for trace export. Kept next to the subscriber it configures.
+#[derive(clap::Args, Debug, Clone)]
+pub struct TelemetryArgs {
+    /// OTLP/gRPC collector to export traces to, e.g. `http://localhost:4317`
+    /// (unset: logs only).
+    #[clap(long)]
+    pub otlp_endpoint: Option<String>,
+}
+
+/// Compose multiple layers into a `tracing`'s subscriber. With a `tracer`,
+/// spans are also exported over OTLP.
 ///
 /// # Implementation Notes
 ///
@@ -15,6 +35,7 @@ pub fn get_subscriber<Sink>(
     name: String,
     env_filter: String,
     sink: Sink,
+    tracer: Option<Tracer>,
 ) -> impl Subscriber + Sync + Send
 where
     Sink: for<'a> MakeWriter<'a> + Send + Sync + 'static,
@@ -22,10 +43,37 @@ where
     let env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(env_filter));
     let formatting_layer = BunyanFormattingLayer::new(name, sink);
+    let otel_layer = tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
     Registry::default()
         .with(env_filter)
         .with(JsonStorageLayer)
         .with(formatting_layer)
+        .with(otel_layer)
+}
+
+/// Build a batching OTLP/gRPC trace exporter for `endpoint` and register its
+/// provider globally, so `shutdown_tracing` can flush it on exit. Must be
+/// called from within the Tokio runtime.
+pub fn otlp_tracer(endpoint: &str, service_name: &str) -> Result<Tracer, TraceError> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            service_name.to_string(),
+        )]))
+        .build();
+    let tracer = provider.tracer("srt_whep");
+    opentelemetry::global::set_tracer_provider(provider);
+    Ok(tracer)
+}
+
+/// Flush spans still buffered by the OTLP exporter. A no-op without one.
+pub fn shutdown_tracing() {
+    opentelemetry::global::shutdown_tracer_provider();
 }
 
 /// Register a subscriber as global default to process span data.
@@ -35,3 +83,48 @@ pub fn init_subscriber(subscriber: impl Subscriber + Sync + Send) {
     LogTracer::init().expect("Failed to set logger");
     set_global_default(subscriber).expect("Failed to set subscriber");
 }
+
+/// Make `span` part of connection `id`'s trace.
+///
+/// The legs of one connection are independent HTTP requests — the loopback
+/// WHIP POST comes from the whipsink, which cannot propagate a trace
+/// context — so they are stitched by deriving the trace id (and a shared,
+/// never-exported parent span id) from the connection id itself: every span
+/// joined here lands in the same trace. A no-op without OTLP export.
+pub fn join_connection_trace(span: &Span, id: &str) {
+    span.set_parent(connection_context(id));
+}
+
+fn connection_context(id: &str) -> Context {
+    let hash = digest::digest(&digest::SHA256, id.as_bytes());
+    let bytes = hash.as_ref();
+    let mut trace_id = [0; 16];
+    trace_id.copy_from_slice(&bytes[..16]);
+    let mut span_id = [0; 8];
+    span_id.copy_from_slice(&bytes[16..24]);
+    let parent = SpanContext::new(
+        TraceId::from_bytes(trace_id),
+        SpanId::from_bytes(span_id),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    Context::new().with_remote_span_context(parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::connection_context;
+    use opentelemetry::trace::TraceContextExt;
+
+    #[test]
+    fn one_connection_maps_to_one_trace() {
+        let a = connection_context("a");
+        let again = connection_context("a");
+        let b = connection_context("b");
+        let trace = |cx: &opentelemetry::Context| cx.span().span_context().trace_id();
+        assert_eq!(trace(&a), trace(&again));
+        assert_ne!(trace(&a), trace(&b));
+        assert!(a.span().span_context().is_valid());
+    }
+}
@@ -0,0 +1,393 @@
+//! Outbound webhooks: signed JSON POSTs to operator-configured URLs for the
+//! lifecycle events worth paging or billing on.
+//!
+//! Delivered events, by their `type` tag:
+//! - `connection_established`, `connection_terminated` (reason + duration);
+//! - `stream_ready` (input up), `pipeline_eos` / `pipeline_error` (input down);
+//! - `pipeline_started` (supervisor (re)start), `watchdog_restart`.
+//!
+//! The dispatcher subscribes to the `EventBus` like `GET /events` does, so
+//! publishers never wait on it. Each target gets its own bounded queue and
+//! delivery task: a slow or dead receiver delays (and, once its queue is
+//! full, drops) only its own deliveries. Retries reuse the same body, so a
+//! receiver can deduplicate on its `id`.
+use crate::events::{Event, EventBus};
+use ring::hmac;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+const DEFAULT_QUEUE_CAPACITY: usize = 256;
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 1000;
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `sha256=<hex HMAC-SHA256 of the raw body, keyed with the secret>`.
+pub const SIGNATURE_HEADER: &str = "X-Srt-Whep-Signature";
+/// The event's `type` tag, so receivers can route without parsing the body.
+pub const EVENT_HEADER: &str = "X-Srt-Whep-Event";
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    /// Signing key; without one deliveries go out unsigned.
+    pub secret: Option<String>,
+    /// Deliveries a target may have queued before new ones are dropped.
+    pub queue_capacity: usize,
+    /// Attempts per delivery, the first included.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubles per attempt up to a minute.
+    pub retry_base_delay: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            secret: None,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            retry_base_delay: Duration::from_millis(DEFAULT_RETRY_BASE_DELAY_MS),
+        }
+    }
+}
+
+/// CLI surface for webhook delivery. No `--webhook-url` means no webhooks.
+#[derive(clap::Args, Debug, Clone)]
+pub struct WebhookArgs {
+    /// URL to POST lifecycle events to; repeat for several targets.
+    #[clap(long = "webhook-url")]
+    pub webhook_urls: Vec<String>,
+    /// Shared secret for the HMAC-SHA256 `X-Srt-Whep-Signature` header.
+    #[clap(long)]
+    pub webhook_secret: Option<String>,
+    /// Deliveries each target may have queued before new ones are dropped.
+    #[clap(long, default_value_t = DEFAULT_QUEUE_CAPACITY)]
+    pub webhook_queue_capacity: usize,
+    /// Delivery attempts per event before giving up.
+    #[clap(long, default_value_t = DEFAULT_MAX_ATTEMPTS)]
+    pub webhook_max_attempts: u32,
+    /// Milliseconds before the first retry; doubled for each further one.
+    #[clap(long, default_value_t = DEFAULT_RETRY_BASE_DELAY_MS)]
+    pub webhook_retry_delay_ms: u64,
+}
+
+impl WebhookArgs {
+    pub fn to_config(&self) -> WebhookConfig {
+        WebhookConfig {
+            urls: self.webhook_urls.clone(),
+            secret: self.webhook_secret.clone(),
+            queue_capacity: self.webhook_queue_capacity.max(1),
+            max_attempts: self.webhook_max_attempts.max(1),
+            retry_base_delay: Duration::from_millis(self.webhook_retry_delay_ms),
+        }
+    }
+}
+
+/// The JSON body of one delivery.
+#[derive(Serialize)]
+struct Envelope<'a> {
+    id: String,
+    timestamp_unix_ms: u64,
+    event: &'a Event,
+}
+
+/// One event, serialized and signed once, shared by every target's queue.
+struct Payload {
+    name: &'static str,
+    body: String,
+    signature: Option<String>,
+}
+
+impl Payload {
+    fn new(event: &Event, key: Option<&hmac::Key>) -> Self {
+        let envelope = Envelope {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp_unix_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            event,
+        };
+        let body = serde_json::to_string(&envelope).unwrap_or_else(|_| "{}".to_string());
+        let signature = key.map(|key| signature(key, body.as_bytes()));
+        Self {
+            name: event.name(),
+            body,
+            signature,
+        }
+    }
+}
+
+fn signature(key: &hmac::Key, body: &[u8]) -> String {
+    let tag = hmac::sign(key, body);
+    let hex: String = tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+fn is_delivered(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::ConnectionEstablished { .. }
+            | Event::ConnectionTerminated { .. }
+            | Event::StreamReady
+            | Event::PipelineEos
+            | Event::PipelineError { .. }
+            | Event::PipelineStarted
+            | Event::WatchdogRestart
+    )
+}
+
+/// Start delivering `events` to every configured URL. A no-op without URLs;
+/// fails only if the HTTP client cannot be built.
+pub fn spawn(config: WebhookConfig, events: &EventBus) -> Result<(), reqwest::Error> {
+    if config.urls.is_empty() {
+        return Ok(());
+    }
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    let key = config
+        .secret
+        .as_deref()
+        .map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()));
+    if key.is_none() {
+        tracing::warn!("No webhook secret configured: deliveries are unsigned");
+    }
+
+    let targets: Vec<_> = config
+        .urls
+        .iter()
+        .map(|url| {
+            let (tx, rx) = mpsc::channel(config.queue_capacity);
+            let target = Target {
+                client: client.clone(),
+                url: url.clone(),
+                max_attempts: config.max_attempts,
+                retry_base_delay: config.retry_base_delay,
+            };
+            tokio::spawn(target.run(rx));
+            (url.clone(), tx)
+        })
+        .collect();
+
+    let mut rx = events.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Webhook dispatcher missed {} events", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if !is_delivered(&event) {
+                continue;
+            }
+            let payload = Arc::new(Payload::new(&event, key.as_ref()));
+            for (url, queue) in &targets {
+                // Never wait on a target: a full queue drops this delivery.
+                if queue.try_send(payload.clone()).is_err() {
+                    tracing::warn!(
+                        "Webhook queue for {} is full; dropping {}",
+                        url,
+                        payload.name
+                    );
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+enum DeliveryError {
+    /// Worth retrying: network errors, timeouts, 5xx, 408 and 429.
+    Retryable(String),
+    /// The receiver rejected the delivery itself; retrying cannot help.
+    Permanent(String),
+}
+
+struct Target {
+    client: reqwest::Client,
+    url: String,
+    max_attempts: u32,
+    retry_base_delay: Duration,
+}
+
+impl Target {
+    /// Deliver queued payloads in order, each with its own retries.
+    async fn run(self, mut rx: mpsc::Receiver<Arc<Payload>>) {
+        while let Some(payload) = rx.recv().await {
+            self.deliver(&payload).await;
+        }
+    }
+
+    async fn deliver(&self, payload: &Payload) {
+        for attempt in 1..=self.max_attempts {
+            let error = match self.post(payload).await {
+                Ok(()) => return,
+                Err(DeliveryError::Permanent(e)) => {
+                    tracing::warn!("Webhook {} to {} rejected: {}", payload.name, self.url, e);
+                    return;
+                }
+                Err(DeliveryError::Retryable(e)) => e,
+            };
+            if attempt == self.max_attempts {
+                tracing::warn!(
+                    "Webhook {} to {} failed after {} attempts: {}",
+                    payload.name,
+                    self.url,
+                    attempt,
+                    error
+                );
+                return;
+            }
+            let delay = retry_delay(self.retry_base_delay, attempt);
+            tracing::debug!(
+                "Webhook {} to {} failed ({}); retrying in {:?}",
+                payload.name,
+                self.url,
+                error,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn post(&self, payload: &Payload) -> Result<(), DeliveryError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, payload.name)
+            .body(payload.body.clone());
+        if let Some(signature) = &payload.signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+        let status = request
+            .send()
+            .await
+            .map_err(|e| DeliveryError::Retryable(e.to_string()))?
+            .status();
+        if status.is_success() {
+            Ok(())
+        } else if status.is_client_error()
+            && status != reqwest::StatusCode::REQUEST_TIMEOUT
+            && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+        {
+            Err(DeliveryError::Permanent(status.to_string()))
+        } else {
+            Err(DeliveryError::Retryable(status.to_string()))
+        }
+    }
+}
+
+/// `base`, doubled for each retry after the first, capped at a minute.
+fn retry_delay(base: Duration, attempt: u32) -> Duration {
+    let exp = attempt.saturating_sub(1).min(16);
+    base.saturating_mul(1 << exp).min(MAX_RETRY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    #[test]
+    fn signature_is_hex_hmac_sha256_of_the_body() {
+        // RFC 4231, test case 2.
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"Jefe");
+        assert_eq!(
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            signature(&key, b"what do ya want for nothing?")
+        );
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        let base = Duration::from_secs(1);
+        assert_eq!(Duration::from_secs(1), retry_delay(base, 1));
+        assert_eq!(Duration::from_secs(4), retry_delay(base, 3));
+        assert_eq!(MAX_RETRY_DELAY, retry_delay(base, 10));
+    }
+
+    type Received = Arc<Mutex<Vec<(Option<String>, Option<String>, String)>>>;
+
+    /// Record every delivery; fail the first with a 503.
+    async fn receive(
+        req: HttpRequest,
+        body: String,
+        received: web::Data<Received>,
+    ) -> HttpResponse {
+        let header = |name| {
+            req.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let mut received = received.lock().unwrap();
+        received.push((header(EVENT_HEADER), header(SIGNATURE_HEADER), body));
+        if received.len() == 1 {
+            HttpResponse::ServiceUnavailable().finish()
+        } else {
+            HttpResponse::NoContent().finish()
+        }
+    }
+
+    #[tokio::test]
+    async fn selected_events_are_signed_and_retried() {
+        let received: Received = Arc::default();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let data = web::Data::new(received.clone());
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(data.clone())
+                .route("/hook", web::post().to(receive))
+        })
+        .listen(listener)
+        .unwrap()
+        .run();
+        tokio::spawn(server);
+
+        let bus = EventBus::default();
+        let config = WebhookConfig {
+            urls: vec![format!("http://127.0.0.1:{}/hook", port)],
+            secret: Some("s3cret".to_string()),
+            retry_base_delay: Duration::from_millis(10),
+            ..WebhookConfig::default()
+        };
+        spawn(config, &bus).unwrap();
+
+        bus.publish(Event::CapsDetected {
+            caps: "video/x-h264".to_string(),
+        }); // not a webhook event
+        bus.publish(Event::ConnectionEstablished {
+            id: "a".to_string(),
+        });
+
+        for _ in 0..200 {
+            if received.lock().unwrap().len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let received = received.lock().unwrap().clone();
+        assert_eq!(2, received.len(), "one failed attempt, one retry");
+        assert_eq!(received[0], received[1], "a retry resends the same body");
+
+        let (event, signed, body) = &received[1];
+        assert_eq!(Some("connection_established"), event.as_deref());
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"s3cret");
+        assert_eq!(Some(signature(&key, body.as_bytes())), *signed);
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!("connection_established", json["event"]["type"]);
+        assert_eq!("a", json["event"]["id"]);
+    }
+}
@@ -19,8 +19,11 @@
 use gst::prelude::*;
 use gstreamer as gst;
 use srt_whep::signal::CoordinatorConfig;
-use srt_whep::startup::Application;
-use srt_whep::stream::{Args, BranchControl, SRTMode, SharablePipeline};
+use srt_whep::startup::{AppConfig, Application};
+use srt_whep::stream::{
+    Args, BranchControl, PipelineLifecycle, PreflightMode, SRTMode, SharablePipeline,
+    TEST_SOURCE_ADDRESS,
+};
 use std::net::TcpListener;
 use std::time::Duration;
 
@@ -50,7 +53,7 @@ async fn pipeline_survives_repeated_handshake_failures() {
 
     let args = Args {
         input_address: format!("127.0.0.1:{}", SRT_PORT),
-        output_address: "127.0.0.1:9912".to_string(),
+        output_address: Vec::new(),
         srt_mode: SRTMode::Caller,
         srt_latency: 100,
         tsdemux_latency: 100,
@@ -58,6 +61,15 @@ async fn pipeline_survives_repeated_handshake_failures() {
         discoverer_timeout_sec: 5,
         port: HTTP_PORT,
         decode_video: false,
+        rist_receiver_buffer_ms: 1000,
+        rist_reorder_section_ms: 70,
+        rist_max_rtx_retries: 7,
+        whip_server_port: 8190,
+        whep_input_token: None,
+        srt_allow: Vec::new(),
+        srt_streamid: Vec::new(),
+        srt_passphrase: None,
+        preflight: PreflightMode::Strict,
     };
 
     let config = CoordinatorConfig {
@@ -67,6 +79,7 @@ async fn pipeline_survives_repeated_handshake_failures() {
         watchdog_window: Duration::from_secs(60),
         sweep_interval: Duration::from_millis(200),
         teardown_timeout: Duration::from_secs(5),
+        ..CoordinatorConfig::default()
     };
 
     let listener =
@@ -74,8 +87,11 @@ async fn pipeline_survives_repeated_handshake_failures() {
     // The production wiring: coordinator + supervisor + HTTP server.
     let (app, pipeline) = Application::assemble(
         listener,
-        |branch_failures| SharablePipeline::new(args.clone(), branch_failures),
-        config,
+        |branch_failures, events| SharablePipeline::new(args.clone(), branch_failures, events),
+        AppConfig {
+            coordinator: config,
+            ..AppConfig::default()
+        },
         Some(HTTP_PORT),
     )
     .unwrap();
@@ -96,7 +112,7 @@ async fn pipeline_survives_repeated_handshake_failures() {
         // Wait for the SRT input to be demuxed.
         let mut ready = false;
         for _ in 0..100 {
-            if pipeline.ready().await.unwrap_or(false) {
+            if pipeline.ready().await.is_ok() {
                 ready = true;
                 break;
             }
@@ -177,3 +193,44 @@ async fn pipeline_survives_repeated_handshake_failures() {
         std::process::exit(1);
     }
 }
+
+/// The built-in test source needs no network and no external process: the
+/// pipeline generates its own MPEG-TS and becomes ready on it.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore]
+async fn the_test_source_feeds_the_pipeline_without_an_srt_peer() {
+    let args = Args {
+        input_address: TEST_SOURCE_ADDRESS.to_string(),
+        output_address: Vec::new(),
+        srt_mode: SRTMode::Caller,
+        srt_latency: 0,
+        tsdemux_latency: 0,
+        run_discoverer: false,
+        discoverer_timeout_sec: 5,
+        port: HTTP_PORT,
+        decode_video: false,
+        rist_receiver_buffer_ms: 1000,
+        rist_reorder_section_ms: 70,
+        rist_max_rtx_retries: 7,
+        whip_server_port: 8190,
+        whep_input_token: None,
+        srt_allow: Vec::new(),
+        srt_streamid: Vec::new(),
+        srt_passphrase: None,
+        preflight: PreflightMode::Strict,
+    };
+    let (branch_failures, _failures) = tokio::sync::mpsc::channel(1);
+    let pipeline = SharablePipeline::new(args, branch_failures, Default::default());
+    pipeline.init().await.unwrap();
+
+    let mut ready = false;
+    for _ in 0..50 {
+        if pipeline.ready().await.is_ok() {
+            ready = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    pipeline.clean_up().await.unwrap();
+    assert!(ready, "the test source never made the pipeline ready");
+}
@@ -2,14 +2,16 @@ use once_cell::sync::Lazy;
 use reqwest::StatusCode;
 use srt_whep::domain::{VALID_WHEP_ANSWER, VALID_WHIP_OFFER};
 use srt_whep::signal::CoordinatorConfig;
-use srt_whep::startup::Application;
-use srt_whep::stream::{PipelineError, TestPipeline};
+use srt_whep::startup::{AdminConfig, AppConfig, Application, PublishConfig, ShutdownConfig};
+use srt_whep::stream::{
+    CallerInfo, InputStats, NotReadyReason, PipelineError, StreamStats, TestPipeline, ViewerStats,
+};
 use srt_whep::telemetry::{get_subscriber, init_subscriber};
 use std::net::TcpListener;
 use std::time::Duration;
 
 static TRACING: Lazy<()> = Lazy::new(|| {
-    let subscriber = get_subscriber("test".into(), "debug".into(), std::io::stdout);
+    let subscriber = get_subscriber("test".into(), "debug".into(), std::io::stdout, None);
     init_subscriber(subscriber);
 });
 
@@ -32,6 +34,7 @@ fn functional_config() -> CoordinatorConfig {
         watchdog_window: Duration::from_secs(60),
         sweep_interval: Duration::from_millis(50),
         teardown_timeout: Duration::from_secs(5),
+        ..CoordinatorConfig::default()
     }
 }
 
@@ -44,9 +47,13 @@ fn expiring_config(watchdog_threshold: u32) -> CoordinatorConfig {
         watchdog_window: Duration::from_secs(60),
         sweep_interval: Duration::from_millis(50),
         teardown_timeout: Duration::from_secs(5),
+        ..CoordinatorConfig::default()
     }
 }
 
+/// The bearer token every test app's admin API accepts.
+const ADMIN_TOKEN: &str = "test-admin-token";
+
 fn spawn_app(config: CoordinatorConfig) -> (String, TestPipeline) {
     Lazy::force(&TRACING);
     let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
@@ -57,12 +64,18 @@ fn spawn_app(config: CoordinatorConfig) -> (String, TestPipeline) {
     // trips — exactly like the real pipeline between restarts.
     let (app, pipeline) = Application::assemble(
         listener,
-        |branch_failures| {
+        |branch_failures, _events| {
             let pipeline = TestPipeline::new(branch_failures);
             pipeline.set_ready(true);
             pipeline
         },
-        config,
+        AppConfig {
+            coordinator: config,
+            admin: AdminConfig {
+                token: Some(ADMIN_TOKEN.to_string()),
+            },
+            ..AppConfig::default()
+        },
         None,
     )
     .expect("Failed to assemble app");
@@ -168,7 +181,7 @@ async fn non_empty_channel_post_is_rejected() {
 #[tokio::test]
 async fn not_ready_pipeline_returns_503_with_retry_after() {
     let (address, pipeline) = spawn_app(functional_config());
-    pipeline.set_ready(false);
+    pipeline.set_not_ready(NotReadyReason::AwaitingStreamInfo);
 
     let response = http_client()
         .post(format!("{}/channel", address))
@@ -177,6 +190,126 @@ async fn not_ready_pipeline_returns_503_with_retry_after() {
         .unwrap();
     assert_eq!(503, response.status());
     assert_eq!("3", response.headers()["Retry-After"].to_str().unwrap());
+    assert_eq!(
+        "application/problem+json",
+        response.headers()["Content-Type"].to_str().unwrap()
+    );
+    // The problem names the missing stage.
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("not_ready", problem["code"]);
+    assert_eq!("awaiting_stream_info", problem["reason"]);
+    assert_eq!(3, problem["retry_after"]);
+}
+
+#[tokio::test]
+async fn health_probes_report_liveness_and_pipeline_readiness() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let response = client
+        .get(format!("{}/healthz", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(200, response.status());
+    let health: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("ok", health["status"]);
+    assert_eq!("responsive", health["coordinator"]);
+
+    // Ready once the supervisor has the (ready) pipeline running.
+    let mut readiness = serde_json::Value::Null;
+    for _ in 0..50 {
+        let response = client
+            .get(format!("{}/readyz", address))
+            .send()
+            .await
+            .unwrap();
+        readiness = response.json().await.unwrap();
+        if readiness["status"] == "ready" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!("ready", readiness["status"]);
+    assert_eq!("running", readiness["supervisor"]);
+
+    // No input: not ready, but still alive.
+    pipeline.set_ready(false);
+    let response = client
+        .get(format!("{}/readyz", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(503, response.status());
+    let readiness: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("not_ready", readiness["pipeline"]);
+    assert_eq!("no_input", readiness["reason"]);
+
+    pipeline.set_not_ready(NotReadyReason::UnsupportedCodec {
+        caps: "video/x-vp8".to_string(),
+    });
+    let readiness: serde_json::Value = client
+        .get(format!("{}/readyz", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!("unsupported_codec", readiness["reason"]);
+    assert_eq!("video/x-vp8", readiness["caps"]);
+    let response = client
+        .get(format!("{}/healthz", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(200, response.status());
+}
+
+#[tokio::test]
+async fn status_reports_restarts_and_the_last_error() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let status = poll_status(&client, &address, |s| s["phase"] == "running").await;
+    assert_eq!(0, status["restarts"]);
+    assert_eq!(0, status["uptime_secs"]);
+    assert!(status["last_error"].is_null());
+    // No --push-url: no push outputs.
+    assert_eq!(serde_json::json!([]), status["pushes"]);
+
+    // A failed run: the supervisor backs off, then reruns.
+    pipeline.fail_run("gst blew up");
+    let status = poll_status(&client, &address, |s| {
+        s["restarts"] == 1 && s["phase"] == "running"
+    })
+    .await;
+    assert_eq!(1, status["consecutive_failures"]);
+    assert_eq!("gst blew up", status["last_error"]);
+    assert!(status["backoff_remaining_ms"].is_null());
+}
+
+/// Poll `GET /status` until `done` holds (for up to 5s), returning the last body.
+async fn poll_status(
+    client: &reqwest::Client,
+    address: &str,
+    done: impl Fn(&serde_json::Value) -> bool,
+) -> serde_json::Value {
+    let mut status = serde_json::Value::Null;
+    for _ in 0..250 {
+        let response = client
+            .get(format!("{}/status", address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, response.status());
+        status = response.json().await.unwrap();
+        if done(&status) {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("status never reached the expected state: {}", status);
 }
 
 #[tokio::test]
@@ -232,6 +365,20 @@ async fn unknown_ids_return_404() {
         .unwrap();
     assert_eq!(404, response.status());
 
+    let response = client
+        .get(format!("{}/channel/ghost", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(404, response.status());
+
+    let response = client
+        .get(format!("{}/channel/ghost/stats", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(404, response.status());
+
     // DELETE of an unknown id is idempotent (204), covered by delete_is_idempotent.
 }
 
@@ -391,6 +538,441 @@ async fn watchdog_restarts_pipeline_after_consecutive_failures() {
     assert_eq!(1, pipeline.snapshot().quit_count);
 }
 
+#[tokio::test]
+async fn admin_api_requires_its_token() {
+    let (address, _pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let response = client
+        .post(format!("{}/admin/restart", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+    assert_eq!("Bearer", response.headers()["WWW-Authenticate"]);
+
+    let response = client
+        .post(format!("{}/admin/restart", address))
+        .bearer_auth("wrong-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("unauthorized", problem["code"]);
+}
+
+#[tokio::test]
+async fn whip_publishing_is_refused_unless_the_input_is_whip() {
+    let (address, _pipeline) = spawn_app(functional_config());
+    let response = http_client()
+        .post(format!("{}/whip", address))
+        .header("Content-Type", "application/sdp")
+        .body(VALID_WHIP_OFFER)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("not_publishing", problem["code"]);
+}
+
+#[tokio::test]
+async fn whip_publishers_need_the_token_before_reaching_the_whip_server() {
+    Lazy::force(&TRACING);
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
+    // Nothing listens on the WHIP server's port: an admitted offer fails upstream.
+    let unused = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = format!("http://{}", unused.local_addr().unwrap());
+    drop(unused);
+    let (app, _pipeline) = Application::assemble(
+        listener,
+        |branch_failures, _events| TestPipeline::new(branch_failures),
+        AppConfig {
+            coordinator: functional_config(),
+            publish: PublishConfig {
+                token: Some("publisher-token".to_string()),
+                server: Some(server),
+                ..PublishConfig::default()
+            },
+            ..AppConfig::default()
+        },
+        None,
+    )
+    .expect("Failed to assemble app");
+    let address = format!("http://127.0.0.1:{}", app.port());
+    tokio::spawn(app.run_until_stopped(std::future::pending()));
+    let client = http_client();
+
+    let response = client
+        .post(format!("{}/whip", address))
+        .bearer_auth("wrong-token")
+        .body(VALID_WHIP_OFFER)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+    assert_eq!("Bearer", response.headers()["WWW-Authenticate"]);
+
+    let response = client
+        .post(format!("{}/whip", address))
+        .bearer_auth("publisher-token")
+        .body(VALID_WHIP_OFFER)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::BAD_GATEWAY, response.status());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("publish_failed", problem["code"]);
+}
+
+#[tokio::test]
+async fn admin_api_kicks_viewers_drains_and_restarts() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let first = complete_exchange(&address, &pipeline, 0).await;
+    let _second = complete_exchange(&address, &pipeline, 1).await;
+
+    // Kick one, then everyone left.
+    let response = client
+        .delete(format!("{}/admin/channel/{}", address, first))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::NO_CONTENT, response.status());
+    assert!(pipeline.snapshot().removed.contains(&first));
+    let response = client
+        .delete(format!("{}/admin/channel/{}", address, first))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+    let kicked: serde_json::Value = client
+        .delete(format!("{}/admin/channel", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, kicked["kicked"]);
+    assert_eq!(2, pipeline.snapshot().removed.len());
+
+    // Drain mode refuses new viewers until it is lifted.
+    let response = client
+        .post(format!("{}/admin/drain", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let response = client
+        .post(format!("{}/channel", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("draining", problem["code"]);
+    client
+        .delete(format!("{}/admin/drain", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    complete_exchange(&address, &pipeline, 2).await;
+
+    // A restart reaches the supervisor, which force-quits the pipeline.
+    let response = client
+        .post(format!("{}/admin/restart", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::ACCEPTED, response.status());
+    for _ in 0..200 {
+        if pipeline.snapshot().quit_count == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(1, pipeline.snapshot().quit_count);
+}
+
+#[tokio::test]
+async fn admin_api_reads_and_replaces_the_coordinator_config() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let mut config: serde_json::Value = client
+        .get(format!("{}/admin/config", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(5, config["offer_timeout_sec"]);
+    assert_eq!(50, config["sweep_interval_ms"]);
+
+    config["offer_timeout_sec"] = 8.into();
+    config["max_session_duration_sec"] = 3600.into();
+    let response = client
+        .put(format!("{}/admin/config", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&config)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let swapped: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(5, swapped["previous"]["offer_timeout_sec"]);
+    assert_eq!(8, swapped["current"]["offer_timeout_sec"]);
+    assert_eq!(3600, swapped["current"]["max_session_duration_sec"]);
+
+    // A config the coordinator cannot run with is refused whole.
+    config["sweep_interval_ms"] = 0.into();
+    let response = client
+        .put(format!("{}/admin/config", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&config)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("invalid_config", problem["code"]);
+    let response = client
+        .put(format!("{}/admin/config", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .body("{\"offer_timeout_sec\": \"soon\"}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+    let current: serde_json::Value = client
+        .get(format!("{}/admin/config", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(swapped["current"], current);
+    // Viewers are unaffected by the swap.
+    complete_exchange(&address, &pipeline, 0).await;
+}
+
+#[tokio::test]
+async fn admin_api_adds_lists_and_removes_passthrough_outputs() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+    let list = || {
+        client
+            .get(format!("{}/admin/outputs", address))
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+    };
+    let outputs: serde_json::Value = list().await.unwrap().json().await.unwrap();
+    assert_eq!(serde_json::json!([]), outputs);
+
+    let response = client
+        .post(format!("{}/admin/outputs", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&serde_json::json!({
+            "uri": "srt://10.0.0.7:9000?mode=caller&passphrase=0123456789abc"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::CREATED, response.status());
+    let added: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(0, added["id"]);
+    assert_eq!("attached", added["state"]);
+    // The passphrase never comes back out.
+    assert_eq!(
+        "srt://10.0.0.7:9000?mode=caller&passphrase=<redacted>",
+        added["uri"]
+    );
+    let outputs: serde_json::Value = list().await.unwrap().json().await.unwrap();
+    assert_eq!(serde_json::json!([added]), outputs);
+
+    // Refused like a bad --output-address.
+    for uri in [
+        "udp://10.0.0.7:9000",
+        "srt://10.0.0.7:9000?passphrase=short",
+    ] {
+        let response = client
+            .post(format!("{}/admin/outputs", address))
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&serde_json::json!({ "uri": uri }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, response.status(), "{uri}");
+    }
+
+    let remove = |id: usize| {
+        client
+            .delete(format!("{}/admin/outputs/{}", address, id))
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+    };
+    assert_eq!(StatusCode::NO_CONTENT, remove(0).await.unwrap().status());
+    let response = remove(0).await.unwrap();
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("output_not_found", problem["code"]);
+    assert!(pipeline.snapshot().outputs.is_empty());
+}
+
+#[tokio::test]
+async fn admin_api_lists_and_disconnects_the_callers_of_a_listener_output() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+    let response = client
+        .post(format!("{}/admin/outputs", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&serde_json::json!({
+            "uri": "srt://0.0.0.0:9000?mode=listener&streamid=decoder-a&streamid=decoder-b"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::CREATED, response.status());
+    let added: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(serde_json::json!([]), added["callers"]);
+
+    pipeline.connect_caller(
+        0,
+        CallerInfo {
+            id: 3,
+            address: "10.0.0.7:41000".to_string(),
+            streamid: Some("decoder-a".to_string()),
+        },
+    );
+    let outputs: serde_json::Value = client
+        .get(format!("{}/admin/outputs", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::json!([{
+            "id": 3,
+            "address": "10.0.0.7:41000",
+            "streamid": "decoder-a",
+        }]),
+        outputs[0]["callers"]
+    );
+
+    let disconnect = |output: usize, caller: usize| {
+        client
+            .delete(format!(
+                "{}/admin/outputs/{}/callers/{}",
+                address, output, caller
+            ))
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+    };
+    assert_eq!(
+        StatusCode::NO_CONTENT,
+        disconnect(0, 3).await.unwrap().status()
+    );
+    assert_eq!(Some(vec![]), pipeline.snapshot().outputs[0].callers);
+    for (output, caller) in [(0, 3), (1, 0)] {
+        let response = disconnect(output, caller).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        let problem: serde_json::Value = response.json().await.unwrap();
+        assert_eq!("caller_not_found", problem["code"]);
+    }
+}
+
+#[tokio::test]
+async fn stop_drains_viewers_before_stopping_the_pipeline() {
+    Lazy::force(&TRACING);
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
+    let (app, pipeline) = Application::assemble(
+        listener,
+        |branch_failures, _events| {
+            let pipeline = TestPipeline::new(branch_failures);
+            pipeline.set_ready(true);
+            pipeline
+        },
+        AppConfig {
+            coordinator: functional_config(),
+            shutdown: ShutdownConfig {
+                drain_grace: Duration::from_secs(1),
+            },
+            ..AppConfig::default()
+        },
+        None,
+    )
+    .expect("Failed to assemble app");
+    let address = format!("http://127.0.0.1:{}", app.port());
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let app_task = tokio::spawn(app.run_until_stopped(async move {
+        let _ = stop_rx.await;
+    }));
+    let client = http_client();
+
+    let leaving = complete_exchange(&address, &pipeline, 0).await;
+    let staying = complete_exchange(&address, &pipeline, 1).await;
+    stop_tx.send(()).unwrap();
+
+    // Draining: not ready, new viewers refused, current ones still served.
+    let mut readiness = serde_json::Value::Null;
+    for _ in 0..200 {
+        let response = client
+            .get(format!("{}/readyz", address))
+            .send()
+            .await
+            .unwrap();
+        readiness = response.json().await.unwrap();
+        if readiness["pipeline"] == "draining" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!("draining", readiness["pipeline"]);
+    let response = client
+        .post(format!("{}/channel", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("draining", problem["code"]);
+
+    let response = client
+        .delete(format!("{}/channel/{}", address, leaving))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::NO_CONTENT, response.status());
+    let snap = pipeline.snapshot();
+    assert!(!snap.removed.contains(&staying));
+    assert_eq!(0, snap.end_count);
+
+    // The grace period runs out: the remaining viewer is closed, then the
+    // pipeline is stopped.
+    app_task.await.unwrap().unwrap();
+    let snap = pipeline.snapshot();
+    assert!(snap.removed.contains(&staying));
+    assert_eq!(1, snap.end_count);
+}
+
 #[tokio::test]
 async fn whip_resource_location_is_routable_and_delete_removes_the_connection() {
     let (address, pipeline) = spawn_app(functional_config());
@@ -511,6 +1093,228 @@ async fn list_and_delete_manage_the_connection_lifecycle() {
     assert!(list.is_empty());
 }
 
+#[tokio::test]
+async fn channel_resource_and_list_filter_expose_connection_metadata() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let id = complete_exchange(&address, &pipeline, 0).await;
+
+    let info: serde_json::Value = client
+        .get(format!("{}/channel/{}", address, id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(id, info["id"]);
+    assert_eq!("established", info["state"]);
+    assert_eq!("127.0.0.1", info["remote_ip"]);
+    assert_eq!(serde_json::json!(["H264/90000"]), info["codecs"]);
+    assert!(info["created_unix_secs"].as_u64().unwrap() > 0);
+    assert!(info["age_secs"].is_u64());
+    assert!(info["state_secs"].is_u64());
+
+    // The list carries the same document, and filters by state.
+    let list: Vec<serde_json::Value> = client
+        .get(format!("{}/list?state=established", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, list.len());
+    assert_eq!(info["remote_ip"], list[0]["remote_ip"]);
+
+    let list: Vec<serde_json::Value> = client
+        .get(format!("{}/list?state=awaiting_offer", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(list.is_empty());
+}
+
+#[tokio::test]
+async fn stats_endpoints_report_per_viewer_and_aggregate_statistics() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let id = complete_exchange(&address, &pipeline, 0).await;
+    pipeline.set_viewer_stats(
+        &id,
+        ViewerStats {
+            bitrate_bps: None,
+            streams: vec![StreamStats {
+                kind: Some("video".to_string()),
+                bytes_sent: Some(5_000),
+                nack_count: Some(2),
+                round_trip_time: Some(0.02),
+                ..StreamStats::default()
+            }],
+        },
+    );
+
+    let stats: serde_json::Value = client
+        .get(format!("{}/channel/{}/stats", address, id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!("video", stats["streams"][0]["kind"]);
+    assert_eq!(2, stats["streams"][0]["nack_count"]);
+
+    let aggregate: serde_json::Value = client
+        .get(format!("{}/stats", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, aggregate["viewers"]);
+    assert_eq!(5_000, aggregate["bytes_sent"]);
+    assert_eq!(0.02, aggregate["mean_round_trip_time"]);
+    assert!(aggregate["per_viewer"][&id].is_object());
+}
+
+#[tokio::test]
+async fn input_stats_report_the_ingest_protocols_own_statistics() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let response = client
+        .get(format!("{}/input/stats", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(503, response.status().as_u16());
+
+    pipeline.set_input_stats(InputStats {
+        protocol: "rist".to_string(),
+        stats: Some(serde_json::json!({ "session-id": 1, "recovered": 4 })),
+        publishers: None,
+    });
+    let stats: serde_json::Value = client
+        .get(format!("{}/input/stats", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!("rist", stats["protocol"]);
+    assert_eq!(4, stats["stats"]["recovered"]);
+    assert!(stats.get("publishers").is_none());
+
+    // An SRT listener input names its publishers.
+    pipeline.set_input_stats(InputStats {
+        protocol: "srt".to_string(),
+        stats: None,
+        publishers: Some(vec![CallerInfo {
+            id: 0,
+            address: "198.51.100.4:50000".to_string(),
+            streamid: Some("studio-1".to_string()),
+        }]),
+    });
+    let stats: serde_json::Value = client
+        .get(format!("{}/input/stats", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::json!([{ "id": 0, "address": "198.51.100.4:50000", "streamid": "studio-1" }]),
+        stats["publishers"]
+    );
+}
+
+#[tokio::test]
+async fn metrics_endpoint_reports_signaling_and_http_counters() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let client = http_client();
+
+    let id = complete_exchange(&address, &pipeline, 0).await;
+    let response = client
+        .delete(format!("{}/channel/{}", address, id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(204, response.status().as_u16());
+    let response = client
+        .get(format!("{}/channel/ghost", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(404, response.status().as_u16());
+
+    let response = client
+        .get(format!("{}/metrics", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(200, response.status().as_u16());
+    assert!(response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    let text = response.text().await.unwrap();
+    for line in [
+        "srt_whep_connections{state=\"established\"} 0",
+        "srt_whep_handshake_duration_seconds_count{outcome=\"established\"} 1",
+        "srt_whep_connections_terminated_total{reason=\"deleted\"} 1",
+        "srt_whep_branch_attach_seconds_count 1",
+        "srt_whep_branch_detach_seconds_count 1",
+        "srt_whep_http_responses_total{method=\"DELETE\",route=\"/channel/{id}\",status=\"204\"} 1",
+        "srt_whep_http_responses_total{method=\"GET\",route=\"/channel/{id}\",status=\"404\"} 1",
+    ] {
+        assert!(
+            text.lines().any(|l| l == line),
+            "missing {:?} in\n{}",
+            line,
+            text
+        );
+    }
+}
+
+#[tokio::test]
+async fn events_stream_pushes_connection_transitions() {
+    let (address, pipeline) = spawn_app(functional_config());
+    let mut events = http_client()
+        .get(format!("{}/events", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(200, events.status());
+    assert_eq!(
+        "text/event-stream",
+        events.headers()["content-type"].to_str().unwrap()
+    );
+
+    let id = complete_exchange(&address, &pipeline, 0).await;
+
+    let mut received = String::new();
+    while !received.contains("event: connection_established") {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), events.chunk())
+            .await
+            .expect("no event within 5s")
+            .unwrap()
+            .expect("event stream ended");
+        received.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+    assert!(received.contains("event: connection_created"));
+    assert!(received.contains("event: offer_delivered"));
+    assert!(received.contains(&format!("\"id\":\"{}\"", id)));
+}
+
 #[tokio::test]
 async fn a_branch_runtime_failure_reaps_the_established_connection() {
     let (address, pipeline) = spawn_app(functional_config());
@@ -561,10 +1365,13 @@ async fn assemble_rejects_a_mismatched_whip_port() {
     let wrong = bound.checked_add(1).unwrap_or(bound - 1);
     let result = Application::assemble(
         listener,
-        |_branch_failures| -> TestPipeline {
+        |_branch_failures, _events| -> TestPipeline {
             unreachable!("assembly fails the port check before constructing the pipeline")
         },
-        functional_config(),
+        AppConfig {
+            coordinator: functional_config(),
+            ..AppConfig::default()
+        },
         Some(wrong),
     );
     assert!(result.is_err(), "mismatched whip port must fail assembly");